@@ -1,18 +1,24 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{FromSample, Sample};
-use rodio::{OutputStream, Sink, Source};
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::BufWriter;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
-use crate::logic::{set_status, AppStatus};
+use crate::dsp::DspChain;
+use crate::error::{AppError, AppResult};
+use crate::models::AudioProcessingSettings;
 
 pub struct AudioState {
-    pub stop_sender: Arc<Mutex<Option<std::sync::mpsc::Sender<()>>>>,
+    pub stop_sender: Arc<Mutex<Option<Sender<Control>>>>,
     pub recording_active: Arc<Mutex<bool>>,
+    /// Receives the result of finalizing the current recording's WAV file
+    pub finished: Arc<Mutex<Option<Receiver<AppResult<()>>>>>,
 }
 
 impl Default for AudioState {
@@ -26,10 +32,117 @@ impl AudioState {
         Self {
             stop_sender: Arc::new(Mutex::new(None)),
             recording_active: Arc::new(Mutex::new(false)),
+            finished: Arc::new(Mutex::new(None)),
         }
     }
 }
 
+/// Messages to the recording thread
+pub enum Control {
+    Stop,
+    /// Drop incoming audio until `Resume`; paused time does not count toward the timeout
+    Pause,
+    Resume,
+    /// The stream opened as `generation` reported an error
+    StreamError {
+        generation: u32,
+        error: String,
+    },
+}
+
+/// How a recording is made
+#[derive(Debug, Clone)]
+pub struct RecordingOptions {
+    /// Input device; `None` records from the default device
+    pub device: Option<String>,
+    pub timeout: Duration,
+    /// How long before the timeout `on_timeout_warning` runs; zero disables it
+    pub timeout_warning: Duration,
+    pub processing: AudioProcessingSettings,
+    /// Continue on the default input device when the device fails
+    pub continue_on_default_device: bool,
+}
+
+/// Runs when a recording reaches its timeout
+pub type TimeoutHandler = Box<dyn FnOnce() + Send>;
+
+/// Runs once a recording is close to its timeout, with the time left
+pub type TimeoutWarningHandler = Box<dyn FnMut(Duration) + Send>;
+
+/// Runs when the input device fails during a recording
+pub type InterruptionHandler = Box<dyn FnMut(Interruption) + Send>;
+
+/// Callbacks of one recording, run on the recording thread
+pub struct RecordingHandlers {
+    pub on_timeout: TimeoutHandler,
+    pub on_timeout_warning: TimeoutWarningHandler,
+    pub on_interrupted: InterruptionHandler,
+}
+
+impl Default for RecordingHandlers {
+    fn default() -> Self {
+        Self {
+            on_timeout: Box::new(|| {}),
+            on_timeout_warning: Box::new(|_| {}),
+            on_interrupted: Box::new(|_| {}),
+        }
+    }
+}
+
+/// The input device failed during a recording. The audio recorded up to the
+/// failure is kept in the WAV file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interruption {
+    /// Device that failed
+    pub device: String,
+    pub error: String,
+    /// Position in the recording where audio is missing
+    pub at_ms: u64,
+    /// Device the recording continues on; `None` if the recording ended
+    #[serde(default)]
+    pub resumed_on: Option<String>,
+    /// Audio lost until the recording continued
+    #[serde(default)]
+    pub gap_ms: u64,
+}
+
+/// Starts and stops recordings for the state machine. `AudioState` records from
+/// the microphone; tests use `mock::MockRecorder`.
+pub trait Recorder: Send + Sync {
+    fn start(
+        &self,
+        path: PathBuf,
+        options: RecordingOptions,
+        handlers: RecordingHandlers,
+    ) -> AppResult<()>;
+
+    /// Stops the current recording. Returns once its file is complete, with an
+    /// error if the file could not be finalized.
+    fn stop(&self) -> AppResult<()>;
+
+    /// Pauses or resumes the current recording.
+    fn set_paused(&self, paused: bool);
+}
+
+impl Recorder for AudioState {
+    fn start(
+        &self,
+        path: PathBuf,
+        options: RecordingOptions,
+        handlers: RecordingHandlers,
+    ) -> AppResult<()> {
+        start_recording(self, path, options, handlers).map(|_| ())
+    }
+
+    fn stop(&self) -> AppResult<()> {
+        stop_recording(self)
+    }
+
+    fn set_paused(&self, paused: bool) {
+        set_recording_paused(self, paused);
+    }
+}
+
 pub fn list_input_devices() -> Vec<String> {
     let host = cpal::default_host();
     match host.input_devices() {
@@ -38,206 +151,655 @@ pub fn list_input_devices() -> Vec<String> {
     }
 }
 
-pub fn play_sound(name: &str) {
-    let name = name.to_string(); // Own it
-    std::thread::spawn(move || {
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&stream_handle).unwrap();
+// How long `stop_recording` waits for the recording thread to finalize the file
+const FINALIZE_TIMEOUT: Duration = Duration::from_secs(5);
 
-        match name.as_str() {
-            "Ping" => {
-                let source = rodio::source::SineWave::new(880.0) // A5
-                    .take_duration(Duration::from_millis(200))
-                    .amplify(0.20);
-                sink.append(source);
-            }
-            "Click" => {
-                let source = rodio::source::SineWave::new(1200.0)
-                    .take_duration(Duration::from_millis(50))
-                    .amplify(0.10);
-                sink.append(source);
-            }
-            "Swoosh" => {
-                // Approximate with a lower freq
-                let source = rodio::source::SineWave::new(400.0)
-                    .take_duration(Duration::from_millis(300))
-                    .amplify(0.20);
-                sink.append(source);
-            }
-            "Success" => {
-                let s1 = rodio::source::SineWave::new(523.25)
-                    .take_duration(Duration::from_millis(150))
-                    .amplify(0.20);
-                let s2 = rodio::source::SineWave::new(659.25)
-                    .take_duration(Duration::from_millis(150))
-                    .amplify(0.20);
-                let s3 = rodio::source::SineWave::new(783.99)
-                    .take_duration(Duration::from_millis(300))
-                    .amplify(0.20);
-                sink.append(s1);
-                sink.append(s2);
-                sink.append(s3);
+// Attempts to continue on the default device after the input device failed;
+// the system needs a moment to switch its default device
+const RESUME_ATTEMPTS: u32 = 3;
+const RESUME_DELAY: Duration = Duration::from_millis(500);
+
+type SharedWriter = Arc<Mutex<Option<hound::WavWriter<BufWriter<File>>>>>;
+
+/// Connects the callbacks of a stream to the recording thread
+#[derive(Clone)]
+struct StreamControl {
+    errors: Sender<Control>,
+    /// Identifies the stream in its error reports
+    generation: u32,
+    /// Incoming audio is dropped while set
+    paused: Arc<AtomicBool>,
+}
+
+/// Time left until a recording times out; paused time does not count.
+struct RecordingClock {
+    deadline: Instant,
+    paused_at: Option<Instant>,
+    /// How long before the deadline to warn, until the warning was given
+    warning: Option<Duration>,
+}
+
+impl RecordingClock {
+    fn new(now: Instant, timeout: Duration, warning: Duration) -> Self {
+        Self {
+            deadline: now + timeout,
+            paused_at: None,
+            // A warning as long as the recording would come right away
+            warning: Some(warning).filter(|w| !w.is_zero() && *w < timeout),
+        }
+    }
+
+    /// Returns false if the clock was already paused.
+    fn pause(&mut self, now: Instant) -> bool {
+        if self.paused_at.is_some() {
+            return false;
+        }
+        self.paused_at = Some(now);
+        true
+    }
+
+    /// Returns false if the clock was not paused.
+    fn resume(&mut self, now: Instant) -> bool {
+        match self.paused_at.take() {
+            Some(paused_at) => {
+                self.deadline += now.saturating_duration_since(paused_at);
+                true
             }
-            _ => {}
+            None => false,
         }
+    }
 
-        sink.sleep_until_end();
-    });
+    /// Time until the timeout, or `None` while paused
+    fn remaining(&self, now: Instant) -> Option<Duration> {
+        match self.paused_at {
+            Some(_) => None,
+            None => Some(self.deadline.saturating_duration_since(now)),
+        }
+    }
+
+    /// Time until the pending warning, or until the timeout once it was given;
+    /// `None` while paused
+    fn next_wakeup(&self, now: Instant) -> Option<Duration> {
+        let remaining = self.remaining(now)?;
+        Some(match self.warning {
+            Some(warning) => remaining.saturating_sub(warning),
+            None => remaining,
+        })
+    }
+
+    /// Called when `next_wakeup` elapsed. Returns true if that was the warning,
+    /// false if the recording timed out.
+    fn take_warning(&mut self) -> bool {
+        self.warning.take().is_some()
+    }
 }
 
+/// Records from the input device into a WAV file until `stop_recording` is called
+/// or the timeout elapses, in which case `on_timeout` runs; `on_timeout_warning`
+/// runs `timeout_warning` before that. When the device fails,
+/// the audio recorded so far is kept and `on_interrupted` runs, after the
+/// recording moved to the default device if `continue_on_default_device` is set.
+/// While paused, incoming audio is dropped, so the file holds one continuous
+/// recording. Returns once the device is recording, or with the reason it could
+/// not be opened. The file is finalized before the handlers run and before
+/// `stop_recording` returns; the returned thread ends after that.
 pub fn start_recording(
     state: &AudioState,
-    device_name: Option<String>,
     output_path: PathBuf,
-    timeout_minutes: u32,
-    app: AppHandle,
-) -> Result<(), String> {
+    options: RecordingOptions,
+    handlers: RecordingHandlers,
+) -> AppResult<JoinHandle<()>> {
     let (tx, rx) = std::sync::mpsc::channel();
+    // Reports whether the stream was started, before anything is recorded
+    let (ready_tx, ready_rx) = std::sync::mpsc::sync_channel(1);
+    // Stream errors reach the thread through the same channel as the stop signal
+    let mut control = StreamControl {
+        errors: tx.clone(),
+        generation: 0,
+        paused: Arc::new(AtomicBool::new(false)),
+    };
+    let (done_tx, done_rx) = std::sync::mpsc::sync_channel(1);
 
     // Store sender first to ensure we can stop it
     {
         let mut sender_guard = state.stop_sender.lock().unwrap();
         *sender_guard = Some(tx);
     }
+    *state.finished.lock().unwrap() = Some(done_rx);
     {
         let mut recording = state.recording_active.lock().unwrap();
         *recording = true;
     }
 
-    let timeout_duration = Duration::from_secs(timeout_minutes as u64 * 60);
     let recording_active = state.recording_active.clone();
 
-    // Spawn thread to handle stream lifetime
-    std::thread::spawn(move || {
-        let host = cpal::default_host();
-
-        // Find device
-        let device = if let Some(name) = device_name {
-            host.input_devices()
-                .ok()
-                .and_then(|mut d| d.find(|x| x.name().unwrap_or_default() == name))
-        } else {
-            host.default_input_device()
+    // Spawn thread to handle stream lifetime; cpal streams cannot be moved between threads
+    let handle = std::thread::spawn(move || {
+        let RecordingHandlers {
+            on_timeout,
+            mut on_timeout_warning,
+            mut on_interrupted,
+        } = handlers;
+        let mut input = match open_input_stream(
+            options.device.as_deref(),
+            &output_path,
+            false,
+            &options.processing,
+            &control,
+        ) {
+            Ok(input) => input,
+            Err(e) => {
+                let _ = ready_tx.send(Err(e));
+                return;
+            }
         };
+        let _ = ready_tx.send(Ok(()));
 
-        if let Some(device) = device {
-            // Try 16kHz config first for Whisper compatibility
-            let desired_sample_rate = cpal::SampleRate(16000);
-            let config = device
-                .supported_input_configs()
-                .ok()
-                .and_then(|mut configs| {
-                    // Try to find exact 16kHz support
-                    configs
-                        .find(|c| {
-                            c.min_sample_rate() <= desired_sample_rate
-                                && c.max_sample_rate() >= desired_sample_rate
-                        })
-                        .map(|c| c.with_sample_rate(desired_sample_rate))
-                })
-                .or_else(|| device.default_input_config().ok());
-
-            if let Some(config) = config {
-                let spec = hound::WavSpec {
-                    channels: config.channels(),
-                    sample_rate: config.sample_rate().0,
-                    bits_per_sample: 16,
-                    sample_format: hound::SampleFormat::Int,
-                };
-
-                if let Ok(writer) = hound::WavWriter::create(output_path, spec) {
-                    let writer = Arc::new(Mutex::new(Some(writer)));
-                    let writer_2 = writer.clone();
-
-                    let err_fn = move |err| {
-                        eprintln!("an error occurred on stream: {}", err);
-                    };
+        // Wait for stop signal, timeout or device failure, then stop recording and
+        // finalize the file before anyone looks at it
+        let mut clock =
+            RecordingClock::new(Instant::now(), options.timeout, options.timeout_warning);
+        let (finalized, ending) = loop {
+            let message = match clock.next_wakeup(Instant::now()) {
+                Some(wait) => rx.recv_timeout(wait),
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match message {
+                Ok(Control::Pause) => {
+                    if clock.pause(Instant::now()) {
+                        control.paused.store(true, Ordering::Relaxed);
+                    }
+                }
+                Ok(Control::Resume) => {
+                    if clock.resume(Instant::now()) {
+                        control.paused.store(false, Ordering::Relaxed);
+                    }
+                }
+                Ok(Control::StreamError {
+                    generation: failed,
+                    error,
+                }) if failed == control.generation => {
+                    let failed_at = Instant::now();
+                    let device = input.device.clone();
+                    let closed = input.close();
+                    let at_ms = *closed.as_ref().unwrap_or(&0);
+                    log::warn!(
+                        "Recording from {} failed at {} ms: {}",
+                        device,
+                        at_ms,
+                        error
+                    );
 
-                    let stream_res = match config.sample_format() {
-                        cpal::SampleFormat::F32 => device.build_input_stream(
-                            &config.into(),
-                            move |data: &[f32], _: &_| {
-                                write_input_data::<f32, i16>(data, &writer_2)
-                            },
-                            err_fn,
-                            None,
-                        ),
-                        cpal::SampleFormat::I16 => device.build_input_stream(
-                            &config.into(),
-                            move |data: &[i16], _: &_| {
-                                write_input_data::<i16, i16>(data, &writer_2)
-                            },
-                            err_fn,
-                            None,
-                        ),
-                        cpal::SampleFormat::U16 => device.build_input_stream(
-                            &config.into(),
-                            move |data: &[u16], _: &_| {
-                                write_input_data::<u16, i16>(data, &writer_2)
-                            },
-                            err_fn,
-                            None,
-                        ),
-                        _ => Err(cpal::BuildStreamError::StreamConfigNotSupported),
+                    control.generation += 1;
+                    let resumed = if options.continue_on_default_device {
+                        resume_on_default_device(&output_path, &options.processing, &control)
+                    } else {
+                        None
                     };
-
-                    if let Ok(stream) = stream_res {
-                        if stream.play().is_ok() {
-                            // Wait for stop signal or timeout
-                            let recv_result = rx.recv_timeout(timeout_duration);
-
-                            // Check if it was a timeout
-                            if recv_result.is_err() {
-                                // Timeout occurred
-                                let mut recording = recording_active.lock().unwrap();
-                                *recording = false;
-
-                                // Reset backend state to Idle
-                                set_status(&app, AppStatus::Idle);
-
-                                // Emit timeout event
-                                let _ = app.emit(
-                                    "recording-timeout",
-                                    "Recording exceeded maximum duration",
-                                );
-
-                                // Play error sound
-                                play_sound("Click");
-                            }
-                            // Stream drops here, stopping recording (either on signal or timeout)
+                    let interruption = Interruption {
+                        device,
+                        error,
+                        at_ms,
+                        resumed_on: resumed.as_ref().map(|input| input.device.clone()),
+                        gap_ms: match resumed {
+                            Some(_) => failed_at.elapsed().as_millis() as u64,
+                            None => 0,
+                        },
+                    };
+                    match resumed {
+                        Some(resumed) => {
+                            input = resumed;
+                            on_interrupted(interruption);
+                        }
+                        None => {
+                            break (closed.map(|_| ()), Ending::Interrupted(interruption));
                         }
                     }
                 }
+                // Late errors of a stream that was already replaced
+                Ok(Control::StreamError { .. }) => {}
+                Ok(Control::Stop) | Err(RecvTimeoutError::Disconnected) => {
+                    break (input.close().map(|_| ()), Ending::Stopped)
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if clock.take_warning() {
+                        let remaining = clock.remaining(Instant::now()).unwrap_or_default();
+                        on_timeout_warning(remaining);
+                        continue;
+                    }
+                    break (input.close().map(|_| ()), Ending::TimedOut);
+                }
+            }
+        };
+
+        if let Err(e) = &finalized {
+            log::error!("{}", e);
+        }
+        let _ = done_tx.send(finalized);
+
+        match ending {
+            Ending::Stopped => {}
+            Ending::TimedOut => {
+                *recording_active.lock().unwrap() = false;
+                on_timeout();
+            }
+            Ending::Interrupted(interruption) => {
+                *recording_active.lock().unwrap() = false;
+                on_interrupted(interruption);
             }
         }
     });
 
-    Ok(())
+    let result = ready_rx.recv().unwrap_or_else(|_| {
+        Err(AppError::Internal(
+            "The recording thread exited unexpectedly".to_string(),
+        ))
+    });
+    match result {
+        Ok(()) => Ok(handle),
+        Err(e) => {
+            // Nothing is recording, so there is nothing to stop later
+            state.stop_sender.lock().unwrap().take();
+            state.finished.lock().unwrap().take();
+            *state.recording_active.lock().unwrap() = false;
+            Err(e)
+        }
+    }
+}
+
+/// Why the recording thread stopped recording
+enum Ending {
+    Stopped,
+    TimedOut,
+    Interrupted(Interruption),
 }
 
-pub fn stop_recording(state: &AudioState) {
-    let mut sender_guard = state.stop_sender.lock().unwrap();
-    if let Some(tx) = sender_guard.take() {
-        let _ = tx.send(()); // Signal thread to exit
+/// A running input stream and the WAV file it writes to
+struct InputStream {
+    stream: cpal::Stream,
+    writer: SharedWriter,
+    device: String,
+}
+
+impl InputStream {
+    /// Stops the stream and finalizes the WAV file, writing its header. Returns
+    /// the length of the recording in milliseconds.
+    fn close(self) -> AppResult<u64> {
+        drop(self.stream);
+        let writer = self.writer.lock().ok().and_then(|mut writer| writer.take());
+        let Some(writer) = writer else {
+            return Ok(0);
+        };
+        let length_ms = writer.duration() as u64 * 1000 / writer.spec().sample_rate.max(1) as u64;
+        writer
+            .finalize()
+            .map_err(|e| AppError::Storage(format!("Failed to finalize the recording: {}", e)))?;
+        Ok(length_ms)
     }
+}
 
-    let mut recording = state.recording_active.lock().unwrap();
-    *recording = false;
+/// Opens the input device and starts a stream writing to `output_path`. With
+/// `append` the stream continues the existing file in its format.
+fn open_input_stream(
+    device_name: Option<&str>,
+    output_path: &Path,
+    append: bool,
+    processing: &AudioProcessingSettings,
+    control: &StreamControl,
+) -> AppResult<InputStream> {
+    let host = cpal::default_host();
+
+    // Find device
+    let device = match device_name {
+        Some(name) => host
+            .input_devices()
+            .map_err(|e| AppError::AudioDevice(e.to_string()))?
+            .find(|x| x.name().unwrap_or_default() == name),
+        None => host.default_input_device(),
+    };
+    let device = device.ok_or_else(|| {
+        AppError::InputDeviceNotFound(device_name.unwrap_or("default").to_string())
+    })?;
+    let device_label = device.name().unwrap_or_else(|_| "default".to_string());
+
+    let storage_error = |e: hound::Error| AppError::Storage(format!("{:?}: {}", output_path, e));
+    let (writer, config) = if append {
+        let writer = hound::WavWriter::append(output_path).map_err(storage_error)?;
+        let config = matching_input_config(&device, writer.spec())?;
+        (writer, config)
+    } else {
+        let config = preferred_input_config(&device)?;
+        let spec = hound::WavSpec {
+            channels: config.channels(),
+            sample_rate: config.sample_rate().0,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let writer = hound::WavWriter::create(output_path, spec).map_err(storage_error)?;
+        (writer, config)
+    };
+
+    let spec = writer.spec();
+    let input_channels = config.channels();
+    let writer = Arc::new(Mutex::new(Some(writer)));
+    let callback_writer = writer.clone();
+
+    // Optional preprocessing, owned by the stream callback
+    let mut dsp = DspChain::new(processing, spec.sample_rate, spec.channels);
+
+    let paused = control.paused.clone();
+
+    // The recording thread decides whether the recording ends or continues
+    let errors = control.errors.clone();
+    let generation = control.generation;
+    let err_fn = move |err: cpal::StreamError| {
+        let _ = errors.send(Control::StreamError {
+            generation,
+            error: err.to_string(),
+        });
+    };
+
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &config.into(),
+            move |data: &[f32], _: &_| {
+                write_input_data::<f32, i16>(
+                    data,
+                    input_channels,
+                    &paused,
+                    &callback_writer,
+                    &mut dsp,
+                )
+            },
+            err_fn,
+            None,
+        ),
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[i16], _: &_| {
+                write_input_data::<i16, i16>(
+                    data,
+                    input_channels,
+                    &paused,
+                    &callback_writer,
+                    &mut dsp,
+                )
+            },
+            err_fn,
+            None,
+        ),
+        cpal::SampleFormat::U16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[u16], _: &_| {
+                write_input_data::<u16, i16>(
+                    data,
+                    input_channels,
+                    &paused,
+                    &callback_writer,
+                    &mut dsp,
+                )
+            },
+            err_fn,
+            None,
+        ),
+        _ => Err(cpal::BuildStreamError::StreamConfigNotSupported),
+    }
+    .map_err(|e| AppError::AudioDevice(e.to_string()))?;
+
+    stream
+        .play()
+        .map_err(|e| AppError::AudioDevice(e.to_string()))?;
+    Ok(InputStream {
+        stream,
+        writer,
+        device: device_label,
+    })
+}
+
+fn preferred_input_config(device: &cpal::Device) -> AppResult<cpal::SupportedStreamConfig> {
+    // Try 16kHz config first for Whisper compatibility
+    let desired_sample_rate = cpal::SampleRate(16000);
+    match device
+        .supported_input_configs()
+        .ok()
+        .and_then(|mut configs| {
+            // Try to find exact 16kHz support
+            configs
+                .find(|c| {
+                    c.min_sample_rate() <= desired_sample_rate
+                        && c.max_sample_rate() >= desired_sample_rate
+                })
+                .map(|c| c.with_sample_rate(desired_sample_rate))
+        }) {
+        Some(config) => Ok(config),
+        None => device
+            .default_input_config()
+            .map_err(|e| AppError::AudioDevice(e.to_string())),
+    }
+}
+
+/// Finds a config recording at the sample rate of `spec`, preferably with the
+/// same number of channels.
+fn matching_input_config(
+    device: &cpal::Device,
+    spec: hound::WavSpec,
+) -> AppResult<cpal::SupportedStreamConfig> {
+    let sample_rate = cpal::SampleRate(spec.sample_rate);
+    device
+        .supported_input_configs()
+        .map_err(|e| AppError::AudioDevice(e.to_string()))?
+        .filter(|c| c.min_sample_rate() <= sample_rate && c.max_sample_rate() >= sample_rate)
+        .min_by_key(|c| c.channels() != spec.channels)
+        .map(|c| c.with_sample_rate(sample_rate))
+        .ok_or_else(|| {
+            AppError::AudioDevice(format!(
+                "The device cannot record at {} Hz",
+                spec.sample_rate
+            ))
+        })
+}
+
+/// Opens the default input device to continue the recording in `output_path`.
+fn resume_on_default_device(
+    output_path: &Path,
+    processing: &AudioProcessingSettings,
+    control: &StreamControl,
+) -> Option<InputStream> {
+    for attempt in 1..=RESUME_ATTEMPTS {
+        std::thread::sleep(RESUME_DELAY);
+        match open_input_stream(None, output_path, true, processing, control) {
+            Ok(input) => return Some(input),
+            Err(e) => log::warn!(
+                "Attempt {} to continue on the default device failed: {}",
+                attempt,
+                e
+            ),
+        }
+    }
+    None
+}
+
+/// Stops the current recording and waits until its WAV file is finalized, so
+/// the file is complete when this returns.
+pub fn stop_recording(state: &AudioState) -> AppResult<()> {
+    {
+        let mut sender_guard = state.stop_sender.lock().unwrap();
+        if let Some(tx) = sender_guard.take() {
+            let _ = tx.send(Control::Stop); // Signal thread to exit
+        }
+    }
+    {
+        let mut recording = state.recording_active.lock().unwrap();
+        *recording = false;
+    }
+
+    // The handlers stop the recording from the recording thread itself; it has
+    // finalized the file before running them
+    let finished = state.finished.lock().unwrap().take();
+    match finished {
+        Some(finished) => match finished.recv_timeout(FINALIZE_TIMEOUT) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => Err(AppError::Storage(
+                "The recording was not finalized in time".to_string(),
+            )),
+            Err(RecvTimeoutError::Disconnected) => Err(AppError::Internal(
+                "The recording thread exited unexpectedly".to_string(),
+            )),
+        },
+        None => Ok(()),
+    }
+}
+
+/// Pauses or resumes the current recording, if any.
+pub fn set_recording_paused(state: &AudioState, paused: bool) {
+    if let Some(tx) = state.stop_sender.lock().unwrap().as_ref() {
+        let _ = tx.send(if paused {
+            Control::Pause
+        } else {
+            Control::Resume
+        });
+    }
 }
 
 fn write_input_data<T, U>(
     input: &[T],
-    writer: &Arc<Mutex<Option<hound::WavWriter<BufWriter<File>>>>>,
+    input_channels: u16,
+    paused: &AtomicBool,
+    writer: &SharedWriter,
+    dsp: &mut Option<DspChain>,
 ) where
     T: Sample,
-    U: Sample + hound::Sample + FromSample<T>,
+    f32: FromSample<T>,
+    U: Sample + hound::Sample + FromSample<T> + FromSample<f32>,
 {
+    if paused.load(Ordering::Relaxed) {
+        return;
+    }
     if let Ok(mut guard) = writer.lock() {
         if let Some(writer) = guard.as_mut() {
-            for &sample in input.iter() {
+            let output_channels = writer.spec().channels;
+            if dsp.is_none() && input_channels == output_channels {
+                for &sample in input.iter() {
+                    let sample: U = U::from_sample(sample);
+                    writer.write_sample(sample).ok();
+                }
+                return;
+            }
+
+            let mut buffer: Vec<f32> = input
+                .iter()
+                .map(|&sample| f32::from_sample(sample))
+                .collect();
+            if input_channels != output_channels {
+                buffer = remap_channels(&buffer, input_channels, output_channels);
+            }
+            if let Some(chain) = dsp {
+                chain.process(&mut buffer);
+            }
+            for sample in buffer {
                 let sample: U = U::from_sample(sample);
                 writer.write_sample(sample).ok();
             }
         }
     }
 }
+
+/// Converts interleaved audio to another number of channels. Mono is the
+/// average of all channels; otherwise channels are copied and missing ones
+/// repeat the first channel.
+fn remap_channels(input: &[f32], from: u16, to: u16) -> Vec<f32> {
+    let (from, to) = (from.max(1) as usize, to.max(1) as usize);
+    let mut output = Vec::with_capacity(input.len() / from * to);
+    for frame in input.chunks_exact(from) {
+        if to == 1 {
+            output.push(frame.iter().sum::<f32>() / from as f32);
+        } else {
+            output.extend((0..to).map(|channel| frame.get(channel).copied().unwrap_or(frame[0])));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remap_channels_downmixes_and_duplicates() {
+        assert_eq!(remap_channels(&[0.25, 0.75, -1.0, 0.0], 2, 1), [0.5, -0.5]);
+        assert_eq!(remap_channels(&[0.1, 0.2], 1, 2), [0.1, 0.1, 0.2, 0.2]);
+        assert_eq!(
+            remap_channels(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6], 3, 2),
+            [0.1, 0.2, 0.4, 0.5]
+        );
+        // Incomplete trailing frames are dropped
+        assert_eq!(remap_channels(&[0.1, 0.2, 0.3], 2, 2), [0.1, 0.2]);
+    }
+
+    #[test]
+    fn paused_time_does_not_count_toward_the_timeout() {
+        let start = Instant::now();
+        let mut clock = RecordingClock::new(start, Duration::from_secs(60), Duration::ZERO);
+        let at = |seconds| start + Duration::from_secs(seconds);
+
+        assert!(clock.pause(at(10)));
+        assert!(!clock.pause(at(15)));
+        assert_eq!(clock.remaining(at(20)), None);
+        assert!(clock.resume(at(40)));
+        assert!(!clock.resume(at(45)));
+        assert_eq!(clock.remaining(at(40)), Some(Duration::from_secs(50)));
+
+        clock.pause(at(50));
+        clock.resume(at(55));
+        assert_eq!(clock.remaining(at(80)), Some(Duration::from_secs(15)));
+        assert_eq!(clock.remaining(at(200)), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn warning_comes_once_before_the_timeout() {
+        let start = Instant::now();
+        let secs = Duration::from_secs;
+        let mut clock = RecordingClock::new(start, secs(60), secs(10));
+        let at = |seconds| start + secs(seconds);
+
+        assert_eq!(clock.next_wakeup(at(0)), Some(secs(50)));
+        // Paused time moves the warning along with the timeout
+        clock.pause(at(20));
+        assert_eq!(clock.next_wakeup(at(25)), None);
+        clock.resume(at(30));
+        assert_eq!(clock.next_wakeup(at(30)), Some(secs(30)));
+
+        assert!(clock.take_warning());
+        assert_eq!(clock.next_wakeup(at(60)), Some(secs(10)));
+        assert!(!clock.take_warning());
+
+        // A warning as long as the recording is never given
+        let mut short = RecordingClock::new(start, secs(10), secs(30));
+        assert_eq!(short.next_wakeup(at(0)), Some(secs(10)));
+        assert!(!short.take_warning());
+    }
+
+    #[test]
+    fn stop_waits_until_the_file_is_finalized() {
+        let state = AudioState::new();
+        let (done_tx, done_rx) = std::sync::mpsc::sync_channel(1);
+        *state.finished.lock().unwrap() = Some(done_rx);
+        let finalizer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            done_tx
+                .send(Err(AppError::Storage(
+                    "No space left on device".to_string(),
+                )))
+                .unwrap();
+        });
+
+        let started = Instant::now();
+        let result = stop_recording(&state);
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert!(matches!(result, Err(AppError::Storage(_))));
+        finalizer.join().unwrap();
+
+        // Nothing is recording anymore
+        assert!(stop_recording(&state).is_ok());
+    }
+}
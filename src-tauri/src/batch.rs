@@ -0,0 +1,509 @@
+use crate::context::AppContext;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    BatchJob, BatchJobStatus, BatchQueueData, HistoryItem, ImportMode, PipelineStage,
+};
+use crate::pipeline::{
+    check_speech, decode_audio_file, enrich, load_whisper_context, transcribe_samples,
+    PipelineFailure, Transcriber, SUPPORTED_AUDIO_EXTENSIONS,
+};
+use crate::progress::{ProgressCallback, StageTimer};
+use crate::store::{append_history_item, load_batch_queue, load_data, save_batch_queue};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+pub struct BatchState {
+    /// Serializes load-modify-save cycles on the persisted queue
+    pub queue_lock: Mutex<()>,
+    pub worker_running: AtomicBool,
+    /// The running job and the token that stops it. Only changed while the
+    /// queue lock is held, so it always matches the persisted job status.
+    current: Mutex<Option<(String, CancellationToken)>>,
+}
+
+impl Default for BatchState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchState {
+    pub fn new() -> Self {
+        Self {
+            queue_lock: Mutex::new(()),
+            worker_running: AtomicBool::new(false),
+            current: Mutex::new(None),
+        }
+    }
+
+    /// Stops the running job, if it is `job_id` (or any job for `None`).
+    fn cancel_current(&self, job_id: Option<&str>) {
+        if let Some((id, token)) = self.current.lock().unwrap().as_ref() {
+            if job_id.map_or(true, |job_id| job_id == id) {
+                token.cancel();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchJobProgress {
+    pub job_id: String,
+    pub status: BatchJobStatus,
+    pub stage: String,
+    pub progress: u8,
+    pub error: Option<String>,
+}
+
+/// Applies `f` to the persisted queue while holding the queue lock.
+fn update_queue<C: AppContext, T>(
+    ctx: &C,
+    state: &BatchState,
+    f: impl FnOnce(&mut BatchQueueData) -> T,
+) -> AppResult<T> {
+    let _guard = state.queue_lock.lock().unwrap();
+    let mut queue = load_batch_queue(ctx)?;
+    let result = f(&mut queue);
+    save_batch_queue(ctx, &queue)?;
+    ctx.emit_event("batch-queue-changed", &queue);
+    Ok(result)
+}
+
+pub fn get_jobs(ctx: &impl AppContext, state: &BatchState) -> AppResult<BatchQueueData> {
+    let _guard = state.queue_lock.lock().unwrap();
+    load_batch_queue(ctx)
+}
+
+/// Queues every supported audio file in `dir`. Returns the number of queued
+/// files; the caller starts the worker.
+pub fn enqueue_directory<C: AppContext>(
+    ctx: &C,
+    state: &BatchState,
+    dir: &Path,
+    mode: ImportMode,
+    instruction: Option<String>,
+    recursive: bool,
+) -> AppResult<usize> {
+    if !dir.is_dir() {
+        return Err(AppError::InvalidInput(format!(
+            "Not a directory: {:?}",
+            dir
+        )));
+    }
+    if mode == ImportMode::Enrich
+        && instruction
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or("")
+            .is_empty()
+    {
+        return Err(AppError::InvalidInput(
+            "An instruction is required to enrich imported files".to_string(),
+        ));
+    }
+
+    let max_depth = if recursive { usize::MAX } else { 1 };
+    let mut files: Vec<_> = WalkDir::new(dir)
+        .max_depth(max_depth)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .filter(|p| {
+            p.extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .is_some_and(|ext| SUPPORTED_AUDIO_EXTENSIONS.contains(&ext.as_str()))
+        })
+        .collect();
+    files.sort();
+
+    if files.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "No supported audio files found in {:?}",
+            dir
+        )));
+    }
+
+    let count = files.len();
+    update_queue(ctx, state, |queue| {
+        for path in files {
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            queue.jobs.push(BatchJob {
+                id: uuid::Uuid::new_v4().to_string(),
+                path: path.to_string_lossy().to_string(),
+                file_name,
+                mode,
+                instruction: instruction.clone(),
+                status: BatchJobStatus::Pending,
+                progress: 0,
+                error: None,
+                history_id: None,
+                created_at: chrono::Local::now().to_rfc3339(),
+            });
+        }
+    })?;
+
+    log::info!("Queued {} files from {:?}", count, dir);
+    Ok(count)
+}
+
+/// Stops the running job and starts no further jobs until resumed. The stopped
+/// job is requeued.
+pub fn pause<C: AppContext>(ctx: &C, state: &BatchState) -> AppResult<()> {
+    update_queue(ctx, state, |queue| {
+        queue.paused = true;
+        state.cancel_current(None);
+    })
+}
+
+/// Lets the worker pick up jobs again; the caller starts the worker.
+pub fn resume<C: AppContext>(ctx: &C, state: &BatchState) -> AppResult<()> {
+    update_queue(ctx, state, |queue| queue.paused = false)
+}
+
+/// Cancels a single job. A running job is stopped, including a decode or
+/// enrichment in progress.
+pub fn cancel_job<C: AppContext>(ctx: &C, state: &BatchState, job_id: &str) -> AppResult<()> {
+    update_queue(ctx, state, |queue| {
+        if let Some(job) = queue.jobs.iter_mut().find(|j| j.id == job_id) {
+            match job.status {
+                BatchJobStatus::Pending => job.status = BatchJobStatus::Cancelled,
+                BatchJobStatus::Running => {
+                    job.status = BatchJobStatus::Cancelled;
+                    state.cancel_current(Some(job_id));
+                }
+                _ => {}
+            }
+        }
+    })
+}
+
+/// Cancels all pending and running jobs.
+pub fn cancel_all<C: AppContext>(ctx: &C, state: &BatchState) -> AppResult<()> {
+    update_queue(ctx, state, |queue| {
+        for job in queue.jobs.iter_mut() {
+            if matches!(
+                job.status,
+                BatchJobStatus::Pending | BatchJobStatus::Running
+            ) {
+                job.status = BatchJobStatus::Cancelled;
+            }
+        }
+        state.cancel_current(None);
+    })
+}
+
+/// Removes completed, failed and cancelled jobs from the queue.
+pub fn clear_finished<C: AppContext>(ctx: &C, state: &BatchState) -> AppResult<()> {
+    update_queue(ctx, state, |queue| {
+        queue
+            .jobs
+            .retain(|j| matches!(j.status, BatchJobStatus::Pending | BatchJobStatus::Running));
+    })
+}
+
+/// Called on startup: jobs interrupted by a previous shutdown are requeued and
+/// the worker is resumed unless the queue was paused.
+pub fn restore(app: &AppHandle) {
+    let result = update_queue(app, &app.state::<BatchState>(), |queue| {
+        for job in queue.jobs.iter_mut() {
+            if job.status == BatchJobStatus::Running {
+                job.status = BatchJobStatus::Pending;
+                job.progress = 0;
+            }
+        }
+    });
+    if let Err(e) = result {
+        log::error!("Failed to restore batch queue: {}", e);
+        return;
+    }
+    ensure_worker(app);
+}
+
+/// Starts the worker thread if it is not running already.
+pub fn ensure_worker(app: &AppHandle) {
+    let state = app.state::<BatchState>();
+    if state
+        .worker_running
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return;
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let state = app.state::<BatchState>();
+        run_worker(&app, &state);
+        state.worker_running.store(false, Ordering::SeqCst);
+        log::info!("Batch worker stopped");
+
+        // Jobs may have been queued or resumed while the worker was shutting down
+        let runnable = get_jobs(&app, &state)
+            .map(|q| !q.paused && q.jobs.iter().any(|j| j.status == BatchJobStatus::Pending))
+            .unwrap_or(false);
+        if runnable {
+            ensure_worker(&app);
+        }
+    });
+}
+
+/// Processes pending jobs in order until the queue is empty or paused.
+pub fn run_worker<C: AppContext + Clone + 'static>(ctx: &C, state: &BatchState) {
+    log::info!("Batch worker started");
+    // Loaded lazily so an empty or paused queue does not load the model
+    let mut transcriber: Option<Arc<dyn Transcriber>> = ctx.transcriber();
+
+    loop {
+        let (job, cancel) = match next_job(ctx, state) {
+            Ok(Some(next)) => next,
+            Ok(None) => return,
+            Err(e) => {
+                log::error!("Failed to read batch queue: {}", e);
+                return;
+            }
+        };
+
+        if transcriber.is_none() {
+            match load_whisper_context(ctx) {
+                Ok(model) => transcriber = Some(model),
+                Err(e) => {
+                    // Without a model no job can succeed; leave the job pending for a later resume
+                    log::error!("Batch worker could not load Whisper: {}", e);
+                    let _ = update_queue(ctx, state, |queue| {
+                        queue.paused = true;
+                        if let Some(j) = queue.jobs.iter_mut().find(|j| j.id == job.id) {
+                            j.status = BatchJobStatus::Pending;
+                        }
+                        *state.current.lock().unwrap() = None;
+                    });
+                    ctx.emit_event(
+                        "pipeline-error",
+                        PipelineFailure {
+                            session_id: None,
+                            error: &e,
+                        },
+                    );
+                    return;
+                }
+            }
+        }
+
+        let result = process_job(ctx, transcriber.as_deref().unwrap(), &job, &cancel);
+        finish_job(ctx, state, &job, result);
+    }
+}
+
+/// Takes the first pending job, marks it as running and returns it with the
+/// token that cancels it. Returns `None` if the queue is paused or has no
+/// pending jobs.
+pub fn next_job<C: AppContext>(
+    ctx: &C,
+    state: &BatchState,
+) -> AppResult<Option<(BatchJob, CancellationToken)>> {
+    update_queue(ctx, state, |queue| {
+        if queue.paused {
+            return None;
+        }
+        let job = queue
+            .jobs
+            .iter_mut()
+            .find(|j| j.status == BatchJobStatus::Pending)?;
+        job.status = BatchJobStatus::Running;
+        job.progress = 0;
+
+        // Registered together with the status change, so a cancel cannot fall in between
+        let cancel = CancellationToken::new();
+        *state.current.lock().unwrap() = Some((job.id.clone(), cancel.clone()));
+        Some((job.clone(), cancel))
+    })
+}
+
+/// Records the outcome of the running job. A job stopped by `pause` goes back to
+/// pending; one stopped by a cancel stays cancelled.
+pub fn finish_job<C: AppContext>(
+    ctx: &C,
+    state: &BatchState,
+    job: &BatchJob,
+    result: AppResult<String>,
+) {
+    let (status, error, history_id) = match result {
+        Ok(history_id) => (BatchJobStatus::Completed, None, Some(history_id)),
+        Err(AppError::Cancelled) => (BatchJobStatus::Cancelled, None, None),
+        Err(e) => {
+            log::error!(
+                "Batch job {} failed: {} {:?}",
+                job.file_name,
+                e,
+                e.details()
+            );
+            (BatchJobStatus::Failed, Some(e.to_string()), None)
+        }
+    };
+
+    let updated = update_queue(ctx, state, |queue| {
+        *state.current.lock().unwrap() = None;
+        let j = queue.jobs.iter_mut().find(|j| j.id == job.id)?;
+        match status {
+            // Cancelled jobs were marked as such by the cancel
+            BatchJobStatus::Cancelled if j.status == BatchJobStatus::Running => {
+                j.status = BatchJobStatus::Pending;
+                j.progress = 0;
+            }
+            BatchJobStatus::Cancelled => {}
+            _ => {
+                j.status = status;
+                j.error = error.clone();
+                j.history_id = history_id;
+                if status == BatchJobStatus::Completed {
+                    j.progress = 100;
+                }
+            }
+        }
+        Some(j.status)
+    });
+    if let Ok(Some(status)) = updated {
+        emit_progress(ctx, &job.id, status, "done", 100, error);
+    }
+}
+
+fn process_job<C: AppContext + Clone + 'static>(
+    ctx: &C,
+    transcriber: &dyn Transcriber,
+    job: &BatchJob,
+    cancel: &CancellationToken,
+) -> AppResult<String> {
+    let check_cancelled = || {
+        if cancel.is_cancelled() {
+            Err(AppError::Cancelled)
+        } else {
+            Ok(())
+        }
+    };
+
+    log::info!("Batch job started: {}", job.file_name);
+    let mut timer = StageTimer::new(PipelineStage::TranscribeContent);
+    emit_progress(
+        ctx,
+        &job.id,
+        BatchJobStatus::Running,
+        "transcribe",
+        10,
+        None,
+    );
+
+    // Whisper's progress fills the transcribe range, enrichment the rest
+    let transcribe_end = match job.mode {
+        ImportMode::Enrich => 60,
+        ImportMode::Transcribe => 90,
+    };
+    let settings = load_data(ctx)?.settings;
+    // Silent files and files without recognized speech fail like imported files do
+    let samples = decode_audio_file(Path::new(&job.path))?;
+    check_speech(&samples, &job.file_name, &settings)?;
+    check_cancelled()?;
+    let content = transcribe_samples(
+        transcriber,
+        &samples,
+        &settings,
+        Some(transcribe_progress(ctx, &job.id, 10, transcribe_end)),
+        Some(cancel.clone()),
+    )?;
+    let content_text = content.text.clone();
+    check_cancelled()?;
+    if content_text.is_empty() {
+        return Err(AppError::NoSpeech(job.file_name.clone()));
+    }
+
+    let enriched_text = match (job.mode, &job.instruction) {
+        (ImportMode::Enrich, Some(instruction)) => {
+            timer.stage(PipelineStage::Enrich);
+            emit_progress(ctx, &job.id, BatchJobStatus::Running, "enrich", 60, None);
+            // The worker is a plain thread, so drive the async request to completion here
+            let text = tauri::async_runtime::block_on(enrich(
+                instruction,
+                &content_text,
+                &settings,
+                cancel,
+            ))?;
+            check_cancelled()?;
+            text
+        }
+        _ => content_text.clone(),
+    };
+
+    timer.stage(PipelineStage::Save);
+    emit_progress(ctx, &job.id, BatchJobStatus::Running, "save", 90, None);
+    let item = HistoryItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Local::now().to_rfc3339(),
+        instruction: job.instruction.clone().unwrap_or_default(),
+        original_content: content_text,
+        enriched_content: enriched_text,
+        source_file: Some(job.file_name.clone()),
+        language: content.language.clone(),
+        language_probability: content.language_probability,
+        instruction_transcript: None,
+        content_transcript: Some(content),
+        stage_timings: timer.finish(),
+    };
+    let history_id = item.id.clone();
+    append_history_item(ctx, item)?;
+
+    log::info!("Batch job completed: {}", job.file_name);
+    Ok(history_id)
+}
+
+/// Reports Whisper's progress as job progress between `start` and `end`.
+fn transcribe_progress<C: AppContext + Clone + 'static>(
+    ctx: &C,
+    job_id: &str,
+    start: u8,
+    end: u8,
+) -> ProgressCallback {
+    let ctx = ctx.clone();
+    let job_id = job_id.to_string();
+    let mut last = start;
+    Box::new(move |percent: i32| {
+        let progress = start + ((end - start) as i32 * percent.clamp(0, 100) / 100) as u8;
+        if progress != last {
+            last = progress;
+            emit_progress(
+                &ctx,
+                &job_id,
+                BatchJobStatus::Running,
+                "transcribe",
+                progress,
+                None,
+            );
+        }
+    })
+}
+
+fn emit_progress(
+    ctx: &impl AppContext,
+    job_id: &str,
+    status: BatchJobStatus,
+    stage: &str,
+    progress: u8,
+    error: Option<String>,
+) {
+    ctx.emit_event(
+        "batch-job-progress",
+        BatchJobProgress {
+            job_id: job_id.to_string(),
+            status,
+            stage: stage.to_string(),
+            progress,
+            error,
+        },
+    );
+}
@@ -0,0 +1,366 @@
+//! Command line interface to the transcription and enrichment pipeline.
+//! Uses the same settings, history and models as the desktop app.
+
+use app_lib::audio::{
+    start_recording, stop_recording, AudioState, Interruption, RecordingHandlers, RecordingOptions,
+};
+use app_lib::context::{default_data_dir, AppContext};
+use app_lib::error::{AppError, AppResult};
+use app_lib::export::render_transcript;
+use app_lib::models::{Cue, SubtitleFormat};
+use app_lib::{ollama, pipeline, store};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::Value;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Parser)]
+#[command(
+    name = "voice-intel",
+    version,
+    about = "Transcribe and enrich speech from the command line"
+)]
+struct Cli {
+    /// Data directory; defaults to the one used by the desktop app
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Transcribe an audio file with Whisper
+    Transcribe {
+        file: PathBuf,
+        /// Print the transcript with segments and confidence as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rewrite content following an instruction with the configured Ollama model
+    Enrich {
+        #[arg(long)]
+        instruction: String,
+        /// Text to rewrite; read from stdin if omitted
+        #[arg(long)]
+        content: Option<String>,
+    },
+    /// Record from the microphone into a WAV file
+    Record {
+        #[arg(long, default_value_t = 10)]
+        seconds: u64,
+        #[arg(long, short, default_value = "recording.wav")]
+        output: PathBuf,
+        /// Input device; defaults to the microphone from the settings
+        #[arg(long)]
+        device: Option<String>,
+    },
+    /// Manage the Whisper and Ollama models
+    Models {
+        #[command(subcommand)]
+        command: ModelsCommand,
+    },
+    /// Browse processed recordings
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ModelsCommand {
+    /// Show the Whisper model and the installed Ollama models
+    List,
+    /// Download the Whisper model, or pull the named Ollama model
+    Pull { name: Option<String> },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// List history items, newest first
+    List {
+        #[arg(long, short = 'n')]
+        limit: Option<usize>,
+    },
+    /// Print or save one history item
+    Export {
+        id: String,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Text)]
+        format: ExportFormat,
+        /// Export the instruction instead of the result
+        #[arg(long)]
+        instruction: bool,
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// Plain text of the enriched result (or the instruction)
+    Text,
+    Srt,
+    Vtt,
+    /// Timed transcript as JSON
+    Json,
+}
+
+/// Host for the shared code: events are printed instead of sent to a frontend.
+struct CliContext {
+    data_dir: PathBuf,
+}
+
+impl AppContext for CliContext {
+    fn data_dir(&self) -> PathBuf {
+        self.data_dir.clone()
+    }
+
+    fn emit_value(&self, _event: &str, payload: Value) {
+        // Status messages go to stderr so stdout only carries results
+        if let Value::String(message) = payload {
+            eprintln!("{}", message);
+        }
+    }
+
+    fn read_clipboard(&self) -> AppResult<String> {
+        Err(AppError::Clipboard(
+            "Not available in the command line tool".to_string(),
+        ))
+    }
+
+    fn write_clipboard(&self, _text: &str) -> AppResult<()> {
+        Err(AppError::Clipboard(
+            "Not available in the command line tool".to_string(),
+        ))
+    }
+
+    fn play_cue(&self, _cue: Cue) {}
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let Some(data_dir) = cli.data_dir.or_else(default_data_dir) else {
+        eprintln!("error: could not determine the data directory, pass --data-dir");
+        return ExitCode::FAILURE;
+    };
+    let ctx = CliContext { data_dir };
+
+    match run(&ctx, cli.command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            if let Some(details) = e.details() {
+                eprintln!("  {}", details);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(ctx: &CliContext, command: Command) -> AppResult<()> {
+    match command {
+        Command::Transcribe { file, json } => transcribe(ctx, &file, json),
+        Command::Enrich {
+            instruction,
+            content,
+        } => enrich(ctx, &instruction, content),
+        Command::Record {
+            seconds,
+            output,
+            device,
+        } => record(ctx, seconds, output, device),
+        Command::Models { command } => match command {
+            ModelsCommand::List => list_models(ctx),
+            ModelsCommand::Pull { name: None } => {
+                pipeline::ensure_whisper_model(ctx, &pipeline::whisper_model_path(ctx))
+            }
+            ModelsCommand::Pull { name: Some(name) } => {
+                let settings = store::load_data(ctx)?.settings;
+                if !ollama::pull_missing_model(&settings.ollama_url, &name)? {
+                    eprintln!("{} is already installed", name);
+                }
+                Ok(())
+            }
+        },
+        Command::History { command } => match command {
+            HistoryCommand::List { limit } => list_history(ctx, limit),
+            HistoryCommand::Export {
+                id,
+                format,
+                instruction,
+                output,
+            } => export_history(ctx, &id, format, instruction, output),
+        },
+    }
+}
+
+fn transcribe(ctx: &CliContext, file: &Path, json: bool) -> AppResult<()> {
+    let settings = store::load_data(ctx)?.settings;
+    let whisper = pipeline::load_whisper_context(ctx)?;
+    let transcript = pipeline::transcribe_local(&*whisper, file, &settings, None, None)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&transcript)?);
+    } else {
+        println!("{}", transcript.text);
+    }
+    Ok(())
+}
+
+fn enrich(ctx: &CliContext, instruction: &str, content: Option<String>) -> AppResult<()> {
+    let content = match content {
+        Some(content) => content,
+        None => {
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content)?;
+            content
+        }
+    };
+    let settings = store::load_data(ctx)?.settings;
+    let result = tauri::async_runtime::block_on(pipeline::enrich(
+        instruction,
+        content.trim(),
+        &settings,
+        &CancellationToken::new(),
+    ))?;
+    println!("{}", result);
+    Ok(())
+}
+
+fn record(
+    ctx: &CliContext,
+    seconds: u64,
+    output: PathBuf,
+    device: Option<String>,
+) -> AppResult<()> {
+    let settings = store::load_data(ctx)?.settings;
+    let device = device.or_else(|| Some(settings.microphone).filter(|m| m != "default"));
+    let options = RecordingOptions {
+        device,
+        timeout: Duration::from_secs(seconds),
+        // The recording is meant to run for the full duration
+        timeout_warning: Duration::ZERO,
+        processing: settings.audio_processing,
+        continue_on_default_device: settings.continue_on_default_device,
+    };
+    let handlers = RecordingHandlers {
+        on_interrupted: Box::new(|interruption: Interruption| match interruption.resumed_on {
+            Some(device) => eprintln!(
+                "{} failed ({}), continuing on {}",
+                interruption.device, interruption.error, device
+            ),
+            None => eprintln!(
+                "{} failed ({}), keeping the first {:.1} seconds",
+                interruption.device,
+                interruption.error,
+                interruption.at_ms as f64 / 1000.0
+            ),
+        }),
+        ..Default::default()
+    };
+
+    // Dropping the state would stop the recording right away
+    let state = AudioState::new();
+    // The timeout ends the recording; the thread finishes once the file is written
+    let recorder = start_recording(&state, output.clone(), options, handlers)?;
+    eprintln!("Recording for {} seconds...", seconds);
+    recorder
+        .join()
+        .map_err(|_| AppError::Internal("Recording thread panicked".to_string()))?;
+    // Reports whether the file was finalized
+    stop_recording(&state)?;
+    eprintln!("Saved {}", output.display());
+    Ok(())
+}
+
+fn list_models(ctx: &CliContext) -> AppResult<()> {
+    let settings = store::load_data(ctx)?.settings;
+    let whisper = pipeline::whisper_model_path(ctx);
+    println!(
+        "Whisper: {} ({})",
+        whisper.display(),
+        if whisper.exists() {
+            "installed"
+        } else {
+            "not downloaded"
+        }
+    );
+
+    println!("Ollama:");
+    for model in ollama::scan_models() {
+        // Mark the model used for enrichment
+        let marker = if model == settings.model { "*" } else { " " };
+        println!("{} {}", marker, model);
+    }
+    Ok(())
+}
+
+fn list_history(ctx: &CliContext, limit: Option<usize>) -> AppResult<()> {
+    let data = store::load_data(ctx)?;
+    for item in data.history.iter().rev().take(limit.unwrap_or(usize::MAX)) {
+        println!(
+            "{}  {}  {}",
+            item.id,
+            item.timestamp,
+            summary(&item.enriched_content)
+        );
+    }
+    Ok(())
+}
+
+/// First line of `text`, shortened to fit a terminal row.
+fn summary(text: &str) -> String {
+    const MAX_CHARS: usize = 60;
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > MAX_CHARS {
+        format!("{}...", line.chars().take(MAX_CHARS).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+fn export_history(
+    ctx: &CliContext,
+    id: &str,
+    format: ExportFormat,
+    instruction: bool,
+    output: Option<PathBuf>,
+) -> AppResult<()> {
+    let data = store::load_data(ctx)?;
+    let item = data
+        .history
+        .iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("History item '{}'", id)))?;
+
+    let subtitle_format = match format {
+        ExportFormat::Text => None,
+        ExportFormat::Srt => Some(SubtitleFormat::Srt),
+        ExportFormat::Vtt => Some(SubtitleFormat::Vtt),
+        ExportFormat::Json => Some(SubtitleFormat::Json),
+    };
+    let rendered = match subtitle_format {
+        None if instruction => item.instruction.clone(),
+        None => item.enriched_content.clone(),
+        Some(subtitle_format) => {
+            let transcript = if instruction {
+                item.instruction_transcript.as_ref()
+            } else {
+                item.content_transcript.as_ref()
+            }
+            .ok_or_else(|| {
+                AppError::InvalidInput("This history item has no timed transcript".to_string())
+            })?;
+            render_transcript(transcript, subtitle_format)?
+        }
+    };
+
+    match output {
+        Some(path) => std::fs::write(&path, rendered)?,
+        None => println!("{}", rendered),
+    }
+    Ok(())
+}
@@ -1,14 +1,54 @@
-use crate::audio::{list_input_devices, play_sound};
-use crate::models::{HistoryItem, Settings};
+use crate::audio::list_input_devices;
+use crate::batch::{self, BatchState};
+use crate::error::{AppError, AppResult};
+use crate::logic::{
+    discard_recording, emit_status, state_machine_snapshot, status_snapshot, toggle_pause,
+    StatusSnapshot,
+};
+use crate::models::{
+    BatchQueueData, Cue, HistoryItem, ImportMode, Settings, SoundSettings, SubtitleFormat,
+    VocabularyFile,
+};
 use crate::ollama::scan_models;
-use crate::store::{get_status, load_data, save_data};
+use crate::pipeline::SUPPORTED_AUDIO_EXTENSIONS;
+use crate::queue::{self, QueuedSession, SessionQueue};
+use crate::sounds::{list_output_devices, test_cue};
+use crate::state_machine::StateMachineSnapshot;
+use crate::store::{load_data, update};
+use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
 #[cfg(desktop)]
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
 
 #[tauri::command]
-pub fn get_current_status(app: AppHandle) -> Result<String, String> {
-    get_status(&app)
+pub fn get_current_status(app: AppHandle) -> StatusSnapshot {
+    status_snapshot(&app)
+}
+
+/// Current recording state, the transition table and the most recent
+/// transitions, for debugging
+#[tauri::command]
+pub fn get_state_machine(app: AppHandle) -> StateMachineSnapshot {
+    state_machine_snapshot(&app)
+}
+
+/// Stops the current recording and discards its session
+#[tauri::command]
+pub fn cancel_recording(app: AppHandle) {
+    discard_recording(&app);
+}
+
+/// Pauses the current recording, or resumes it if it is paused
+#[tauri::command]
+pub fn toggle_recording_pause(app: AppHandle) {
+    toggle_pause(&app);
+}
+
+/// Sessions waiting to be processed, oldest first
+#[tauri::command]
+pub fn get_queued_sessions(app: AppHandle) -> Vec<QueuedSession> {
+    app.state::<SessionQueue>().pending()
 }
 
 #[tauri::command]
@@ -22,68 +62,92 @@ pub fn get_input_devices() -> Vec<String> {
 }
 
 #[tauri::command]
-pub fn play_test_sound(name: String) {
-    play_sound(&name);
+pub fn get_output_devices() -> Vec<String> {
+    list_output_devices()
+}
+
+/// Plays `cue` with `sounds`, so sound settings can be tried before saving them,
+/// or with the saved settings
+#[tauri::command]
+pub fn play_test_sound(app: AppHandle, cue: Cue, sounds: Option<SoundSettings>) -> AppResult<()> {
+    let sounds = match sounds {
+        Some(sounds) => {
+            sounds.validate().map_err(AppError::InvalidSettings)?;
+            sounds
+        }
+        None => load_data(&app)?.settings.sounds,
+    };
+    test_cue(sounds, cue)
 }
 
 #[tauri::command]
-pub fn get_settings(app: AppHandle) -> Result<Settings, String> {
+pub fn get_settings(app: AppHandle) -> AppResult<Settings> {
     let data = load_data(&app)?;
     Ok(data.settings)
 }
 
 #[tauri::command]
-pub fn save_settings(app: AppHandle, settings: Settings) -> Result<(), String> {
+pub fn save_settings(app: AppHandle, settings: Settings) -> AppResult<()> {
+    settings.validate().map_err(AppError::InvalidSettings)?;
+
     #[cfg(desktop)]
     {
         // Unregister all existing shortcuts
         if let Err(e) = app.global_shortcut().unregister_all() {
             log::error!("Failed to unregister hotkeys: {}", e);
         }
-        // Register the new hotkey
-        if let Err(e) = app.global_shortcut().register(settings.hotkey.as_str()) {
-            return Err(format!(
-                "Failed to register hotkey '{}': {}",
-                settings.hotkey, e
-            ));
-        }
+        // Register the new hotkeys
+        register_hotkeys(&app, &settings)?;
     }
 
-    let mut data = load_data(&app)?;
-    data.settings = settings;
-    save_data(&app, &data)
+    update(&app, |data| data.settings = settings)
+}
+
+/// Registers the recording hotkey and, if set, the pause hotkey.
+#[cfg(desktop)]
+pub fn register_hotkeys(app: &AppHandle, settings: &Settings) -> AppResult<()> {
+    for hotkey in std::iter::once(&settings.hotkey).chain(&settings.pause_hotkey) {
+        app.global_shortcut()
+            .register(hotkey.as_str())
+            .map_err(|e| AppError::Hotkey(hotkey.clone(), e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Whether `shortcut` is the pause hotkey from the settings
+#[cfg(desktop)]
+pub fn is_pause_hotkey(app: &AppHandle, shortcut: &Shortcut) -> bool {
+    load_data(app)
+        .ok()
+        .and_then(|data| data.settings.pause_hotkey)
+        .and_then(|hotkey| hotkey.parse::<Shortcut>().ok())
+        .map_or(false, |pause| pause == *shortcut)
 }
 
 #[tauri::command]
-pub fn get_history(app: AppHandle) -> Result<Vec<HistoryItem>, String> {
+pub fn get_history(app: AppHandle) -> AppResult<Vec<HistoryItem>> {
     let data = load_data(&app)?;
     Ok(data.history)
 }
 
 // Internal helper might be needed later, but for now specific command:
 #[tauri::command]
-pub fn add_history_item(app: AppHandle, item: HistoryItem) -> Result<(), String> {
-    let mut data = load_data(&app)?;
-    data.history.push(item);
-    save_data(&app, &data)
+pub fn add_history_item(app: AppHandle, item: HistoryItem) -> AppResult<()> {
+    update(&app, |data| data.history.push(item))
 }
 
 #[tauri::command]
-pub fn clear_history(app: AppHandle) -> Result<(), String> {
-    let mut data = load_data(&app)?;
-    data.history.clear();
-    save_data(&app, &data)
+pub fn clear_history(app: AppHandle) -> AppResult<()> {
+    update(&app, |data| data.history.clear())
 }
 
 #[tauri::command]
-pub fn delete_history_item(app: AppHandle, id: String) -> Result<(), String> {
-    let mut data = load_data(&app)?;
-    data.history.retain(|item| item.id != id);
-    save_data(&app, &data)
+pub fn delete_history_item(app: AppHandle, id: String) -> AppResult<()> {
+    update(&app, |data| data.history.retain(|item| item.id != id))
 }
 
 #[tauri::command]
-pub fn get_app_info(app: AppHandle) -> Result<serde_json::Value, String> {
+pub fn get_app_info(app: AppHandle) -> AppResult<serde_json::Value> {
     let app_data_dir = app
         .path()
         .app_data_dir()
@@ -102,11 +166,254 @@ pub fn get_app_info(app: AppHandle) -> Result<serde_json::Value, String> {
     }))
 }
 
+/// Cancels the given session, whether it is waiting in the queue or running. Without
+/// an id, every queued and running session is cancelled. Recording is not affected.
+#[tauri::command]
+pub fn stop_pipeline(app: AppHandle, session_id: Option<String>) -> AppResult<()> {
+    match session_id {
+        Some(id) => {
+            log::info!("Stop requested for pipeline session {}", id);
+            queue::cancel_session(&app, &id);
+        }
+        None => {
+            log::info!("Stop requested for all pipeline sessions");
+            queue::cancel_all(&app);
+        }
+    }
+    Ok(())
+}
+
+/// Queues an existing audio file to be transcribed (and optionally enriched) after
+/// the recordings ahead of it. When no path is given, a file picker is shown.
+/// Returns the selected path, or `None` if the picker was dismissed. Results are
+/// reported through the pipeline events.
+#[tauri::command]
+pub async fn import_audio_file(
+    app: AppHandle,
+    path: Option<String>,
+    mode: ImportMode,
+    instruction: Option<String>,
+) -> AppResult<Option<String>> {
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => {
+            // Async commands run off the main thread, so the blocking picker is safe here
+            let picked = app
+                .dialog()
+                .file()
+                .add_filter("Audio", SUPPORTED_AUDIO_EXTENSIONS)
+                .blocking_pick_file();
+            match picked {
+                Some(file) => file
+                    .into_path()
+                    .map_err(|e| AppError::InvalidInput(e.to_string()))?,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !SUPPORTED_AUDIO_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported audio format '{}'. Supported formats: {}",
+            extension,
+            SUPPORTED_AUDIO_EXTENSIONS.join(", ")
+        )));
+    }
+
+    let queued = path.to_string_lossy().to_string();
+    queue::enqueue_import(&app, &app.state::<SessionQueue>(), path, mode, instruction);
+    emit_status(&app);
+    Ok(Some(queued))
+}
+
+/// Queues every audio file in a directory for background transcription. When no
+/// directory is given, a folder picker is shown. Returns the number of queued files.
 #[tauri::command]
-pub fn stop_pipeline(app: AppHandle) -> Result<(), String> {
-    use crate::logic::{set_status, AppStatus};
-    log::info!("Stop pipeline requested");
-    crate::pipeline::cancel_pipeline();
-    set_status(&app, AppStatus::Idle);
+pub async fn start_batch(
+    app: AppHandle,
+    dir: Option<String>,
+    mode: ImportMode,
+    instruction: Option<String>,
+    recursive: Option<bool>,
+) -> AppResult<usize> {
+    let dir = match dir {
+        Some(d) => PathBuf::from(d),
+        None => match app.dialog().file().blocking_pick_folder() {
+            Some(folder) => folder
+                .into_path()
+                .map_err(|e| AppError::InvalidInput(e.to_string()))?,
+            None => return Ok(0),
+        },
+    };
+
+    let count = batch::enqueue_directory(
+        &app,
+        &app.state::<BatchState>(),
+        &dir,
+        mode,
+        instruction,
+        recursive.unwrap_or(false),
+    )?;
+    batch::ensure_worker(&app);
+    Ok(count)
+}
+
+#[tauri::command]
+pub fn get_batch_jobs(app: AppHandle) -> AppResult<BatchQueueData> {
+    batch::get_jobs(&app, &app.state::<BatchState>())
+}
+
+#[tauri::command]
+pub fn pause_batch(app: AppHandle) -> AppResult<()> {
+    batch::pause(&app, &app.state::<BatchState>())
+}
+
+#[tauri::command]
+pub fn resume_batch(app: AppHandle) -> AppResult<()> {
+    batch::resume(&app, &app.state::<BatchState>())?;
+    batch::ensure_worker(&app);
     Ok(())
 }
+
+#[tauri::command]
+pub fn cancel_batch_job(app: AppHandle, id: String) -> AppResult<()> {
+    batch::cancel_job(&app, &app.state::<BatchState>(), &id)
+}
+
+#[tauri::command]
+pub fn cancel_batch(app: AppHandle) -> AppResult<()> {
+    batch::cancel_all(&app, &app.state::<BatchState>())
+}
+
+#[tauri::command]
+pub fn clear_finished_batch_jobs(app: AppHandle) -> AppResult<()> {
+    batch::clear_finished(&app, &app.state::<BatchState>())
+}
+
+/// Writes the global vocabulary and replacements to a JSON file. When no path
+/// is given, a save dialog is shown. Returns the written path.
+#[tauri::command]
+pub async fn export_vocabulary(app: AppHandle, path: Option<String>) -> AppResult<Option<String>> {
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => match app
+            .dialog()
+            .file()
+            .add_filter("Vocabulary", &["json"])
+            .set_file_name("vocabulary.json")
+            .blocking_save_file()
+        {
+            Some(file) => file
+                .into_path()
+                .map_err(|e| AppError::InvalidInput(e.to_string()))?,
+            None => return Ok(None),
+        },
+    };
+
+    let settings = load_data(&app)?.settings;
+    let file = VocabularyFile {
+        vocabulary: settings.vocabulary,
+        replacements: settings.replacements,
+    };
+    let json = serde_json::to_string_pretty(&file)?;
+    std::fs::write(&path, json)?;
+    Ok(Some(path.to_string_lossy().to_string()))
+}
+
+/// Merges vocabulary and replacements from a JSON file into the global settings,
+/// skipping entries that already exist. Returns the updated settings.
+#[tauri::command]
+pub async fn import_vocabulary(app: AppHandle, path: Option<String>) -> AppResult<Settings> {
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => match app
+            .dialog()
+            .file()
+            .add_filter("Vocabulary", &["json"])
+            .blocking_pick_file()
+        {
+            Some(file) => file
+                .into_path()
+                .map_err(|e| AppError::InvalidInput(e.to_string()))?,
+            None => return Ok(load_data(&app)?.settings),
+        },
+    };
+
+    let json = std::fs::read_to_string(&path)?;
+    let imported: VocabularyFile = serde_json::from_str(&json)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid vocabulary file: {}", e)))?;
+    for replacement in &imported.replacements {
+        replacement.validate().map_err(AppError::InvalidInput)?;
+    }
+
+    update(&app, |data| {
+        for term in imported.vocabulary {
+            let term = term.trim().to_string();
+            if !term.is_empty() && !data.settings.vocabulary.contains(&term) {
+                data.settings.vocabulary.push(term);
+            }
+        }
+        for replacement in imported.replacements {
+            if !data.settings.replacements.contains(&replacement) {
+                data.settings.replacements.push(replacement);
+            }
+        }
+        data.settings.clone()
+    })
+}
+
+/// Exports the timed transcript of a history item as SRT, WebVTT or JSON. Uses the
+/// content transcript unless `instruction` is true. When no path is given, a save
+/// dialog is shown. Returns the written path.
+#[tauri::command]
+pub async fn export_transcript(
+    app: AppHandle,
+    id: String,
+    format: SubtitleFormat,
+    instruction: Option<bool>,
+    path: Option<String>,
+) -> AppResult<Option<String>> {
+    let data = load_data(&app)?;
+    let item = data
+        .history
+        .iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("History item '{}'", id)))?;
+
+    let transcript = if instruction.unwrap_or(false) {
+        item.instruction_transcript.as_ref()
+    } else {
+        item.content_transcript.as_ref()
+    }
+    .ok_or_else(|| {
+        AppError::InvalidInput("This history item has no timed transcript".to_string())
+    })?;
+
+    let rendered = crate::export::render_transcript(transcript, format)?;
+
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => {
+            let default_name = format!("transcript.{}", format.extension());
+            match app
+                .dialog()
+                .file()
+                .add_filter("Transcript", &[format.extension()])
+                .set_file_name(default_name)
+                .blocking_save_file()
+            {
+                Some(file) => file
+                    .into_path()
+                    .map_err(|e| AppError::InvalidInput(e.to_string()))?,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    std::fs::write(&path, rendered)?;
+    Ok(Some(path.to_string_lossy().to_string()))
+}
@@ -0,0 +1,153 @@
+use crate::models::{Transcript, TranscriptSegment, TranscriptToken};
+use serde::Serialize;
+
+/// A word whose weakest token fell below the confidence threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct LowConfidenceWord {
+    pub word: String,
+    pub probability: f32,
+    pub segment: usize,
+    pub start_ms: u64,
+}
+
+/// Confidence summary of one transcript, as sent in pipeline event payloads
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptConfidence {
+    pub text: String,
+    pub confidence: Option<f32>,
+    pub low_confidence_words: Vec<LowConfidenceWord>,
+}
+
+impl TranscriptConfidence {
+    pub fn new(transcript: &Transcript, threshold: f32) -> Self {
+        Self {
+            text: transcript.text.clone(),
+            confidence: transcript.confidence,
+            low_confidence_words: low_confidence_words(transcript, threshold),
+        }
+    }
+}
+
+/// Mean token probability of a segment; segments without tokens count as certain.
+pub fn segment_confidence(tokens: &[TranscriptToken]) -> f32 {
+    if tokens.is_empty() {
+        return 1.0;
+    }
+    tokens.iter().map(|t| t.probability).sum::<f32>() / tokens.len() as f32
+}
+
+/// Token-weighted mean over all segments, or `None` if there are no tokens.
+pub fn transcript_confidence(segments: &[TranscriptSegment]) -> Option<f32> {
+    let (sum, count) = segments
+        .iter()
+        .flat_map(|s| s.tokens.iter())
+        .fold((0.0f32, 0usize), |(sum, count), t| {
+            (sum + t.probability, count + 1)
+        });
+    (count > 0).then(|| sum / count as f32)
+}
+
+/// Groups tokens into words (a leading space starts a new word) and returns the
+/// words whose least probable token is below `threshold`.
+pub fn low_confidence_words(transcript: &Transcript, threshold: f32) -> Vec<LowConfidenceWord> {
+    let mut words = Vec::new();
+
+    for (index, segment) in transcript.segments.iter().enumerate() {
+        let mut current = String::new();
+        let mut current_min = 1.0f32;
+
+        let mut flush = |word: &mut String, min: f32| {
+            let trimmed = word.trim();
+            if !trimmed.is_empty() && min < threshold {
+                words.push(LowConfidenceWord {
+                    word: trimmed.to_string(),
+                    probability: min,
+                    segment: index,
+                    start_ms: segment.start_ms,
+                });
+            }
+            word.clear();
+        };
+
+        for token in &segment.tokens {
+            if token.text.starts_with(' ') && !current.trim().is_empty() {
+                flush(&mut current, current_min);
+                current_min = 1.0;
+            }
+            current.push_str(&token.text);
+            current_min = current_min.min(token.probability);
+        }
+        flush(&mut current, current_min);
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_ms: u64, tokens: &[(&str, f32)]) -> TranscriptSegment {
+        let tokens: Vec<_> = tokens
+            .iter()
+            .map(|&(text, probability)| TranscriptToken {
+                text: text.to_string(),
+                probability,
+            })
+            .collect();
+        TranscriptSegment {
+            start_ms,
+            end_ms: start_ms + 1_000,
+            text: tokens.iter().map(|t| t.text.as_str()).collect(),
+            confidence: segment_confidence(&tokens),
+            tokens,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn segment_confidence_is_the_mean_token_probability() {
+        let segment = segment(0, &[(" a", 0.5), (" b", 1.0), (" c", 0.6)]);
+        assert!((segment.confidence - 0.7).abs() < 1e-6);
+        assert_eq!(segment_confidence(&[]), 1.0);
+    }
+
+    #[test]
+    fn transcript_confidence_is_weighted_by_tokens() {
+        let segments = [
+            segment(0, &[(" a", 0.2)]),
+            segment(1_000, &[(" b", 1.0), (" c", 1.0), (" d", 1.0)]),
+        ];
+        // The mean of the segment means would be 0.6
+        assert!((transcript_confidence(&segments).unwrap() - 0.8).abs() < 1e-6);
+
+        assert_eq!(transcript_confidence(&[segment(0, &[])]), None);
+        assert_eq!(transcript_confidence(&[]), None);
+    }
+
+    #[test]
+    fn words_are_flagged_by_their_weakest_token() {
+        let transcript = Transcript {
+            segments: vec![
+                segment(
+                    0,
+                    &[(" Hello", 0.9), (" Kub", 0.8), ("ern", 0.3), ("etes", 0.9)],
+                ),
+                segment(2_500, &[(" fine", 0.4), (".", 0.95)]),
+            ],
+            ..Default::default()
+        };
+
+        let words = low_confidence_words(&transcript, 0.5);
+
+        let flagged: Vec<_> = words
+            .iter()
+            .map(|w| (w.word.as_str(), w.probability, w.segment, w.start_ms))
+            .collect();
+        assert_eq!(
+            flagged,
+            [("Kubernetes", 0.3, 0, 0), ("fine.", 0.4, 1, 2_500)]
+        );
+        assert!(low_confidence_words(&transcript, 0.3).is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::error::{AppError, AppResult};
+use crate::models::Cue;
+use crate::pipeline::Transcriber;
+use crate::{sounds, store};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Must match `identifier` in tauri.conf.json, so the CLI finds the app's data
+pub const APP_IDENTIFIER: &str = "com.voice-intelligence";
+
+/// What the core logic needs from its host, so it runs in the Tauri app as well
+/// as in the command line tool.
+pub trait AppContext: Send + Sync {
+    /// Directory holding settings, history, models and recordings
+    fn data_dir(&self) -> PathBuf;
+
+    /// Reports an event to the frontend, or to the terminal in the CLI.
+    fn emit_value(&self, event: &str, payload: Value);
+
+    fn read_clipboard(&self) -> AppResult<String>;
+
+    fn write_clipboard(&self, text: &str) -> AppResult<()>;
+
+    /// Plays `cue` as the sound settings configure it, without blocking.
+    fn play_cue(&self, cue: Cue);
+
+    /// Speech recognizer to use instead of loading the Whisper model
+    fn transcriber(&self) -> Option<Arc<dyn Transcriber>> {
+        None
+    }
+
+    fn emit_event<S: Serialize>(&self, event: &str, payload: S)
+    where
+        Self: Sized,
+    {
+        match serde_json::to_value(payload) {
+            Ok(value) => self.emit_value(event, value),
+            Err(e) => log::error!("Failed to serialize {} event: {}", event, e),
+        }
+    }
+}
+
+impl<R: Runtime> AppContext for AppHandle<R> {
+    fn data_dir(&self) -> PathBuf {
+        self.path()
+            .app_data_dir()
+            .expect("failed to get app data dir")
+    }
+
+    fn emit_value(&self, event: &str, payload: Value) {
+        let _ = self.emit(event, payload);
+    }
+
+    fn read_clipboard(&self) -> AppResult<String> {
+        self.clipboard()
+            .read_text()
+            .map_err(|e| AppError::Clipboard(e.to_string()))
+    }
+
+    fn write_clipboard(&self, text: &str) -> AppResult<()> {
+        self.clipboard()
+            .write_text(text.to_string())
+            .map_err(|e| AppError::Clipboard(e.to_string()))
+    }
+
+    fn play_cue(&self, cue: Cue) {
+        let settings = store::load_data(self)
+            .map(|data| data.settings.sounds)
+            .unwrap_or_default();
+        sounds::play_cue(settings, cue);
+    }
+}
+
+impl<T: AppContext + ?Sized> AppContext for &T {
+    fn data_dir(&self) -> PathBuf {
+        (**self).data_dir()
+    }
+
+    fn emit_value(&self, event: &str, payload: Value) {
+        (**self).emit_value(event, payload)
+    }
+
+    fn read_clipboard(&self) -> AppResult<String> {
+        (**self).read_clipboard()
+    }
+
+    fn write_clipboard(&self, text: &str) -> AppResult<()> {
+        (**self).write_clipboard(text)
+    }
+
+    fn play_cue(&self, cue: Cue) {
+        (**self).play_cue(cue)
+    }
+
+    fn transcriber(&self) -> Option<Arc<dyn Transcriber>> {
+        (**self).transcriber()
+    }
+}
+
+/// Data directory the Tauri app uses, for hosts without an `AppHandle`.
+pub fn default_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER))
+}
@@ -0,0 +1,583 @@
+use crate::error::{AppError, AppResult};
+use crate::models::AudioProcessingSettings;
+use std::path::Path;
+
+// Time constants for the level detectors (in seconds)
+const DC_BLOCKER_POLE: f32 = 0.995;
+const LEVEL_WINDOW_SECS: f32 = 0.3;
+const GAIN_SMOOTHING_SECS: f32 = 0.5;
+const GATE_ATTACK_SECS: f32 = 0.005;
+const GATE_RELEASE_SECS: f32 = 0.15;
+const GATE_HOLD_SECS: f32 = 0.2;
+// Gain applied to gated samples (about -40 dB) instead of hard muting,
+// which avoids audible clicks when the gate opens and closes
+const GATE_FLOOR: f32 = 0.01;
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn smoothing_coef(secs: f32, sample_rate: u32) -> f32 {
+    (-1.0 / (secs * sample_rate as f32)).exp()
+}
+
+/// First-order DC blocker: y[n] = x[n] - x[n-1] + R * y[n-1]
+#[derive(Debug, Clone, Default)]
+struct DcBlocker {
+    prev_input: f32,
+    prev_output: f32,
+}
+
+impl DcBlocker {
+    fn process(&mut self, x: f32) -> f32 {
+        let y = x - self.prev_input + DC_BLOCKER_POLE * self.prev_output;
+        self.prev_input = x;
+        self.prev_output = y;
+        y
+    }
+}
+
+/// Second-order Butterworth high-pass (RBJ cookbook biquad)
+#[derive(Debug, Clone)]
+struct HighPass {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl HighPass {
+    fn new(cutoff_hz: f32, sample_rate: u32) -> Self {
+        // Keep the cutoff safely below Nyquist
+        let nyquist = sample_rate as f32 / 2.0;
+        let cutoff = cutoff_hz.clamp(1.0, nyquist * 0.9);
+
+        let omega = 2.0 * std::f32::consts::PI * cutoff / sample_rate as f32;
+        let (sin, cos) = omega.sin_cos();
+        let alpha = sin / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let a0 = 1.0 + alpha;
+
+        Self {
+            b0: ((1.0 + cos) / 2.0) / a0,
+            b1: (-(1.0 + cos)) / a0,
+            b2: ((1.0 + cos) / 2.0) / a0,
+            a1: (-2.0 * cos) / a0,
+            a2: (1.0 - alpha) / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1
+            - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+/// Noise gate driven by a peak envelope follower, with hold time so that
+/// short pauses between words are not chopped.
+#[derive(Debug, Clone)]
+struct NoiseGate {
+    threshold: f32,
+    attack_coef: f32,
+    release_coef: f32,
+    hold_samples: u32,
+    envelope: f32,
+    gain: f32,
+    hold_counter: u32,
+}
+
+impl NoiseGate {
+    fn new(threshold_dbfs: f32, sample_rate: u32) -> Self {
+        Self {
+            threshold: db_to_linear(threshold_dbfs),
+            attack_coef: smoothing_coef(GATE_ATTACK_SECS, sample_rate),
+            release_coef: smoothing_coef(GATE_RELEASE_SECS, sample_rate),
+            hold_samples: (GATE_HOLD_SECS * sample_rate as f32) as u32,
+            envelope: 0.0,
+            gain: GATE_FLOOR,
+            hold_counter: 0,
+        }
+    }
+
+    /// Updates the gate with the current frame level and returns the gain to apply.
+    fn process(&mut self, level: f32) -> f32 {
+        let coef = if level > self.envelope {
+            self.attack_coef
+        } else {
+            self.release_coef
+        };
+        self.envelope = coef * self.envelope + (1.0 - coef) * level;
+
+        let target = if self.envelope >= self.threshold {
+            self.hold_counter = self.hold_samples;
+            1.0
+        } else if self.hold_counter > 0 {
+            self.hold_counter -= 1;
+            1.0
+        } else {
+            GATE_FLOOR
+        };
+
+        // Open fast, close slowly
+        let coef = if target > self.gain {
+            self.attack_coef
+        } else {
+            self.release_coef
+        };
+        self.gain = coef * self.gain + (1.0 - coef) * target;
+        self.gain
+    }
+
+    fn is_open(&self) -> bool {
+        self.envelope >= self.threshold || self.hold_counter > 0
+    }
+}
+
+/// Automatic gain control towards a target RMS level.
+#[derive(Debug, Clone)]
+struct Normalizer {
+    target: f32,
+    max_gain: f32,
+    level_coef: f32,
+    gain_coef: f32,
+    mean_square: f32,
+    gain: f32,
+}
+
+impl Normalizer {
+    fn new(target_dbfs: f32, max_gain_db: f32, sample_rate: u32) -> Self {
+        Self {
+            target: db_to_linear(target_dbfs),
+            max_gain: db_to_linear(max_gain_db.max(0.0)),
+            level_coef: smoothing_coef(LEVEL_WINDOW_SECS, sample_rate),
+            gain_coef: smoothing_coef(GAIN_SMOOTHING_SECS, sample_rate),
+            mean_square: 0.0,
+            gain: 1.0,
+        }
+    }
+
+    /// Updates the level estimate and returns the gain to apply. When `adapt` is
+    /// false (e.g. the noise gate is closed) the current gain is held so that
+    /// background noise is not amplified during pauses.
+    fn process(&mut self, level: f32, adapt: bool) -> f32 {
+        if adapt {
+            self.mean_square =
+                self.level_coef * self.mean_square + (1.0 - self.level_coef) * level * level;
+            let rms = self.mean_square.sqrt();
+            if rms > f32::EPSILON {
+                let desired = (self.target / rms).min(self.max_gain);
+                self.gain = self.gain_coef * self.gain + (1.0 - self.gain_coef) * desired;
+            }
+        }
+        self.gain
+    }
+}
+
+/// Optional preprocessing applied to captured audio before it is written to disk.
+///
+/// Stages run in a fixed order: DC removal, high-pass, noise gate, normalization.
+/// Filters keep per-channel state; the gate and normalizer operate on the frame
+/// level so all channels receive the same gain.
+#[derive(Debug, Clone)]
+pub struct DspChain {
+    channels: usize,
+    dc_blockers: Option<Vec<DcBlocker>>,
+    high_passes: Option<Vec<HighPass>>,
+    gate: Option<NoiseGate>,
+    normalizer: Option<Normalizer>,
+}
+
+impl DspChain {
+    /// Builds a chain for the given stream format. Returns `None` when processing
+    /// is disabled or no stage is enabled, so callers can skip the chain entirely.
+    pub fn new(
+        settings: &AudioProcessingSettings,
+        sample_rate: u32,
+        channels: u16,
+    ) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+
+        let channels = channels.max(1) as usize;
+        let chain = Self {
+            channels,
+            dc_blockers: settings
+                .dc_removal
+                .then(|| vec![DcBlocker::default(); channels]),
+            high_passes: settings
+                .high_pass_enabled
+                .then(|| vec![HighPass::new(settings.high_pass_cutoff_hz, sample_rate); channels]),
+            gate: settings
+                .noise_gate_enabled
+                .then(|| NoiseGate::new(settings.noise_gate_threshold_dbfs, sample_rate)),
+            normalizer: settings.normalization_enabled.then(|| {
+                Normalizer::new(
+                    settings.normalization_target_dbfs,
+                    settings.normalization_max_gain_db,
+                    sample_rate,
+                )
+            }),
+        };
+
+        if chain.dc_blockers.is_none()
+            && chain.high_passes.is_none()
+            && chain.gate.is_none()
+            && chain.normalizer.is_none()
+        {
+            return None;
+        }
+
+        Some(chain)
+    }
+
+    /// Processes interleaved samples in place.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for frame in samples.chunks_mut(self.channels) {
+            let mut peak = 0.0f32;
+            for (ch, sample) in frame.iter_mut().enumerate() {
+                let mut x = *sample;
+                if let Some(dc) = self.dc_blockers.as_mut() {
+                    x = dc[ch].process(x);
+                }
+                if let Some(hp) = self.high_passes.as_mut() {
+                    x = hp[ch].process(x);
+                }
+                *sample = x;
+                peak = peak.max(x.abs());
+            }
+
+            let mut gain = 1.0;
+            let mut gate_open = true;
+            if let Some(gate) = self.gate.as_mut() {
+                gain *= gate.process(peak);
+                gate_open = gate.is_open();
+            }
+            if let Some(normalizer) = self.normalizer.as_mut() {
+                gain *= normalizer.process(peak, gate_open);
+            }
+
+            if gain != 1.0 {
+                for sample in frame.iter_mut() {
+                    *sample = (*sample * gain).clamp(-1.0, 1.0);
+                }
+            }
+        }
+    }
+}
+
+/// Runs the chain over an existing WAV file and writes the result as 16-bit PCM.
+/// Useful for checking settings against fixture recordings.
+pub fn process_wav_file(
+    input: &Path,
+    output: &Path,
+    settings: &AudioProcessingSettings,
+) -> AppResult<()> {
+    let decode_error = |e: hound::Error| AppError::AudioDecode(format!("{:?}: {}", input, e));
+    let mut reader = hound::WavReader::open(input).map_err(decode_error)?;
+    let spec = reader.spec();
+
+    // Scaled by the bit depth (8, 16, 24 or 32 bit), like the recordings Whisper reads
+    let mut samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Int => {
+            let scale = (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|x| x as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(decode_error)?
+        }
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(decode_error)?,
+    };
+
+    if let Some(mut chain) = DspChain::new(settings, spec.sample_rate, spec.channels) {
+        chain.process(&mut samples);
+    }
+
+    let out_spec = hound::WavSpec {
+        channels: spec.channels,
+        sample_rate: spec.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let write_error = |e: hound::Error| AppError::Storage(format!("{:?}: {}", output, e));
+    let mut writer = hound::WavWriter::create(output, out_spec).map_err(write_error)?;
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        writer.write_sample(value).map_err(write_error)?;
+    }
+    writer.finalize().map_err(write_error)?;
+
+    Ok(())
+}
+
+// Frame length used for level analysis
+const ANALYSIS_FRAME_MS: u32 = 20;
+
+/// Level statistics of a recording, used to reject empty or silent input
+#[derive(Debug, Clone, Copy)]
+pub struct SignalStats {
+    pub duration_ms: u64,
+    /// RMS over the whole recording in dBFS
+    pub rms_dbfs: f32,
+    /// Total length of frames louder than the given threshold
+    pub voiced_ms: u64,
+}
+
+/// Analyzes mono samples. `threshold_dbfs` decides which frames count as voiced.
+pub fn analyze_signal(samples: &[f32], sample_rate: u32, threshold_dbfs: f32) -> SignalStats {
+    let duration_ms = samples.len() as u64 * 1000 / sample_rate.max(1) as u64;
+    if samples.is_empty() {
+        return SignalStats {
+            duration_ms,
+            rms_dbfs: f32::NEG_INFINITY,
+            voiced_ms: 0,
+        };
+    }
+
+    let mean_square = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+    let threshold = db_to_linear(threshold_dbfs);
+    let frame_len = (sample_rate * ANALYSIS_FRAME_MS / 1000).max(1) as usize;
+    let voiced_frames = samples
+        .chunks(frame_len)
+        .filter(|frame| {
+            let ms = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+            ms.sqrt() >= threshold
+        })
+        .count();
+
+    SignalStats {
+        duration_ms,
+        rms_dbfs: 20.0 * mean_square.sqrt().max(f32::MIN_POSITIVE).log10(),
+        voiced_ms: voiced_frames as u64 * ANALYSIS_FRAME_MS as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn sine(frequency: f32, amplitude: f32, secs: f32) -> Vec<f32> {
+        let step = 2.0 * std::f32::consts::PI * frequency / RATE as f32;
+        (0..(secs * RATE as f32) as usize)
+            .map(|i| amplitude * (step * i as f32).sin())
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// The second half of `samples`, after the filters have settled
+    fn settled(samples: &[f32]) -> &[f32] {
+        &samples[samples.len() / 2..]
+    }
+
+    fn only_normalization() -> AudioProcessingSettings {
+        AudioProcessingSettings {
+            enabled: true,
+            dc_removal: false,
+            high_pass_enabled: false,
+            noise_gate_enabled: false,
+            ..AudioProcessingSettings::default()
+        }
+    }
+
+    fn write_wav(path: &Path, spec: hound::WavSpec, samples: &[f32]) {
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        let scale = ((1u64 << (spec.bits_per_sample - 1)) - 1) as f32;
+        for &s in samples {
+            match spec.sample_format {
+                hound::SampleFormat::Int => {
+                    writer.write_sample((s * scale).round() as i32).unwrap()
+                }
+                hound::SampleFormat::Float => writer.write_sample(s).unwrap(),
+            }
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn read_wav(path: &Path) -> Vec<f32> {
+        hound::WavReader::open(path)
+            .unwrap()
+            .samples::<i16>()
+            .map(|s| s.unwrap() as f32 / 32768.0)
+            .collect()
+    }
+
+    fn spec(bits_per_sample: u16, sample_format: hound::SampleFormat) -> hound::WavSpec {
+        hound::WavSpec {
+            channels: 1,
+            sample_rate: RATE,
+            bits_per_sample,
+            sample_format,
+        }
+    }
+
+    #[test]
+    fn dc_blocker_removes_an_offset() {
+        let tone = sine(440.0, 0.25, 2.0);
+        let mut dc = DcBlocker::default();
+        let out: Vec<f32> = tone.iter().map(|s| dc.process(s + 0.5)).collect();
+
+        let mean = settled(&out).iter().sum::<f32>() / settled(&out).len() as f32;
+        assert!(mean.abs() < 0.01, "mean {}", mean);
+        assert!((rms(settled(&out)) - rms(&tone)).abs() < 0.01);
+    }
+
+    #[test]
+    fn high_pass_removes_rumble_and_keeps_speech() {
+        let rumble = sine(20.0, 0.5, 2.0);
+        let mut hp = HighPass::new(80.0, RATE);
+        let out: Vec<f32> = rumble.iter().map(|&s| hp.process(s)).collect();
+        assert!(rms(settled(&out)) < 0.1 * rms(&rumble));
+
+        let speech = sine(1000.0, 0.5, 2.0);
+        let mut hp = HighPass::new(80.0, RATE);
+        let out: Vec<f32> = speech.iter().map(|&s| hp.process(s)).collect();
+        assert!(rms(settled(&out)) > 0.95 * rms(&speech));
+    }
+
+    #[test]
+    fn noise_gate_opens_for_loud_input_only() {
+        let mut gate = NoiseGate::new(-50.0, RATE);
+        let seconds = |secs: f32| (secs * RATE as f32) as usize;
+
+        let mut gain = 0.0;
+        for _ in 0..seconds(1.0) {
+            gain = gate.process(0.001);
+        }
+        assert!(gain < 2.0 * GATE_FLOOR, "quiet gain {}", gain);
+        assert!(!gate.is_open());
+
+        for _ in 0..seconds(0.1) {
+            gain = gate.process(0.3);
+        }
+        assert!(gain > 0.99, "loud gain {}", gain);
+        assert!(gate.is_open());
+
+        for _ in 0..seconds(3.0) {
+            gain = gate.process(0.0);
+        }
+        assert!(gain < 2.0 * GATE_FLOOR, "released gain {}", gain);
+    }
+
+    #[test]
+    fn normalizer_reaches_the_target_within_the_gain_limit() {
+        // -37 dBFS needs about 17 dB to reach -20 dBFS
+        let mut quiet = sine(440.0, 0.02, 6.0);
+        DspChain::new(&only_normalization(), RATE, 1)
+            .unwrap()
+            .process(&mut quiet);
+        let level = rms(settled(&quiet));
+        assert!((0.089..0.112).contains(&level), "level {}", level);
+
+        // -63 dBFS would need 43 dB, but only 24 dB are allowed
+        let mut silent = sine(440.0, 0.001, 6.0);
+        DspChain::new(&only_normalization(), RATE, 1)
+            .unwrap()
+            .process(&mut silent);
+        let expected = rms(&sine(440.0, 0.001, 1.0)) * db_to_linear(24.0);
+        let level = rms(settled(&silent));
+        assert!((level / expected - 1.0).abs() < 0.1, "level {}", level);
+    }
+
+    #[test]
+    fn disabled_chain_is_skipped() {
+        assert!(DspChain::new(&AudioProcessingSettings::default(), RATE, 1).is_none());
+        let no_stages = AudioProcessingSettings {
+            normalization_enabled: false,
+            ..only_normalization()
+        };
+        assert!(DspChain::new(&no_stages, RATE, 1).is_none());
+    }
+
+    #[test]
+    fn wav_files_of_every_bit_depth_are_processed() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.wav");
+        let output = dir.path().join("output.wav");
+        let tone = sine(1000.0, 0.5, 4.0);
+        let processing = AudioProcessingSettings {
+            enabled: true,
+            ..AudioProcessingSettings::default()
+        };
+
+        for (bits, format) in [
+            (8, hound::SampleFormat::Int),
+            (16, hound::SampleFormat::Int),
+            (24, hound::SampleFormat::Int),
+            (32, hound::SampleFormat::Int),
+            (32, hound::SampleFormat::Float),
+        ] {
+            write_wav(&input, spec(bits, format), &tone);
+
+            // Without processing the level is unchanged
+            process_wav_file(&input, &output, &AudioProcessingSettings::default()).unwrap();
+            let level = rms(&read_wav(&output));
+            assert!(
+                (level / rms(&tone) - 1.0).abs() < 0.02,
+                "{}-bit {:?}: {}",
+                bits,
+                format,
+                level
+            );
+
+            // The chain normalizes the tone to -20 dBFS
+            process_wav_file(&input, &output, &processing).unwrap();
+            let level = rms(settled(&read_wav(&output)));
+            assert!(
+                (0.089..0.112).contains(&level),
+                "{}-bit {:?}: {}",
+                bits,
+                format,
+                level
+            );
+        }
+    }
+
+    #[test]
+    fn unreadable_wav_files_report_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.wav");
+        let output = dir.path().join("output.wav");
+        write_wav(
+            &input,
+            spec(24, hound::SampleFormat::Int),
+            &sine(440.0, 0.5, 0.1),
+        );
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&input)
+            .unwrap();
+        file.set_len(file.metadata().unwrap().len() - 1).unwrap();
+
+        let err =
+            process_wav_file(&input, &output, &AudioProcessingSettings::default()).unwrap_err();
+        assert_eq!(err.code(), "audio_decode");
+
+        let missing = dir.path().join("missing.wav");
+        let err =
+            process_wav_file(&missing, &output, &AudioProcessingSettings::default()).unwrap_err();
+        assert_eq!(err.code(), "audio_decode");
+    }
+}
@@ -0,0 +1,157 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/// Error type shared by commands and the pipeline.
+///
+/// Serializes to `{ code, message, details }` so the frontend can react to
+/// specific failures (e.g. offer "Start Ollama" for `ollama_unreachable`).
+/// `details` carries the underlying error text, if any.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("The Whisper model could not be loaded. It may be missing or corrupted.")]
+    ModelMissing(String),
+
+    #[error("Failed to download the Whisper model. Please check your internet connection and free disk space.")]
+    DownloadFailed(String),
+
+    #[error("Could not connect to Ollama. Please ensure Ollama is running.")]
+    OllamaUnreachable(String),
+
+    #[error("Model '{0}' is not installed. Please run 'ollama pull {0}' in your terminal.")]
+    ModelNotInstalled(String),
+
+    #[error("Ollama returned an unexpected response. Please check Ollama version and model compatibility.")]
+    Ollama(String),
+
+    #[error("Audio device error.")]
+    AudioDevice(String),
+
+    #[error("Input device '{0}' was not found. Please check your microphone settings.")]
+    InputDeviceNotFound(String),
+
+    #[error("The audio file could not be decoded.")]
+    AudioDecode(String),
+
+    #[error("The {0} recording was not found. Please record again.")]
+    RecordingMissing(String),
+
+    #[error("The {label} recording is too short ({duration_ms} ms). Please record again.")]
+    RecordingTooShort { label: String, duration_ms: u64 },
+
+    #[error("The {0} recording is silent. Please check that your microphone is not muted.")]
+    RecordingSilent(String),
+
+    #[error("No speech was recognized in the {0}. Please try again.")]
+    NoSpeech(String),
+
+    #[error("The instruction was not understood clearly (confidence {:.0}%). Please repeat it.", .0 * 100.0)]
+    LowConfidence(f32),
+
+    #[error("Transcription failed.")]
+    Transcription(String),
+
+    #[error("Failed to access the clipboard.")]
+    Clipboard(String),
+
+    #[error("Failed to paste into the active window.")]
+    AutoPaste(String),
+
+    #[error("Failed to deliver the result.")]
+    Output(String),
+
+    #[error("Failed to read or write app data.")]
+    Storage(String),
+
+    #[error("Failed to register hotkey '{0}'.")]
+    Hotkey(String, String),
+
+    #[error("{0}")]
+    InvalidSettings(String),
+
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[error("{0} not found.")]
+    NotFound(String),
+
+    #[error("Pipeline cancelled by user")]
+    Cancelled,
+
+    #[error("An unexpected internal error occurred.")]
+    Internal(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::ModelMissing(_) => "model_missing",
+            AppError::DownloadFailed(_) => "download_failed",
+            AppError::OllamaUnreachable(_) => "ollama_unreachable",
+            AppError::ModelNotInstalled(_) => "model_not_installed",
+            AppError::Ollama(_) => "ollama_error",
+            AppError::AudioDevice(_) => "audio_device",
+            AppError::InputDeviceNotFound(_) => "input_device_not_found",
+            AppError::AudioDecode(_) => "audio_decode",
+            AppError::RecordingMissing(_) => "recording_missing",
+            AppError::RecordingTooShort { .. } => "recording_too_short",
+            AppError::RecordingSilent(_) => "recording_silent",
+            AppError::NoSpeech(_) => "no_speech",
+            AppError::LowConfidence(_) => "low_confidence",
+            AppError::Transcription(_) => "transcription",
+            AppError::Clipboard(_) => "clipboard",
+            AppError::AutoPaste(_) => "auto_paste",
+            AppError::Output(_) => "output",
+            AppError::Storage(_) => "storage",
+            AppError::Hotkey(..) => "hotkey",
+            AppError::InvalidSettings(_) => "invalid_settings",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::NotFound(_) => "not_found",
+            AppError::Cancelled => "cancelled",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    /// Underlying error text for variants that wrap another error.
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            AppError::ModelMissing(d)
+            | AppError::DownloadFailed(d)
+            | AppError::OllamaUnreachable(d)
+            | AppError::Ollama(d)
+            | AppError::AudioDevice(d)
+            | AppError::AudioDecode(d)
+            | AppError::Transcription(d)
+            | AppError::Clipboard(d)
+            | AppError::AutoPaste(d)
+            | AppError::Output(d)
+            | AppError::Storage(d)
+            | AppError::Hotkey(_, d)
+            | AppError::Internal(d) => Some(d.as_str()),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Storage(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::Storage(e.to_string())
+    }
+}
+
+pub type AppResult<T> = Result<T, AppError>;
@@ -0,0 +1,152 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{SubtitleFormat, Transcript};
+
+/// Renders a transcript in the requested subtitle format.
+pub fn render_transcript(transcript: &Transcript, format: SubtitleFormat) -> AppResult<String> {
+    match format {
+        SubtitleFormat::Srt => to_srt(transcript),
+        SubtitleFormat::Vtt => to_vtt(transcript),
+        SubtitleFormat::Json => Ok(serde_json::to_string_pretty(transcript)?),
+    }
+}
+
+pub fn to_srt(transcript: &Transcript) -> AppResult<String> {
+    let mut out = String::new();
+    for (i, (start, end, text)) in subtitle_segments(transcript)?.enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_timestamp(start, ','),
+            format_timestamp(end, ','),
+            text
+        ));
+    }
+    Ok(out)
+}
+
+pub fn to_vtt(transcript: &Transcript) -> AppResult<String> {
+    let mut out = String::from("WEBVTT\n\n");
+    for (start, end, text) in subtitle_segments(transcript)? {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(start, '.'),
+            format_timestamp(end, '.'),
+            text
+        ));
+    }
+    Ok(out)
+}
+
+/// Non-empty segments as (start_ms, end_ms, text). A transcript without segments
+/// becomes a single cue spanning the recording; transcripts stored by older
+/// versions have neither segments nor a duration and cannot be timed.
+fn subtitle_segments(
+    transcript: &Transcript,
+) -> AppResult<Box<dyn Iterator<Item = (u64, u64, &str)> + '_>> {
+    if transcript.segments.is_empty() {
+        let text = transcript.text.trim();
+        if text.is_empty() {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        let duration_ms = transcript.duration_ms.ok_or_else(|| {
+            AppError::InvalidInput(
+                "This transcript has no timing; export it as JSON instead".to_string(),
+            )
+        })?;
+        return Ok(Box::new(std::iter::once((0, duration_ms, text))));
+    }
+
+    Ok(Box::new(
+        transcript
+            .segments
+            .iter()
+            .map(|s| (s.start_ms, s.end_ms.max(s.start_ms), s.text.trim()))
+            .filter(|(_, _, text)| !text.is_empty()),
+    ))
+}
+
+/// Formats milliseconds as HH:MM:SS,mmm (SRT) or HH:MM:SS.mmm (WebVTT).
+fn format_timestamp(ms: u64, separator: char) -> String {
+    let hours = ms / 3_600_000;
+    let minutes = (ms % 3_600_000) / 60_000;
+    let seconds = (ms % 60_000) / 1000;
+    let millis = ms % 1000;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        hours, minutes, seconds, separator, millis
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TranscriptSegment;
+
+    fn segment(start_ms: u64, end_ms: u64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn transcript() -> Transcript {
+        Transcript {
+            text: "Hello there. General Kenobi.".to_string(),
+            segments: vec![
+                segment(0, 1_500, " Hello there."),
+                segment(1_500, 1_600, "  "),
+                segment(3_723_004, 3_725_010, "General Kenobi."),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn srt_numbers_cues_and_uses_commas() {
+        assert_eq!(
+            to_srt(&transcript()).unwrap(),
+            "1\n00:00:00,000 --> 00:00:01,500\nHello there.\n\n\
+             2\n01:02:03,004 --> 01:02:05,010\nGeneral Kenobi.\n\n"
+        );
+    }
+
+    #[test]
+    fn vtt_has_a_header_and_uses_dots() {
+        assert_eq!(
+            to_vtt(&transcript()).unwrap(),
+            "WEBVTT\n\n\
+             00:00:00.000 --> 00:00:01.500\nHello there.\n\n\
+             01:02:03.004 --> 01:02:05.010\nGeneral Kenobi.\n\n"
+        );
+    }
+
+    #[test]
+    fn json_export_round_trips() {
+        let json = render_transcript(&transcript(), SubtitleFormat::Json).unwrap();
+        let parsed: Transcript = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.text, "Hello there. General Kenobi.");
+        assert_eq!(parsed.segments.len(), 3);
+        assert_eq!(parsed.segments[2].start_ms, 3_723_004);
+    }
+
+    #[test]
+    fn untimed_transcripts_span_the_recording() {
+        let mut transcript = Transcript {
+            text: "Hello there.".to_string(),
+            duration_ms: Some(2_250),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_srt(&transcript).unwrap(),
+            "1\n00:00:00,000 --> 00:00:02,250\nHello there.\n\n"
+        );
+
+        // Stored by a version that kept neither segments nor the duration
+        transcript.duration_ms = None;
+        assert_eq!(to_vtt(&transcript).unwrap_err().code(), "invalid_input");
+
+        assert_eq!(to_vtt(&Transcript::default()).unwrap(), "WEBVTT\n\n");
+    }
+}
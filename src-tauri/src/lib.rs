@@ -1,24 +1,42 @@
 pub mod audio;
+pub mod batch;
 pub mod commands;
+pub mod confidence;
+pub mod context;
+pub mod dsp;
+pub mod error;
+pub mod export;
 pub mod logic;
+#[cfg(any(test, feature = "test-support"))]
+pub mod mock;
 pub mod models;
 pub mod ollama;
+pub mod output;
+pub mod paste;
 pub mod pipeline;
+pub mod progress;
+pub mod queue;
+pub mod sounds;
+pub mod state_machine;
 pub mod store;
 
 use audio::AudioState;
-use logic::LogicState;
+use batch::BatchState;
+use logic::{EventThread, LogicState};
+use pipeline::PipelineState;
+use queue::SessionQueue;
+use state_machine::AppStatus;
 use tauri::AppHandle;
 use tauri::Manager;
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
 };
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tauri_plugin_global_shortcut::ShortcutState;
 
 fn persist_idle_status(app: &AppHandle) {
     // Best-effort: ensure we never persist a non-idle status across app restarts.
-    if let Err(e) = store::set_status(app, "idle") {
+    if let Err(e) = store::set_status(app, AppStatus::Idle) {
         log::error!("Failed to persist idle status on shutdown/startup: {}", e);
     }
 }
@@ -32,12 +50,22 @@ fn manual_trigger(app: tauri::AppHandle) {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_dialog::init())
         .manage(AudioState::new())
         .manage(LogicState::new())
+        .manage(EventThread::default())
+        .manage(BatchState::new())
+        .manage(PipelineState::new())
+        .manage(SessionQueue::new())
         .invoke_handler(tauri::generate_handler![
             commands::get_current_status,
+            commands::get_state_machine,
+            commands::cancel_recording,
+            commands::toggle_recording_pause,
+            commands::get_queued_sessions,
             commands::get_models,
             commands::get_input_devices,
+            commands::get_output_devices,
             commands::play_test_sound,
             commands::get_settings,
             commands::save_settings,
@@ -47,6 +75,17 @@ pub fn run() {
             commands::delete_history_item,
             commands::get_app_info,
             commands::stop_pipeline,
+            commands::import_audio_file,
+            commands::start_batch,
+            commands::get_batch_jobs,
+            commands::pause_batch,
+            commands::resume_batch,
+            commands::cancel_batch_job,
+            commands::cancel_batch,
+            commands::clear_finished_batch_jobs,
+            commands::export_vocabulary,
+            commands::import_vocabulary,
+            commands::export_transcript,
             manual_trigger
         ])
         .setup(|app| {
@@ -80,31 +119,40 @@ pub fn run() {
                 }
             );
 
+            // Hotkeys and commands hand their events to this thread
+            logic::start_event_thread(app.handle());
+
             #[cfg(desktop)]
             {
                 let handle = app.handle();
                 handle.plugin(
                     tauri_plugin_global_shortcut::Builder::new()
-                        .with_handler(|app, _shortcut, event| {
+                        .with_handler(|app, shortcut, event| {
                             if event.state() == ShortcutState::Pressed {
-                                logic::handle_trigger(app);
+                                if commands::is_pause_hotkey(app, shortcut) {
+                                    logic::toggle_pause(app);
+                                } else {
+                                    logic::handle_trigger(app);
+                                }
                             }
                         })
                         .build(),
                 )?;
 
                 let data = store::load_data(handle).unwrap_or_default();
-                if let Err(e) = handle
-                    .global_shortcut()
-                    .register(data.settings.hotkey.as_str())
-                {
-                    log::error!("Failed to register hotkey: {}", e);
+                if let Err(e) = commands::register_hotkeys(handle, &data.settings) {
+                    log::error!("Failed to register hotkey: {} {:?}", e, e.details());
                 }
 
                 // If the previous session ended while mid-flow, force-reset persisted status.
                 // This also prevents stale "processing"/"instruction"/"content" state on next start.
                 persist_idle_status(handle);
 
+                // Pick up batch jobs and recorded sessions left over from the previous run
+                batch::restore(handle);
+                queue::start_worker(handle);
+                queue::restore(handle);
+
                 // Tray Icon Setup
                 let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
                 let show_i = MenuItem::with_id(app, "show", "Show UI", true, None::<&str>)?;
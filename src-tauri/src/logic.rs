@@ -1,31 +1,44 @@
-use crate::audio::{play_sound, start_recording, stop_recording, AudioState};
-use crate::store::{get_status, load_data, set_status as store_set_status};
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use crate::audio::{
+    AudioState, Interruption, InterruptionHandler, Recorder, RecordingHandlers, RecordingOptions,
+    TimeoutHandler, TimeoutWarningHandler,
+};
+use crate::context::AppContext;
+use crate::error::AppError;
+use crate::models::Cue;
+use crate::queue::{self, RecordingGap, SessionQueue};
+use crate::state_machine::{
+    AppStatus, Effect, GuardContext, RecordingEvent, StateMachine, StateMachineSnapshot, Transition,
+};
+use crate::store::{load_data, set_status as store_set_status};
+use serde::Serialize;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub enum AppStatus {
-    #[default]
-    Idle,
-    Instruction,
-    Content,
-    Processing,
-}
-
-impl AppStatus {
-    fn as_str(&self) -> &'static str {
-        match self {
-            AppStatus::Idle => "idle",
-            AppStatus::Instruction => "instruction",
-            AppStatus::Content => "content",
-            AppStatus::Processing => "processing",
-        }
-    }
+/// Payload of `status-changed` and result of `get_current_status`
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusSnapshot {
+    pub recording: AppStatus,
+    /// The current recording is paused
+    pub paused: bool,
+    /// Sessions waiting for or undergoing processing
+    pub queued_jobs: usize,
+    /// Session the worker is processing right now
+    pub active_session: Option<String>,
 }
 
 pub struct LogicState {
-    pub status: Arc<Mutex<AppStatus>>,
+    pub machine: Mutex<StateMachine>,
+    /// Session the current recording belongs to
+    pub session_id: Mutex<Option<String>>,
+    /// Device failures during the session's recordings
+    pub gaps: Mutex<Vec<RecordingGap>>,
+    /// The current recording is paused
+    pub paused: Mutex<bool>,
+    /// Counts the recordings started, so callbacks of earlier recordings can be
+    /// told apart. Only changed while the machine lock is held.
+    pub recording_id: Mutex<u64>,
 }
 
 impl Default for LogicState {
@@ -37,92 +50,498 @@ impl Default for LogicState {
 impl LogicState {
     pub fn new() -> Self {
         Self {
-            status: Arc::new(Mutex::new(AppStatus::Idle)),
+            machine: Mutex::new(StateMachine::new()),
+            session_id: Mutex::new(None),
+            gaps: Mutex::new(Vec::new()),
+            paused: Mutex::new(false),
+            recording_id: Mutex::new(0),
+        }
+    }
+
+    pub fn status(&self) -> AppStatus {
+        self.machine.lock().unwrap().state()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.lock().unwrap()
+    }
+
+    /// Id of the latest recording started
+    pub fn recording_id(&self) -> u64 {
+        *self.recording_id.lock().unwrap()
+    }
+}
+
+/// Work for the event thread
+type EventJob = Box<dyn FnOnce(&AppHandle) + Send>;
+
+/// Runs events from hotkeys and commands on a thread of their own, in the order
+/// they arrive. Stopping a recording waits until its file is finalized, which
+/// must not block the global-shortcut handler.
+#[derive(Default)]
+pub struct EventThread {
+    sender: Mutex<Option<Sender<EventJob>>>,
+}
+
+/// Starts the thread that runs the events of `handle_trigger`, `toggle_pause`
+/// and `discard_recording`.
+pub fn start_event_thread(app: &AppHandle) {
+    let (sender, receiver) = mpsc::channel::<EventJob>();
+    *app.state::<EventThread>().sender.lock().unwrap() = Some(sender);
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for job in receiver {
+            job(&app);
+        }
+    });
+}
+
+/// Runs `job` on the event thread, or right away if the thread is not running.
+fn post_event(app: &AppHandle, job: impl FnOnce(&AppHandle) + Send + 'static) {
+    let sender = app.state::<EventThread>().sender.lock().unwrap().clone();
+    let job: EventJob = Box::new(job);
+    match sender {
+        Some(sender) => {
+            if let Err(mpsc::SendError(job)) = sender.send(job) {
+                log::error!("Event thread stopped, handling the event here");
+                job(app);
+            }
         }
+        None => job(app),
     }
 }
 
 pub fn handle_trigger(app: &AppHandle) {
+    post_event(app, |app| {
+        handle_event(app, RecordingEvent::Trigger);
+    });
+}
+
+/// Pauses the current recording, or resumes it if it is paused.
+pub fn toggle_pause(app: &AppHandle) {
+    post_event(app, |app| {
+        let event = if app.state::<LogicState>().is_paused() {
+            RecordingEvent::Resume
+        } else {
+            RecordingEvent::Pause
+        };
+        handle_event(app, event);
+    });
+}
+
+/// Stops the current recording and discards its session.
+pub fn discard_recording(app: &AppHandle) {
+    post_event(app, |app| {
+        handle_event(app, RecordingEvent::Cancel);
+    });
+}
+
+/// Feeds `event` to the state machine of the running app.
+pub fn handle_event(app: &AppHandle, event: RecordingEvent) -> Option<Transition> {
     let audio_state = app.state::<AudioState>();
+    let logic_state = app.state::<LogicState>();
+    let queue = app.state::<SessionQueue>();
+    dispatch(
+        app,
+        &logic_state,
+        &*audio_state,
+        &queue,
+        &|| recording_handlers(app),
+        event,
+    )
+}
 
-    // Get current status from JSON
-    let current_status_str = match get_status(app) {
-        Ok(s) => s,
-        Err(e) => {
-            log::error!("Failed to get status from JSON: {}", e);
-            return;
+/// Advances the recording state machine and performs the effect of the
+/// transition. `handlers` creates the callbacks for each recording that is
+/// started. Returns `None` if the event is not allowed in the current state.
+pub fn dispatch<C: AppContext>(
+    ctx: &C,
+    logic_state: &LogicState,
+    recorder: &dyn Recorder,
+    queue: &SessionQueue,
+    handlers: &dyn Fn() -> RecordingHandlers,
+    event: RecordingEvent,
+) -> Option<Transition> {
+    dispatch_for(ctx, logic_state, recorder, queue, handlers, event, None)
+}
+
+/// Like `dispatch`, for an event that only applies to the recording
+/// `recording_id` if given.
+fn dispatch_for<C: AppContext>(
+    ctx: &C,
+    logic_state: &LogicState,
+    recorder: &dyn Recorder,
+    queue: &SessionQueue,
+    handlers: &dyn Fn() -> RecordingHandlers,
+    event: RecordingEvent,
+    recording_id: Option<u64>,
+) -> Option<Transition> {
+    let settings = load_data(ctx).map(|data| data.settings).unwrap_or_default();
+    let (state, transition) = {
+        let mut machine = logic_state.machine.lock().unwrap();
+        // Checked under the machine lock, so no other recording can start in between
+        if let Some(id) = recording_id.filter(|id| *id != logic_state.recording_id()) {
+            log::warn!("Ignoring {:?} of earlier recording {}", event, id);
+            return None;
+        }
+        let recording = machine.state();
+        let guards = GuardContext {
+            has_session: logic_state.session_id.lock().unwrap().is_some(),
+            captured_audio: logic_state.gaps.lock().unwrap().iter().any(|gap| {
+                gap.recording == recording
+                    && gap.interruption.resumed_on.is_none()
+                    && gap.interruption.at_ms > 0
+            }),
+            advance_on_timeout: settings.advance_on_timeout,
+        };
+        let transition = machine.apply(event, &guards);
+        if matches!(
+            transition.map(|t| t.effect),
+            Some(Effect::StartSession | Effect::RecordContent)
+        ) {
+            *logic_state.recording_id.lock().unwrap() += 1;
         }
+        (machine.state(), transition)
     };
+    let Some(transition) = transition else {
+        log::warn!("Ignoring {:?} while {:?}", event, state);
+        return None;
+    };
+    log::info!(
+        "{:?} + {:?} -> {:?} ({:?})",
+        transition.from,
+        event,
+        transition.to,
+        transition.effect
+    );
 
-    let current = match current_status_str.as_str() {
-        "idle" => AppStatus::Idle,
-        "instruction" => AppStatus::Instruction,
-        "content" => AppStatus::Content,
-        "processing" => AppStatus::Processing,
-        _ => AppStatus::Idle,
+    let options = RecordingOptions {
+        device: None,
+        timeout: Duration::from_secs(settings.recording_timeout_minutes as u64 * 60),
+        timeout_warning: Duration::from_secs(settings.timeout_warning_seconds as u64),
+        processing: settings.audio_processing.clone(),
+        continue_on_default_device: settings.continue_on_default_device,
     };
 
-    let timeout_minutes = load_data(app)
-        .map(|data| data.settings.recording_timeout_minutes)
-        .unwrap_or(10);
+    // Starting or stopping a recording ends the pause
+    let was_paused = logic_state.is_paused();
+    let paused = match transition.effect {
+        Effect::None => was_paused,
+        Effect::PauseRecording => true,
+        _ => false,
+    };
+    *logic_state.paused.lock().unwrap() = paused;
 
-    let new_status = match current {
-        AppStatus::Idle => {
-            // Idle -> Instruction
-            play_sound("Ping");
+    let cue = transition_cue(event, transition.effect);
+    // A recording that cannot be saved or started is followed by RecordingFailed
+    let mut failure = None;
+    match transition.effect {
+        Effect::None => {}
+        Effect::PauseRecording | Effect::ResumeRecording => {
+            if paused != was_paused {
+                ctx.play_cue(cue);
+                recorder.set_paused(paused);
+            }
+        }
+        Effect::StartSession => {
+            // Every recording gets its own directory so it can wait in the queue
+            let session_id = uuid::Uuid::new_v4().to_string();
+            let dir = queue::session_dir(ctx, &session_id);
+            *logic_state.session_id.lock().unwrap() = Some(session_id);
+            logic_state.gaps.lock().unwrap().clear();
 
-            // Start Recording 1
-            let path = get_audio_path(app, "instruction.wav");
-            let _ = start_recording(&audio_state, None, path, timeout_minutes, app.clone());
+            failure = std::fs::create_dir_all(&dir)
+                .map_err(|e| {
+                    AppError::Storage(format!(
+                        "Failed to create session directory {:?}: {}",
+                        dir, e
+                    ))
+                })
+                .and_then(|_| {
+                    recorder.start(dir.join(queue::INSTRUCTION_FILENAME), options, handlers())
+                })
+                .err();
+            if failure.is_none() {
+                ctx.play_cue(cue);
+            }
+        }
+        Effect::RecordContent => {
+            ctx.play_cue(cue);
 
-            AppStatus::Instruction
+            // The guard ensures there is a session
+            let session_id = logic_state.session_id.lock().unwrap().clone();
+            let path = queue::session_dir(ctx, &session_id.unwrap_or_default())
+                .join(queue::CONTENT_FILENAME);
+            failure = recorder
+                .stop()
+                .and_then(|_| recorder.start(path, options, handlers()))
+                .err();
         }
-        AppStatus::Instruction => {
-            // Instruction -> Content
-            play_sound("Click");
+        Effect::QueueSession => {
+            // The session is processed in the background
+            ctx.play_cue(cue);
+            // Returns once the content is written, so the pipeline reads complete files
+            let stopped = recorder.stop();
 
-            // Stop Recording 1
-            stop_recording(&audio_state);
+            let gaps = std::mem::take(&mut *logic_state.gaps.lock().unwrap());
+            if let Some(session_id) = logic_state.session_id.lock().unwrap().take() {
+                let queued = stopped.and_then(|_| queue::enqueue(ctx, queue, &session_id, gaps));
+                if let Err(e) = queued {
+                    log::error!(
+                        "Failed to queue session {}: {} {:?}",
+                        session_id,
+                        e,
+                        e.details()
+                    );
+                    ctx.emit_event("recording-error", &e);
+                    queue::remove_session_dir(ctx, &session_id);
+                }
+            }
+        }
+        Effect::DiscardSession => {
+            ctx.play_cue(cue);
+            // The recordings are deleted, whether or not they were finalized
+            let _ = recorder.stop();
 
-            // Start Recording 2
-            let path = get_audio_path(app, "content.wav");
-            let _ = start_recording(&audio_state, None, path, timeout_minutes, app.clone());
+            logic_state.gaps.lock().unwrap().clear();
+            match logic_state.session_id.lock().unwrap().take() {
+                Some(session_id) => queue::remove_session_dir(ctx, &session_id),
+                None => log::error!("No recording session to continue"),
+            }
+        }
+    }
 
-            AppStatus::Content
+    let Some(e) = failure else {
+        if transition.from != transition.to || paused != was_paused {
+            publish_status(ctx, logic_state, queue);
         }
-        AppStatus::Content => {
-            // Content -> Processing
-            play_sound("Swoosh");
+        return Some(transition);
+    };
 
-            // Stop Recording 2
-            stop_recording(&audio_state);
+    // The recording never started or the previous one could not be saved, so
+    // the frontend only learns about the error and the state reverting to Idle
+    log::error!("Recording failed: {} {:?}", e, e.details());
+    ctx.emit_event("recording-error", &e);
+    dispatch(
+        ctx,
+        logic_state,
+        recorder,
+        queue,
+        handlers,
+        RecordingEvent::RecordingFailed,
+    );
+    Some(transition)
+}
 
-            // Trigger processing in background
-            use crate::pipeline::run_pipeline;
-            run_pipeline(app.clone());
+/// Sound of a transition. Timeouts and failures sound the same whatever happens
+/// to the session.
+fn transition_cue(event: RecordingEvent, effect: Effect) -> Cue {
+    match (event, effect) {
+        (RecordingEvent::Timeout, _) => Cue::Timeout,
+        (RecordingEvent::RecordingFailed, _) => Cue::Error,
+        (_, Effect::StartSession) => Cue::Start,
+        (_, Effect::QueueSession) => Cue::Processing,
+        _ => Cue::Advance,
+    }
+}
 
-            AppStatus::Processing
-        }
-        AppStatus::Processing => {
-            // Ignore triggers while processing? Or cancel?
-            // Let's ignore for now.
-            return;
-        }
+/// Payload of `recording-interrupted`
+#[derive(Serialize)]
+struct RecordingInterrupted<'a> {
+    session_id: Option<String>,
+    #[serde(flatten)]
+    gap: &'a RecordingGap,
+}
+
+/// Records a device failure in the session and tells the frontend. A recording
+/// that could not continue on another device ends with `RecordingFailed`; content
+/// recorded up to the failure is still processed.
+pub fn recording_interrupted<C: AppContext>(
+    ctx: &C,
+    logic_state: &LogicState,
+    recorder: &dyn Recorder,
+    queue: &SessionQueue,
+    handlers: &dyn Fn() -> RecordingHandlers,
+    interruption: Interruption,
+) {
+    let resumed = interruption.resumed_on.is_some();
+    let gap = RecordingGap {
+        recording: logic_state.status(),
+        interruption,
     };
+    log::warn!(
+        "Recording {:?} interrupted at {} ms: {} ({})",
+        gap.recording,
+        gap.interruption.at_ms,
+        gap.interruption.error,
+        gap.interruption
+            .resumed_on
+            .as_deref()
+            .unwrap_or("not resumed")
+    );
+    ctx.emit_event(
+        "recording-interrupted",
+        RecordingInterrupted {
+            session_id: logic_state.session_id.lock().unwrap().clone(),
+            gap: &gap,
+        },
+    );
+    logic_state.gaps.lock().unwrap().push(gap);
 
-    set_status(app, new_status);
+    if !resumed {
+        dispatch(
+            ctx,
+            logic_state,
+            recorder,
+            queue,
+            handlers,
+            RecordingEvent::RecordingFailed,
+        );
+    }
+}
+
+/// Times out the recording `recording_id`. A timeout that fires after its
+/// recording was stopped is ignored, so it cannot end the next recording.
+pub fn recording_timed_out<C: AppContext>(
+    ctx: &C,
+    logic_state: &LogicState,
+    recorder: &dyn Recorder,
+    queue: &SessionQueue,
+    handlers: &dyn Fn() -> RecordingHandlers,
+    recording_id: u64,
+) -> Option<Transition> {
+    dispatch_for(
+        ctx,
+        logic_state,
+        recorder,
+        queue,
+        handlers,
+        RecordingEvent::Timeout,
+        Some(recording_id),
+    )
+}
+
+/// Payload of `recording-timeout-warning`
+#[derive(Serialize)]
+struct TimeoutWarning {
+    session_id: Option<String>,
+    recording: AppStatus,
+    /// Seconds until the recording times out
+    seconds_left: u64,
+    /// The recording is kept when it times out
+    advance_on_timeout: bool,
+}
+
+/// Tells the frontend that the current recording times out in `remaining`.
+pub fn timeout_warning<C: AppContext>(ctx: &C, logic_state: &LogicState, remaining: Duration) {
+    let warning = TimeoutWarning {
+        session_id: logic_state.session_id.lock().unwrap().clone(),
+        recording: logic_state.status(),
+        seconds_left: remaining.as_secs_f64().round() as u64,
+        advance_on_timeout: load_data(ctx)
+            .map(|data| data.settings.advance_on_timeout)
+            .unwrap_or_default(),
+    };
+    log::info!(
+        "Recording {:?} times out in {} s",
+        warning.recording,
+        warning.seconds_left
+    );
+    ctx.emit_event("recording-timeout-warning", warning);
 }
 
-pub fn set_status(app: &AppHandle, new_status: AppStatus) {
-    let status_str = new_status.as_str();
+fn recording_handlers(app: &AppHandle) -> RecordingHandlers {
+    RecordingHandlers {
+        on_timeout: timeout_handler(app),
+        on_timeout_warning: timeout_warning_handler(app),
+        on_interrupted: interruption_handler(app),
+    }
+}
+
+/// Called by the recording thread when a recording exceeds the timeout.
+fn timeout_handler(app: &AppHandle) -> TimeoutHandler {
+    let app = app.clone();
+    // Handlers are created right after their recording got its id
+    let recording_id = app.state::<LogicState>().recording_id();
+    Box::new(move || {
+        let audio_state = app.state::<AudioState>();
+        let logic_state = app.state::<LogicState>();
+        let queue = app.state::<SessionQueue>();
+        let Some(transition) = recording_timed_out(
+            &app,
+            &logic_state,
+            &*audio_state,
+            &queue,
+            &|| recording_handlers(&app),
+            recording_id,
+        ) else {
+            return;
+        };
+        let message = match transition.effect {
+            Effect::RecordContent => {
+                "Instruction reached the maximum duration, now recording content"
+            }
+            Effect::QueueSession => "Content reached the maximum duration and is being processed",
+            _ => "Recording exceeded maximum duration",
+        };
+        let _ = app.emit("recording-timeout", message);
+    })
+}
+
+/// Called by the recording thread shortly before a recording times out.
+fn timeout_warning_handler(app: &AppHandle) -> TimeoutWarningHandler {
+    let app = app.clone();
+    Box::new(move |remaining| {
+        timeout_warning(&app, &app.state::<LogicState>(), remaining);
+    })
+}
+
+/// Called by the recording thread when the input device fails.
+fn interruption_handler(app: &AppHandle) -> InterruptionHandler {
+    let app = app.clone();
+    Box::new(move |interruption| {
+        let audio_state = app.state::<AudioState>();
+        let logic_state = app.state::<LogicState>();
+        let queue = app.state::<SessionQueue>();
+        recording_interrupted(
+            &app,
+            &logic_state,
+            &*audio_state,
+            &queue,
+            &|| recording_handlers(&app),
+            interruption,
+        );
+    })
+}
+
+/// Persists the recording state and notifies the frontend.
+fn publish_status<C: AppContext>(ctx: &C, logic_state: &LogicState, queue: &SessionQueue) {
     // Update status in JSON
-    if let Err(e) = store_set_status(app, status_str) {
+    if let Err(e) = store_set_status(ctx, logic_state.status()) {
         log::error!("Failed to save status to JSON: {}", e);
     }
     // Emit event to notify frontend
-    let _ = app.emit("status-changed", status_str);
+    ctx.emit_event("status-changed", snapshot(logic_state, queue));
+}
+
+pub fn status_snapshot(app: &AppHandle) -> StatusSnapshot {
+    snapshot(&app.state::<LogicState>(), &app.state::<SessionQueue>())
+}
+
+pub fn snapshot(logic_state: &LogicState, queue: &SessionQueue) -> StatusSnapshot {
+    StatusSnapshot {
+        recording: logic_state.status(),
+        paused: logic_state.is_paused(),
+        queued_jobs: queue.len(),
+        active_session: queue.active(),
+    }
+}
+
+pub fn state_machine_snapshot(app: &AppHandle) -> StateMachineSnapshot {
+    app.state::<LogicState>().machine.lock().unwrap().snapshot()
 }
 
-fn get_audio_path(app: &AppHandle, filename: &str) -> PathBuf {
-    app.path().app_data_dir().unwrap().join(filename)
+/// Notifies the frontend after the recording state or the queue changed.
+pub fn emit_status(app: &AppHandle) {
+    let _ = app.emit("status-changed", status_snapshot(app));
 }
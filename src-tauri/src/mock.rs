@@ -0,0 +1,287 @@
+//! In-memory stand-ins for the Tauri app, the microphone and Whisper, so the
+//! state machine, store and pipeline can be tested without a display, audio
+//! device or model.
+
+use crate::audio::{Recorder, RecordingHandlers, RecordingOptions, TimeoutHandler};
+use crate::context::AppContext;
+use crate::error::{AppError, AppResult};
+use crate::models::{Cue, Settings, Transcript, TranscriptSegment};
+use crate::pipeline::Transcriber;
+use crate::progress::ProgressCallback;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+/// Records emitted events and played cues and keeps the clipboard in memory.
+/// Clones share their state.
+#[derive(Clone)]
+pub struct MockContext {
+    inner: Arc<MockState>,
+}
+
+struct MockState {
+    data_dir: PathBuf,
+    events: Mutex<Vec<(String, Value)>>,
+    clipboard: Mutex<Option<String>>,
+    cues: Mutex<Vec<Cue>>,
+    transcriber: Mutex<Option<Arc<dyn Transcriber>>>,
+}
+
+impl MockContext {
+    pub fn new(data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner: Arc::new(MockState {
+                data_dir: data_dir.into(),
+                events: Mutex::new(Vec::new()),
+                clipboard: Mutex::new(None),
+                cues: Mutex::new(Vec::new()),
+                transcriber: Mutex::new(None),
+            }),
+        }
+    }
+
+    pub fn events(&self) -> Vec<(String, Value)> {
+        self.inner.events.lock().unwrap().clone()
+    }
+
+    /// Payloads of every emitted `event`, oldest first
+    pub fn payloads(&self, event: &str) -> Vec<Value> {
+        self.inner
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| name == event)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+
+    pub fn clipboard(&self) -> Option<String> {
+        self.inner.clipboard.lock().unwrap().clone()
+    }
+
+    pub fn cues(&self) -> Vec<Cue> {
+        self.inner.cues.lock().unwrap().clone()
+    }
+
+    /// Makes the pipeline use `transcriber` instead of loading Whisper.
+    pub fn set_transcriber(&self, transcriber: Arc<dyn Transcriber>) {
+        *self.inner.transcriber.lock().unwrap() = Some(transcriber);
+    }
+}
+
+impl AppContext for MockContext {
+    fn data_dir(&self) -> PathBuf {
+        self.inner.data_dir.clone()
+    }
+
+    fn emit_value(&self, event: &str, payload: Value) {
+        self.inner
+            .events
+            .lock()
+            .unwrap()
+            .push((event.to_string(), payload));
+    }
+
+    fn read_clipboard(&self) -> AppResult<String> {
+        self.inner
+            .clipboard
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| AppError::Clipboard("Clipboard is empty".to_string()))
+    }
+
+    fn write_clipboard(&self, text: &str) -> AppResult<()> {
+        *self.inner.clipboard.lock().unwrap() = Some(text.to_string());
+        Ok(())
+    }
+
+    fn play_cue(&self, cue: Cue) {
+        self.inner.cues.lock().unwrap().push(cue);
+    }
+
+    fn transcriber(&self) -> Option<Arc<dyn Transcriber>> {
+        self.inner.transcriber.lock().unwrap().clone()
+    }
+}
+
+/// Remembers which recordings were started instead of opening an input device.
+#[derive(Default)]
+pub struct MockRecorder {
+    started: Mutex<Vec<PathBuf>>,
+    stopped: Mutex<usize>,
+    options: Mutex<Vec<RecordingOptions>>,
+    on_timeout: Mutex<Option<TimeoutHandler>>,
+    fail_start: Mutex<Option<AppError>>,
+    fail_stop: Mutex<Option<AppError>>,
+    paused: Mutex<Vec<bool>>,
+    stop_gate: Mutex<Option<(Sender<()>, Receiver<()>)>>,
+}
+
+/// Holds a `MockRecorder::stop` until opened; see `MockRecorder::hold_next_stop`.
+pub struct StopGate {
+    reached: Receiver<()>,
+    open: Sender<()>,
+}
+
+impl StopGate {
+    /// Waits until the held `stop` was called.
+    pub fn wait_until_reached(&self) {
+        self.reached.recv().unwrap();
+    }
+
+    /// Lets the held `stop` return.
+    pub fn open(self) {
+        self.open.send(()).unwrap();
+    }
+}
+
+impl MockRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Output paths of all recordings started so far
+    pub fn started(&self) -> Vec<PathBuf> {
+        self.started.lock().unwrap().clone()
+    }
+
+    /// Options of all recordings started so far
+    pub fn options(&self) -> Vec<RecordingOptions> {
+        self.options.lock().unwrap().clone()
+    }
+
+    /// Makes the next `start` fail with `error`, as if the device could not be opened.
+    pub fn fail_next_start(&self, error: AppError) {
+        *self.fail_start.lock().unwrap() = Some(error);
+    }
+
+    /// Makes the next `stop` fail with `error`, as if the file could not be finalized.
+    pub fn fail_next_stop(&self, error: AppError) {
+        *self.fail_stop.lock().unwrap() = Some(error);
+    }
+
+    /// Arguments of all `set_paused` calls so far
+    pub fn pause_calls(&self) -> Vec<bool> {
+        self.paused.lock().unwrap().clone()
+    }
+
+    /// Makes the next `stop` wait until the returned gate is opened, so a test can
+    /// act while a transition is between stopping and starting recordings.
+    pub fn hold_next_stop(&self) -> StopGate {
+        let (reached_tx, reached) = mpsc::channel();
+        let (open, open_rx) = mpsc::channel();
+        *self.stop_gate.lock().unwrap() = Some((reached_tx, open_rx));
+        StopGate { reached, open }
+    }
+
+    pub fn stop_count(&self) -> usize {
+        *self.stopped.lock().unwrap()
+    }
+
+    /// Runs the timeout handler of the current recording, as if it ran too long.
+    pub fn fire_timeout(&self) -> bool {
+        let handler = self.on_timeout.lock().unwrap().take();
+        match handler {
+            Some(handler) => {
+                handler();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Recorder for MockRecorder {
+    fn start(
+        &self,
+        path: PathBuf,
+        options: RecordingOptions,
+        handlers: RecordingHandlers,
+    ) -> AppResult<()> {
+        self.started.lock().unwrap().push(path);
+        self.options.lock().unwrap().push(options);
+        if let Some(error) = self.fail_start.lock().unwrap().take() {
+            return Err(error);
+        }
+        *self.on_timeout.lock().unwrap() = Some(handlers.on_timeout);
+        Ok(())
+    }
+
+    fn stop(&self) -> AppResult<()> {
+        let gate = self.stop_gate.lock().unwrap().take();
+        if let Some((reached, open)) = gate {
+            let _ = reached.send(());
+            let _ = open.recv();
+        }
+        *self.stopped.lock().unwrap() += 1;
+        *self.on_timeout.lock().unwrap() = None;
+        match self.fail_stop.lock().unwrap().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.paused.lock().unwrap().push(paused);
+    }
+}
+
+/// Answers with scripted texts in order, one per transcription.
+pub struct MockTranscriber {
+    texts: Mutex<VecDeque<String>>,
+    inputs: Mutex<Vec<usize>>,
+}
+
+impl MockTranscriber {
+    pub fn new<S: Into<String>>(texts: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            texts: Mutex::new(texts.into_iter().map(Into::into).collect()),
+            inputs: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Number of samples passed to each transcription so far
+    pub fn inputs(&self) -> Vec<usize> {
+        self.inputs.lock().unwrap().clone()
+    }
+}
+
+impl Transcriber for MockTranscriber {
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        _settings: &Settings,
+        on_progress: Option<ProgressCallback>,
+        _cancel: Option<CancellationToken>,
+    ) -> AppResult<Transcript> {
+        self.inputs.lock().unwrap().push(samples.len());
+        let text =
+            self.texts.lock().unwrap().pop_front().ok_or_else(|| {
+                AppError::Transcription("No scripted transcript left".to_string())
+            })?;
+        if let Some(mut on_progress) = on_progress {
+            on_progress(100);
+        }
+
+        Ok(Transcript {
+            text: text.clone(),
+            language: Some("en".to_string()),
+            language_probability: Some(1.0),
+            segments: vec![TranscriptSegment {
+                start_ms: 0,
+                end_ms: samples.len() as u64 / 16,
+                text,
+                tokens: Vec::new(),
+                confidence: 1.0,
+                no_speech_probability: 0.0,
+            }],
+            confidence: None,
+            duration_ms: Some(samples.len() as u64 / 16),
+        })
+    }
+}
@@ -1,11 +1,357 @@
-use serde::{Deserialize, Serialize};
+use crate::sounds;
+use crate::state_machine::AppStatus;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Settings {
     pub model: String,
     pub microphone: String,
     pub hotkey: String,
+    /// Hotkey that pauses and resumes the current recording
+    #[serde(default)]
+    pub pause_hotkey: Option<String>,
     pub recording_timeout_minutes: u32,
+    /// Seconds before the timeout at which `recording-timeout-warning` is emitted;
+    /// 0 disables the warning
+    #[serde(default = "default_timeout_warning_seconds")]
+    pub timeout_warning_seconds: u32,
+    /// A recording reaching the timeout is kept and the session moves on, as if
+    /// triggered, instead of being discarded
+    #[serde(default)]
+    pub advance_on_timeout: bool,
+    #[serde(default)]
+    pub audio_processing: AudioProcessingSettings,
+    /// Keep recording on the default input device when the device in use fails
+    #[serde(default)]
+    pub continue_on_default_device: bool,
+    /// Whisper language code (e.g. "en", "de") or "auto" for detection
+    #[serde(
+        default = "default_transcription_language",
+        deserialize_with = "deserialize_transcription_language"
+    )]
+    pub transcription_language: String,
+    #[serde(default)]
+    pub whisper_task: WhisperTask,
+    #[serde(default)]
+    pub decoding: DecodingSettings,
+    /// Terms passed to Whisper as the initial prompt to bias recognition
+    #[serde(default)]
+    pub vocabulary: Vec<String>,
+    /// Corrections applied to every transcript
+    #[serde(default)]
+    pub replacements: Vec<Replacement>,
+    #[serde(default)]
+    pub templates: Vec<PromptTemplate>,
+    /// Id of the template used by the pipeline, if any
+    #[serde(default)]
+    pub active_template: Option<String>,
+    /// Words with a token probability below this are flagged as uncertain
+    #[serde(default = "default_low_confidence_threshold")]
+    pub low_confidence_threshold: f32,
+    /// Skip enrichment and ask the user to repeat the instruction when its
+    /// confidence is below this value; None disables the check
+    #[serde(default)]
+    pub min_instruction_confidence: Option<f32>,
+    /// Recordings shorter than this are rejected before transcription
+    #[serde(default = "default_min_recording_ms")]
+    pub min_recording_ms: u32,
+    /// Frames quieter than this (RMS, dBFS) count as silence
+    #[serde(default = "default_silence_threshold_dbfs")]
+    pub silence_threshold_dbfs: f32,
+    /// Paste or type the result into the focused application
+    #[serde(default)]
+    pub auto_paste: AutoPasteSettings,
+    /// Audio cues for state changes and results
+    #[serde(default)]
+    pub sounds: SoundSettings,
+    /// Where results are delivered when the active template sets no outputs
+    #[serde(default = "default_outputs")]
+    pub outputs: Vec<OutputSinkConfig>,
+    /// Base URL of the Ollama server used for enrichment
+    #[serde(default = "default_ollama_url")]
+    pub ollama_url: String,
+}
+
+fn default_ollama_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
+fn default_outputs() -> Vec<OutputSinkConfig> {
+    vec![OutputSinkConfig::Clipboard]
+}
+
+fn default_timeout_warning_seconds() -> u32 {
+    30
+}
+
+fn default_min_recording_ms() -> u32 {
+    500
+}
+
+fn default_silence_threshold_dbfs() -> f32 {
+    -50.0
+}
+
+fn default_low_confidence_threshold() -> f32 {
+    0.5
+}
+
+impl Settings {
+    /// Checks values that would otherwise only fail once the pipeline runs.
+    pub fn validate(&self) -> Result<(), String> {
+        let language = self.transcription_language.trim();
+        if language != "auto" && whisper_rs::get_lang_id(language).is_none() {
+            return Err(format!("Unsupported transcription language '{}'", language));
+        }
+        self.decoding.validate()?;
+        self.sounds.validate()?;
+
+        if !(0.0..=1.0).contains(&self.low_confidence_threshold) {
+            return Err("low_confidence_threshold must be between 0.0 and 1.0".to_string());
+        }
+        if !(-90.0..=0.0).contains(&self.silence_threshold_dbfs) {
+            return Err("silence_threshold_dbfs must be between -90 and 0".to_string());
+        }
+        if let Some(pause_hotkey) = &self.pause_hotkey {
+            if pause_hotkey.trim().is_empty() || pause_hotkey.eq_ignore_ascii_case(&self.hotkey) {
+                return Err("pause_hotkey must be a different hotkey than hotkey".to_string());
+            }
+        }
+        if let Some(min) = self.min_instruction_confidence {
+            if !(0.0..=1.0).contains(&min) {
+                return Err("min_instruction_confidence must be between 0.0 and 1.0".to_string());
+            }
+        }
+
+        for replacement in self.replacements.iter().chain(
+            self.templates
+                .iter()
+                .filter_map(|t| t.replacements.as_ref())
+                .flatten(),
+        ) {
+            replacement.validate()?;
+        }
+        if let Some(id) = &self.active_template {
+            if !self.templates.iter().any(|t| &t.id == id) {
+                return Err(format!("Active template '{}' does not exist", id));
+            }
+        }
+
+        let ollama_url = reqwest::Url::parse(&self.ollama_url)
+            .map_err(|e| format!("Invalid Ollama URL '{}': {}", self.ollama_url, e))?;
+        if !matches!(ollama_url.scheme(), "http" | "https") {
+            return Err(format!(
+                "Ollama URL '{}' must use http or https",
+                self.ollama_url
+            ));
+        }
+
+        for output in self.outputs.iter().chain(
+            self.templates
+                .iter()
+                .filter_map(|t| t.outputs.as_ref())
+                .flatten(),
+        ) {
+            output.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn active_template(&self) -> Option<&PromptTemplate> {
+        let id = self.active_template.as_ref()?;
+        self.templates.iter().find(|t| &t.id == id)
+    }
+
+    /// Vocabulary of the active template, falling back to the global list.
+    pub fn effective_vocabulary(&self) -> &[String] {
+        self.active_template()
+            .and_then(|t| t.vocabulary.as_deref())
+            .unwrap_or(&self.vocabulary)
+    }
+
+    /// Replacements of the active template, falling back to the global list.
+    pub fn effective_replacements(&self) -> &[Replacement] {
+        self.active_template()
+            .and_then(|t| t.replacements.as_deref())
+            .unwrap_or(&self.replacements)
+    }
+
+    /// Outputs of the active template, falling back to the global list.
+    pub fn effective_outputs(&self) -> &[OutputSinkConfig] {
+        self.active_template()
+            .and_then(|t| t.outputs.as_deref())
+            .unwrap_or(&self.outputs)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Replacement {
+    pub pattern: String,
+    pub replacement: String,
+    /// Treat `pattern` as a regular expression; otherwise it is matched
+    /// literally and case-insensitively
+    #[serde(default)]
+    pub regex: bool,
+}
+
+impl Replacement {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pattern.is_empty() {
+            return Err("Replacement pattern must not be empty".to_string());
+        }
+        self.compile().map(|_| ())
+    }
+
+    pub fn compile(&self) -> Result<regex::Regex, String> {
+        let pattern = if self.regex {
+            self.pattern.clone()
+        } else {
+            format!("(?i){}", regex::escape(&self.pattern))
+        };
+        regex::Regex::new(&pattern)
+            .map_err(|e| format!("Invalid replacement pattern '{}': {}", self.pattern, e))
+    }
+}
+
+/// Named enrichment setup. Fields left as `None` fall back to the global settings.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub name: String,
+    /// Enrichment prompt; `{instruction}` and `{content}` are substituted
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub vocabulary: Option<Vec<String>>,
+    #[serde(default)]
+    pub replacements: Option<Vec<Replacement>>,
+    #[serde(default)]
+    pub outputs: Option<Vec<OutputSinkConfig>>,
+}
+
+/// File format used to share vocabulary and replacements
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct VocabularyFile {
+    #[serde(default)]
+    pub vocabulary: Vec<String>,
+    #[serde(default)]
+    pub replacements: Vec<Replacement>,
+}
+
+fn default_transcription_language() -> String {
+    "auto".to_string()
+}
+
+/// Reads an empty language, which older versions saved for detection, as "auto"
+fn deserialize_transcription_language<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    let language = String::deserialize(deserializer)?;
+    if language.trim().is_empty() {
+        Ok(default_transcription_language())
+    } else {
+        Ok(language)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DecodingStrategy {
+    #[default]
+    Greedy,
+    BeamSearch,
+}
+
+/// Advanced Whisper decoding parameters
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DecodingSettings {
+    pub strategy: DecodingStrategy,
+    /// Candidates sampled per step with the greedy strategy
+    pub best_of: u32,
+    /// Beam width with the beam search strategy
+    pub beam_size: u32,
+    /// Initial sampling temperature (0 = deterministic)
+    pub temperature: f32,
+    /// Temperature increase for each fallback when decoding fails; 0 disables fallback
+    pub temperature_increment: f32,
+    /// Inference threads; None uses all available cores
+    pub threads: Option<u32>,
+    /// Segments with a no-speech probability above this are treated as silence
+    pub no_speech_threshold: f32,
+    pub suppress_blank: bool,
+    /// Maximum segment length in characters; 0 means no limit
+    pub max_segment_length: u32,
+}
+
+impl Default for DecodingSettings {
+    fn default() -> Self {
+        Self {
+            strategy: DecodingStrategy::Greedy,
+            best_of: 1,
+            beam_size: 5,
+            temperature: 0.0,
+            temperature_increment: 0.2,
+            threads: None,
+            no_speech_threshold: 0.6,
+            suppress_blank: true,
+            max_segment_length: 0,
+        }
+    }
+}
+
+impl DecodingSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=10).contains(&self.best_of) {
+            return Err("best_of must be between 1 and 10".to_string());
+        }
+        if !(1..=16).contains(&self.beam_size) {
+            return Err("beam_size must be between 1 and 16".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.temperature) {
+            return Err("temperature must be between 0.0 and 1.0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.temperature_increment) {
+            return Err("temperature_increment must be between 0.0 and 1.0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.no_speech_threshold) {
+            return Err("no_speech_threshold must be between 0.0 and 1.0".to_string());
+        }
+        if let Some(threads) = self.threads {
+            let available = available_threads();
+            if threads == 0 || threads as usize > available {
+                return Err(format!(
+                    "threads must be between 1 and {} (available cores)",
+                    available
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn thread_count(&self) -> usize {
+        self.threads
+            .map(|t| t as usize)
+            .unwrap_or_else(available_threads)
+    }
+}
+
+fn available_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WhisperTask {
+    /// Transcribe in the spoken language
+    #[default]
+    Transcribe,
+    /// Translate the speech to English
+    Translate,
 }
 
 impl Default for Settings {
@@ -14,7 +360,306 @@ impl Default for Settings {
             model: "gemma3:4b".to_string(), // Default as per requirements
             microphone: "default".to_string(),
             hotkey: "Ctrl+I".to_string(),
+            pause_hotkey: None,
             recording_timeout_minutes: 10,
+            timeout_warning_seconds: default_timeout_warning_seconds(),
+            advance_on_timeout: false,
+            audio_processing: AudioProcessingSettings::default(),
+            continue_on_default_device: false,
+            transcription_language: default_transcription_language(),
+            whisper_task: WhisperTask::default(),
+            decoding: DecodingSettings::default(),
+            vocabulary: Vec::new(),
+            replacements: Vec::new(),
+            templates: Vec::new(),
+            active_template: None,
+            low_confidence_threshold: default_low_confidence_threshold(),
+            min_instruction_confidence: None,
+            min_recording_ms: default_min_recording_ms(),
+            silence_threshold_dbfs: default_silence_threshold_dbfs(),
+            auto_paste: AutoPasteSettings::default(),
+            sounds: SoundSettings::default(),
+            outputs: default_outputs(),
+            ollama_url: default_ollama_url(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AutoPasteSettings {
+    pub enabled: bool,
+    pub method: PasteMethod,
+    /// Input injection tool used on Linux
+    pub linux_backend: LinuxInputBackend,
+    /// Put the previous clipboard contents back after pasting
+    pub restore_clipboard: bool,
+    /// Wait before sending keystrokes, so the hotkey's modifiers are released
+    pub delay_ms: u32,
+}
+
+impl Default for AutoPasteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            method: PasteMethod::Paste,
+            linux_backend: LinuxInputBackend::Auto,
+            restore_clipboard: true,
+            delay_ms: 150,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SoundSettings {
+    pub theme: SoundTheme,
+    /// Sounds that replace the theme's for single cues
+    pub cues: BTreeMap<Cue, CueSound>,
+    /// 0.0 to 1.0
+    pub volume: f32,
+    pub muted: bool,
+    /// Output device; None plays on the default device
+    pub output_device: Option<String>,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            theme: SoundTheme::default(),
+            cues: BTreeMap::new(),
+            volume: 1.0,
+            muted: false,
+            output_device: None,
+        }
+    }
+}
+
+impl SoundSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.volume) {
+            return Err("Sound volume must be between 0.0 and 1.0".to_string());
+        }
+        for (cue, sound) in &self.cues {
+            match sound {
+                CueSound::Tones { tones } => {
+                    let valid = !tones.is_empty()
+                        && tones.iter().all(|tone| {
+                            (20.0..=20_000.0).contains(&tone.frequency_hz)
+                                && (1..=5000).contains(&tone.duration_ms)
+                        });
+                    if !valid {
+                        return Err(format!(
+                            "The {} cue needs tones between 20 and 20000 Hz lasting 1 to 5000 ms",
+                            cue.as_str()
+                        ));
+                    }
+                }
+                CueSound::Bundled { name } => {
+                    if !sounds::is_bundled(name) {
+                        return Err(format!(
+                            "Unknown bundled sound '{}' for the {} cue",
+                            name,
+                            cue.as_str()
+                        ));
+                    }
+                }
+                CueSound::File { path } => {
+                    if !path.is_file() {
+                        return Err(format!(
+                            "Sound file {} for the {} cue does not exist",
+                            path.display(),
+                            cue.as_str()
+                        ));
+                    }
+                }
+                CueSound::Silent => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Moment the app signals with a sound
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Cue {
+    /// A session started recording the instruction
+    Start,
+    /// The recording moved on to the content, was paused, resumed or cancelled
+    Advance,
+    /// A session was queued for processing
+    Processing,
+    /// The result was delivered
+    Success,
+    /// A recording or a session failed
+    Error,
+    /// A recording reached the timeout
+    Timeout,
+}
+
+impl Cue {
+    pub const ALL: [Cue; 6] = [
+        Cue::Start,
+        Cue::Advance,
+        Cue::Processing,
+        Cue::Success,
+        Cue::Error,
+        Cue::Timeout,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Cue::Start => "start",
+            Cue::Advance => "advance",
+            Cue::Processing => "processing",
+            Cue::Success => "success",
+            Cue::Error => "error",
+            Cue::Timeout => "timeout",
+        }
+    }
+}
+
+/// Built-in set of sounds for all cues
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SoundTheme {
+    /// Synthesized beeps
+    #[default]
+    Tones,
+    /// Bell-like sounds bundled with the app
+    Chimes,
+}
+
+/// What a cue plays
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum CueSound {
+    /// Sine tones played one after another
+    Tones {
+        tones: Vec<Tone>,
+    },
+    /// Sound bundled with the app, by name (e.g. "chime-start")
+    Bundled {
+        name: String,
+    },
+    /// Audio file chosen by the user (WAV, FLAC, MP3 or Ogg Vorbis)
+    File {
+        path: PathBuf,
+    },
+    Silent,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Tone {
+    pub frequency_hz: f32,
+    pub duration_ms: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PasteMethod {
+    /// Simulate Ctrl+V (Cmd+V on macOS) with the result on the clipboard
+    #[default]
+    Paste,
+    /// Type the text keystroke by keystroke
+    Type,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LinuxInputBackend {
+    /// wtype or ydotool on Wayland, xdotool on X11
+    #[default]
+    Auto,
+    /// X11 (XTest)
+    Xdotool,
+    /// Wayland compositors with virtual-keyboard support
+    Wtype,
+    /// uinput, works on X11 and Wayland but needs the ydotoold daemon
+    Ydotool,
+}
+
+/// Destination a pipeline result is delivered to
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputSinkConfig {
+    /// Copy to the clipboard, and paste it if auto-paste is enabled
+    Clipboard,
+    /// Append to a Markdown file, or to one file per day in a notes folder
+    File {
+        path: PathBuf,
+        #[serde(default)]
+        format: FileOutputFormat,
+    },
+    /// POST the result as JSON
+    Webhook { url: String },
+    /// Run a shell command with the text on stdin
+    Command { command: String },
+}
+
+impl OutputSinkConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            OutputSinkConfig::Clipboard => {}
+            OutputSinkConfig::File { path, .. } => {
+                if !path.is_absolute() {
+                    return Err(format!("Output path '{}' must be absolute", path.display()));
+                }
+            }
+            OutputSinkConfig::Webhook { url } => {
+                let parsed = reqwest::Url::parse(url)
+                    .map_err(|e| format!("Invalid webhook URL '{}': {}", url, e))?;
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    return Err(format!("Webhook URL '{}' must use http or https", url));
+                }
+            }
+            OutputSinkConfig::Command { command } => {
+                if command.trim().is_empty() {
+                    return Err("Output command must not be empty".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FileOutputFormat {
+    /// `path` is a Markdown file; each result is appended as a dated section
+    #[default]
+    Markdown,
+    /// `path` is a folder with one `YYYY-MM-DD.md` note per day
+    DailyNotes,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AudioProcessingSettings {
+    pub enabled: bool,
+    pub dc_removal: bool,
+    pub high_pass_enabled: bool,
+    pub high_pass_cutoff_hz: f32,
+    pub normalization_enabled: bool,
+    pub normalization_target_dbfs: f32,
+    pub normalization_max_gain_db: f32,
+    pub noise_gate_enabled: bool,
+    pub noise_gate_threshold_dbfs: f32,
+}
+
+impl Default for AudioProcessingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dc_removal: true,
+            high_pass_enabled: true,
+            high_pass_cutoff_hz: 80.0,
+            normalization_enabled: true,
+            normalization_target_dbfs: -20.0,
+            normalization_max_gain_db: 24.0,
+            noise_gate_enabled: true,
+            noise_gate_threshold_dbfs: -50.0,
         }
     }
 }
@@ -26,16 +671,150 @@ pub struct HistoryItem {
     pub instruction: String,
     pub original_content: String,
     pub enriched_content: String,
+    /// Name of the imported audio file, if the item did not come from a recording
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_file: Option<String>,
+    /// Language of the content transcript, as detected or configured
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Probability of the detected language (None if it was configured)
+    #[serde(default)]
+    pub language_probability: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instruction_transcript: Option<Transcript>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_transcript: Option<Transcript>,
+    /// How long each pipeline stage took for this item
+    #[serde(default)]
+    pub stage_timings: Vec<StageTiming>,
+}
+
+/// Steps of the processing pipeline, in the order they run
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStage {
+    ModelCheck,
+    ModelLoad,
+    TranscribeInstruction,
+    TranscribeContent,
+    Enrich,
+    /// Delivery to the configured outputs; stored as "clipboard" by older versions
+    #[serde(alias = "clipboard")]
+    Output,
+    Save,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct StageTiming {
+    pub stage: PipelineStage,
+    pub duration_ms: u64,
+}
+
+/// Result of transcribing a single recording
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Transcript {
+    pub text: String,
+    pub language: Option<String>,
+    pub language_probability: Option<f32>,
+    #[serde(default)]
+    pub segments: Vec<TranscriptSegment>,
+    /// Mean token probability over the whole transcript
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// Length of the transcribed audio; not stored by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    #[serde(default)]
+    pub tokens: Vec<TranscriptToken>,
+    /// Mean token probability of this segment
+    #[serde(default)]
+    pub confidence: f32,
+    /// Whisper's probability that the segment holds no speech
+    #[serde(default)]
+    pub no_speech_probability: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TranscriptToken {
+    pub text: String,
+    pub probability: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+    Json,
+}
+
+impl SubtitleFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Vtt => "vtt",
+            SubtitleFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Only transcribe the file
+    Transcribe,
+    /// Transcribe and enrich the transcript with the given instruction
+    Enrich,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct AppStateData {
     pub settings: Settings,
     pub history: Vec<HistoryItem>,
-    #[serde(default = "default_status")]
-    pub status: String,
+    #[serde(default)]
+    pub status: AppStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchJobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
 }
 
-fn default_status() -> String {
-    "idle".to_string()
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchJob {
+    pub id: String,
+    pub path: String,
+    pub file_name: String,
+    pub mode: ImportMode,
+    #[serde(default)]
+    pub instruction: Option<String>,
+    pub status: BatchJobStatus,
+    /// Progress of the current job in percent (0-100)
+    #[serde(default)]
+    pub progress: u8,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Id of the history item created for this job
+    #[serde(default)]
+    pub history_id: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BatchQueueData {
+    pub jobs: Vec<BatchJob>,
+    #[serde(default)]
+    pub paused: bool,
 }
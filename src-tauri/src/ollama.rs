@@ -1,7 +1,14 @@
-use dirs;
+use crate::error::{AppError, AppResult};
+use serde::Deserialize;
 use std::path::PathBuf;
+use std::time::Duration;
 use walkdir::WalkDir;
 
+/// Joins the configured server URL and an API path like "/api/chat".
+pub fn endpoint(base_url: &str, path: &str) -> String {
+    format!("{}{}", base_url.trim_end_matches('/'), path)
+}
+
 #[cfg(target_os = "windows")]
 fn get_ollama_models_dir() -> Option<PathBuf> {
     dirs::home_dir().map(|home| home.join(".ollama").join("models"))
@@ -78,3 +85,62 @@ pub fn scan_models() -> Vec<String> {
 
     models
 }
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    models: Vec<TagsModel>,
+}
+
+#[derive(Deserialize)]
+struct TagsModel {
+    name: String,
+}
+
+/// Models installed on the Ollama server at `base_url`, as reported by `/api/tags`.
+pub fn installed_models(base_url: &str) -> AppResult<Vec<String>> {
+    let url = endpoint(base_url, "/api/tags");
+    let response = reqwest::blocking::Client::new()
+        .get(&url)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .map_err(|e| AppError::OllamaUnreachable(format!("{}: {}", url, e)))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::Ollama(format!("status {}", response.status())));
+    }
+    let tags: TagsResponse = response
+        .json()
+        .map_err(|e| AppError::Ollama(format!("Failed to parse Ollama response: {}", e)))?;
+    Ok(tags.models.into_iter().map(|m| m.name).collect())
+}
+
+/// Downloads `name` unless the Ollama server already has it. Returns whether it
+/// was downloaded.
+pub fn pull_missing_model(base_url: &str, name: &str) -> AppResult<bool> {
+    if installed_models(base_url)?.iter().any(|m| m == name) {
+        return Ok(false);
+    }
+    pull_model(base_url, name)?;
+    Ok(true)
+}
+
+/// Asks the Ollama server to download a model. Blocks until it is installed.
+pub fn pull_model(base_url: &str, name: &str) -> AppResult<()> {
+    let response = reqwest::blocking::Client::new()
+        .post(endpoint(base_url, "/api/pull"))
+        .json(&serde_json::json!({ "model": name, "stream": false }))
+        // Large models take a while even on fast connections
+        .timeout(Duration::from_secs(3600))
+        .send()
+        .map_err(|e| AppError::OllamaUnreachable(e.to_string()))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().unwrap_or_default();
+        return Err(AppError::Ollama(format!(
+            "Pulling '{}' failed with status {}: {}",
+            name, status, body
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,249 @@
+use crate::context::AppContext;
+use crate::error::{AppError, AppResult};
+use crate::models::{AutoPasteSettings, FileOutputFormat, OutputSinkConfig, Settings};
+use crate::paste::paste_into_active_window;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of a pipeline run, as handed to the output sinks and posted to webhooks
+#[derive(Debug, Clone, Serialize)]
+pub struct Output {
+    pub session_id: String,
+    pub timestamp: DateTime<Local>,
+    /// Empty for imports that were not enriched
+    pub instruction: String,
+    pub original_content: String,
+    pub text: String,
+}
+
+/// Destination for a pipeline result. Sinks run on the blocking thread pool.
+pub trait OutputSink: Send {
+    /// Short name used in logs and `output-failed` events
+    fn name(&self) -> &'static str;
+    fn deliver(&self, output: &Output) -> AppResult<()>;
+}
+
+/// Creates the sinks configured for the active template.
+pub fn build_sinks<C: AppContext + Clone + 'static>(
+    ctx: &C,
+    settings: &Settings,
+) -> Vec<Box<dyn OutputSink>> {
+    settings
+        .effective_outputs()
+        .iter()
+        .map(|config| -> Box<dyn OutputSink> {
+            match config {
+                OutputSinkConfig::Clipboard => Box::new(ClipboardSink {
+                    ctx: ctx.clone(),
+                    auto_paste: settings.auto_paste.clone(),
+                }),
+                OutputSinkConfig::File { path, format } => Box::new(FileSink {
+                    path: path.clone(),
+                    format: *format,
+                }),
+                OutputSinkConfig::Webhook { url } => Box::new(WebhookSink { url: url.clone() }),
+                OutputSinkConfig::Command { command } => Box::new(CommandSink {
+                    command: command.clone(),
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Delivers `output` to every sink, even if earlier ones fail.
+/// Returns the sinks that failed.
+pub fn deliver_all(
+    sinks: &[Box<dyn OutputSink>],
+    output: &Output,
+) -> Vec<(&'static str, AppError)> {
+    let mut failures = Vec::new();
+    for sink in sinks {
+        match sink.deliver(output) {
+            Ok(()) => log::info!(
+                "Result of session {} sent to {}",
+                output.session_id,
+                sink.name()
+            ),
+            Err(e) => failures.push((sink.name(), e)),
+        }
+    }
+    failures
+}
+
+/// Copies the result and pastes it into the focused application if auto-paste is on.
+pub struct ClipboardSink<C> {
+    ctx: C,
+    auto_paste: AutoPasteSettings,
+}
+
+impl<C: AppContext> OutputSink for ClipboardSink<C> {
+    fn name(&self) -> &'static str {
+        "clipboard"
+    }
+
+    fn deliver(&self, output: &Output) -> AppResult<()> {
+        let previous = if self.auto_paste.enabled && self.auto_paste.restore_clipboard {
+            self.ctx.read_clipboard().ok()
+        } else {
+            None
+        };
+        self.ctx.write_clipboard(&output.text)?;
+
+        if self.auto_paste.enabled {
+            paste_into_active_window(&self.ctx, &output.text, &self.auto_paste, previous)?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends the result to a Markdown file or to today's daily note.
+pub struct FileSink {
+    path: PathBuf,
+    format: FileOutputFormat,
+}
+
+impl FileSink {
+    fn target(&self, output: &Output) -> PathBuf {
+        match self.format {
+            FileOutputFormat::Markdown => self.path.clone(),
+            FileOutputFormat::DailyNotes => self
+                .path
+                .join(format!("{}.md", output.timestamp.format("%Y-%m-%d"))),
+        }
+    }
+
+    fn entry(&self, output: &Output) -> String {
+        // Daily notes already carry the date in their file name
+        let heading = match self.format {
+            FileOutputFormat::Markdown => output.timestamp.format("%Y-%m-%d %H:%M"),
+            FileOutputFormat::DailyNotes => output.timestamp.format("%H:%M"),
+        };
+        let mut entry = format!("## {}\n\n", heading);
+        if !output.instruction.trim().is_empty() {
+            for line in output.instruction.trim().lines() {
+                entry.push_str(&format!("> {}\n", line));
+            }
+            entry.push('\n');
+        }
+        entry.push_str(output.text.trim());
+        entry.push_str("\n\n");
+        entry
+    }
+}
+
+impl OutputSink for FileSink {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn deliver(&self, output: &Output) -> AppResult<()> {
+        let path = self.target(output);
+        let io_error = |e: std::io::Error| AppError::Output(format!("{}: {}", path.display(), e));
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(io_error)?;
+        // Keep a blank line between the existing text and the new section
+        let separator = match fs::metadata(&path) {
+            Ok(meta) if meta.len() > 0 => "\n",
+            _ => "",
+        };
+        write!(file, "{}{}", separator, self.entry(output)).map_err(io_error)
+    }
+}
+
+/// Posts the result as JSON to a URL, e.g. a local automation server.
+pub struct WebhookSink {
+    url: String,
+}
+
+impl OutputSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn deliver(&self, output: &Output) -> AppResult<()> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let response = client
+            .post(&self.url)
+            .json(output)
+            .send()
+            .map_err(|e| AppError::Output(format!("{}: {}", self.url, e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Output(format!(
+                "{} returned {}",
+                self.url,
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Runs a shell command and writes the result to its stdin.
+pub struct CommandSink {
+    command: String,
+}
+
+impl OutputSink for CommandSink {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn deliver(&self, output: &Output) -> AppResult<()> {
+        let mut process = if cfg!(target_os = "windows") {
+            let mut process = Command::new("cmd");
+            process.arg("/C").arg(&self.command);
+            process
+        } else {
+            let mut process = Command::new("sh");
+            process.arg("-c").arg(&self.command);
+            process
+        };
+        let command_error =
+            |e: std::io::Error| AppError::Output(format!("{}: {}", self.command, e));
+
+        let mut child = process
+            .env("VOICE_INTEL_SESSION_ID", &output.session_id)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(command_error)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // Commands that do not read their input close the pipe early
+            match stdin.write_all(output.text.as_bytes()) {
+                Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+                    return Err(command_error(e))
+                }
+                _ => {}
+            }
+        }
+
+        let result = child.wait_with_output().map_err(command_error)?;
+        if !result.status.success() {
+            return Err(AppError::Output(format!(
+                "'{}' exited with {}: {}",
+                self.command,
+                result.status,
+                String::from_utf8_lossy(&result.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
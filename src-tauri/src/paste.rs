@@ -0,0 +1,462 @@
+use crate::context::AppContext;
+use crate::error::{AppError, AppResult};
+use crate::models::{AutoPasteSettings, PasteMethod};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+use crate::models::LinuxInputBackend;
+
+// Time the target application gets to read the clipboard before it is restored
+const RESTORE_DELAY: Duration = Duration::from_millis(300);
+
+/// External program that injects input; `stdin` is piped to it if set.
+struct InputCommand {
+    program: &'static str,
+    args: Vec<String>,
+    stdin: Option<String>,
+}
+
+impl InputCommand {
+    fn new(program: &'static str, args: &[&str]) -> Self {
+        Self {
+            program,
+            args: args.iter().map(|a| a.to_string()).collect(),
+            stdin: None,
+        }
+    }
+
+    fn with_stdin(mut self, text: &str) -> Self {
+        self.stdin = Some(text.to_string());
+        self
+    }
+}
+
+/// Writes the previous clipboard contents back when dropped, so the clipboard is
+/// restored however pasting ends.
+struct ClipboardRestore<'a, C: AppContext> {
+    ctx: &'a C,
+    previous: String,
+}
+
+impl<C: AppContext> Drop for ClipboardRestore<'_, C> {
+    fn drop(&mut self) {
+        std::thread::sleep(RESTORE_DELAY);
+        if let Err(e) = self.ctx.write_clipboard(&self.previous) {
+            log::warn!("Failed to restore the clipboard: {}", e);
+        }
+    }
+}
+
+/// Pastes or types `text` into the focused application. For the paste method the
+/// text must already be on the clipboard. `previous_clipboard` is written back
+/// afterwards when restoring is enabled, also if pasting fails.
+pub fn paste_into_active_window<C: AppContext>(
+    ctx: &C,
+    text: &str,
+    settings: &AutoPasteSettings,
+    previous_clipboard: Option<String>,
+) -> AppResult<()> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    paste_with(
+        ctx,
+        text,
+        settings,
+        previous_clipboard,
+        wayland,
+        run_command,
+    )
+}
+
+/// `paste_into_active_window` with the session type and the way commands are run
+/// passed in.
+fn paste_with<C: AppContext>(
+    ctx: &C,
+    text: &str,
+    settings: &AutoPasteSettings,
+    previous_clipboard: Option<String>,
+    wayland: bool,
+    run: impl FnMut(&InputCommand) -> std::io::Result<Output>,
+) -> AppResult<()> {
+    let _restore = previous_clipboard
+        .filter(|_| settings.restore_clipboard)
+        .map(|previous| ClipboardRestore { ctx, previous });
+
+    std::thread::sleep(Duration::from_millis(settings.delay_ms as u64));
+
+    let commands = match settings.method {
+        PasteMethod::Paste => paste_commands(settings, wayland),
+        PasteMethod::Type => type_commands(text, settings, wayland),
+    };
+    let program = run_first_available(commands, run)?;
+    log::info!("Auto-paste sent via {}", program);
+    Ok(())
+}
+
+/// Runs the first command whose program is installed and returns its name.
+fn run_first_available(
+    commands: Vec<InputCommand>,
+    mut run: impl FnMut(&InputCommand) -> std::io::Result<Output>,
+) -> AppResult<&'static str> {
+    let mut tried = Vec::new();
+    for command in commands {
+        let output = match run(&command) {
+            Ok(output) => output,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tried.push(command.program);
+                continue;
+            }
+            Err(e) => {
+                return Err(AppError::AutoPaste(format!(
+                    "Failed to run {}: {}",
+                    command.program, e
+                )))
+            }
+        };
+        if !output.status.success() {
+            return Err(AppError::AutoPaste(format!(
+                "{} exited with {}: {}",
+                command.program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        return Ok(command.program);
+    }
+
+    Err(AppError::AutoPaste(format!(
+        "No input tool found (tried {})",
+        tried.join(", ")
+    )))
+}
+
+/// Runs `command`, feeding it its stdin, and waits for it to exit.
+fn run_command(command: &InputCommand) -> std::io::Result<Output> {
+    let mut process = Command::new(command.program);
+    process
+        .args(&command.args)
+        .stdin(if command.stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW, so no console flashes up
+        process.creation_flags(0x0800_0000);
+    }
+
+    let mut child = process.spawn()?;
+    if let (Some(text), Some(mut stdin)) = (command.stdin.as_deref(), child.stdin.take()) {
+        stdin.write_all(text.as_bytes())?;
+        // stdin is closed when dropped here, so the program sees EOF
+    }
+    child.wait_with_output()
+}
+
+#[cfg(target_os = "linux")]
+fn linux_backends(selected: LinuxInputBackend, wayland: bool) -> Vec<LinuxInputBackend> {
+    match selected {
+        LinuxInputBackend::Auto => {
+            if wayland {
+                vec![
+                    LinuxInputBackend::Wtype,
+                    LinuxInputBackend::Ydotool,
+                    LinuxInputBackend::Xdotool,
+                ]
+            } else {
+                vec![LinuxInputBackend::Xdotool, LinuxInputBackend::Ydotool]
+            }
+        }
+        backend => vec![backend],
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn paste_commands(settings: &AutoPasteSettings, wayland: bool) -> Vec<InputCommand> {
+    linux_backends(settings.linux_backend, wayland)
+        .into_iter()
+        .filter_map(|backend| match backend {
+            LinuxInputBackend::Xdotool => Some(InputCommand::new(
+                "xdotool",
+                &["key", "--clearmodifiers", "ctrl+v"],
+            )),
+            LinuxInputBackend::Wtype => Some(InputCommand::new(
+                "wtype",
+                &["-M", "ctrl", "v", "-m", "ctrl"],
+            )),
+            // Linux input event codes: 29 = KEY_LEFTCTRL, 47 = KEY_V
+            LinuxInputBackend::Ydotool => Some(InputCommand::new(
+                "ydotool",
+                &["key", "29:1", "47:1", "47:0", "29:0"],
+            )),
+            LinuxInputBackend::Auto => None,
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn type_commands(text: &str, settings: &AutoPasteSettings, wayland: bool) -> Vec<InputCommand> {
+    linux_backends(settings.linux_backend, wayland)
+        .into_iter()
+        .filter_map(|backend| match backend {
+            LinuxInputBackend::Xdotool => Some(InputCommand::new(
+                "xdotool",
+                &["type", "--clearmodifiers", "--delay", "0", "--file", "-"],
+            )),
+            LinuxInputBackend::Wtype => Some(InputCommand::new("wtype", &["-"])),
+            LinuxInputBackend::Ydotool => {
+                Some(InputCommand::new("ydotool", &["type", "--file", "-"]))
+            }
+            LinuxInputBackend::Auto => None,
+        })
+        .map(|command| command.with_stdin(text))
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn paste_commands(_settings: &AutoPasteSettings, _wayland: bool) -> Vec<InputCommand> {
+    vec![InputCommand::new(
+        "osascript",
+        &[
+            "-e",
+            "tell application \"System Events\" to keystroke \"v\" using command down",
+        ],
+    )]
+}
+
+#[cfg(target_os = "macos")]
+fn type_commands(text: &str, _settings: &AutoPasteSettings, _wayland: bool) -> Vec<InputCommand> {
+    // Passed as an argument so the text needs no AppleScript escaping
+    vec![InputCommand::new(
+        "osascript",
+        &[
+            "-e",
+            "on run argv",
+            "-e",
+            "tell application \"System Events\" to keystroke (item 1 of argv)",
+            "-e",
+            "end run",
+            text,
+        ],
+    )]
+}
+
+#[cfg(target_os = "windows")]
+fn paste_commands(_settings: &AutoPasteSettings, _wayland: bool) -> Vec<InputCommand> {
+    vec![InputCommand::new(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Add-Type -AssemblyName System.Windows.Forms; [System.Windows.Forms.SendKeys]::SendWait('^v')",
+        ],
+    )]
+}
+
+#[cfg(target_os = "windows")]
+fn type_commands(text: &str, _settings: &AutoPasteSettings, _wayland: bool) -> Vec<InputCommand> {
+    // SendKeys treats + ^ % ~ ( ) { } [ ] as special, so they are wrapped in braces
+    vec![InputCommand::new(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Add-Type -AssemblyName System.Windows.Forms; $t = [Console]::In.ReadToEnd(); [System.Windows.Forms.SendKeys]::SendWait(($t -replace '[+^%~(){}\\[\\]]', '{$0}'))",
+        ],
+    )
+    .with_stdin(text)]
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn paste_commands(_settings: &AutoPasteSettings, _wayland: bool) -> Vec<InputCommand> {
+    Vec::new()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn type_commands(_text: &str, _settings: &AutoPasteSettings, _wayland: bool) -> Vec<InputCommand> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockContext;
+    use std::io::{Error, ErrorKind};
+
+    fn settings(method: PasteMethod) -> AutoPasteSettings {
+        AutoPasteSettings {
+            enabled: true,
+            method,
+            delay_ms: 0,
+            ..AutoPasteSettings::default()
+        }
+    }
+
+    /// Runs nothing; programs not in `installed` are reported as missing.
+    /// Returns the programs that were asked to run.
+    fn stub<'a>(
+        installed: &'a [&'a str],
+        ran: &'a mut Vec<(&'static str, Option<String>)>,
+    ) -> impl FnMut(&InputCommand) -> std::io::Result<Output> + 'a {
+        move |command| {
+            ran.push((command.program, command.stdin.clone()));
+            if !installed.contains(&command.program) {
+                return Err(Error::from(ErrorKind::NotFound));
+            }
+            Ok(Output {
+                status: success(),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            })
+        }
+    }
+
+    #[cfg(unix)]
+    fn success() -> std::process::ExitStatus {
+        std::os::unix::process::ExitStatusExt::from_raw(0)
+    }
+
+    #[cfg(windows)]
+    fn success() -> std::process::ExitStatus {
+        std::os::windows::process::ExitStatusExt::from_raw(0)
+    }
+
+    #[test]
+    fn clipboard_is_restored_when_pasting_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = MockContext::new(dir.path());
+        ctx.write_clipboard("result").unwrap();
+        let mut ran = Vec::new();
+
+        let result = paste_with(
+            &ctx,
+            "result",
+            &settings(PasteMethod::Paste),
+            Some("previous".to_string()),
+            false,
+            stub(&[], &mut ran),
+        );
+
+        assert_eq!(result.unwrap_err().code(), "auto_paste");
+        assert_eq!(ctx.clipboard().as_deref(), Some("previous"));
+    }
+
+    #[test]
+    fn clipboard_is_kept_when_restoring_is_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = MockContext::new(dir.path());
+        ctx.write_clipboard("result").unwrap();
+        let settings = AutoPasteSettings {
+            restore_clipboard: false,
+            ..settings(PasteMethod::Paste)
+        };
+        let mut ran = Vec::new();
+
+        let _ = paste_with(
+            &ctx,
+            "result",
+            &settings,
+            Some("previous".to_string()),
+            false,
+            stub(&[], &mut ran),
+        );
+
+        assert_eq!(ctx.clipboard().as_deref(), Some("result"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn x11_sessions_use_xdotool_then_ydotool() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = MockContext::new(dir.path());
+        let mut ran = Vec::new();
+
+        paste_with(
+            &ctx,
+            "text",
+            &settings(PasteMethod::Paste),
+            None,
+            false,
+            stub(&["ydotool"], &mut ran),
+        )
+        .unwrap();
+
+        let programs: Vec<_> = ran.iter().map(|(program, _)| *program).collect();
+        assert_eq!(programs, ["xdotool", "ydotool"]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn wayland_sessions_prefer_wtype() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = MockContext::new(dir.path());
+        let mut ran = Vec::new();
+
+        paste_with(
+            &ctx,
+            "typed text",
+            &settings(PasteMethod::Type),
+            None,
+            true,
+            stub(&["wtype", "xdotool"], &mut ran),
+        )
+        .unwrap();
+
+        assert_eq!(ran, [("wtype", Some("typed text".to_string()))]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn missing_tools_are_listed_in_the_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = MockContext::new(dir.path());
+        let mut ran = Vec::new();
+
+        let error = paste_with(
+            &ctx,
+            "text",
+            &settings(PasteMethod::Paste),
+            None,
+            true,
+            stub(&[], &mut ran),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.details(),
+            Some("No input tool found (tried wtype, ydotool, xdotool)")
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn a_selected_backend_is_the_only_one_tried() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = MockContext::new(dir.path());
+        let settings = AutoPasteSettings {
+            linux_backend: LinuxInputBackend::Ydotool,
+            ..settings(PasteMethod::Paste)
+        };
+        let mut ran = Vec::new();
+
+        let result = paste_with(
+            &ctx,
+            "text",
+            &settings,
+            None,
+            true,
+            stub(&["wtype", "xdotool"], &mut ran),
+        );
+
+        assert!(result.is_err());
+        let programs: Vec<_> = ran.iter().map(|(program, _)| *program).collect();
+        assert_eq!(programs, ["ydotool"]);
+    }
+}
@@ -1,26 +1,135 @@
-use crate::audio::play_sound;
-use crate::logic::{set_status, AppStatus};
-use crate::models::HistoryItem;
-use crate::store::{load_data, save_data};
+use crate::confidence::{segment_confidence, transcript_confidence, TranscriptConfidence};
+use crate::context::AppContext;
+use crate::dsp::analyze_signal;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Cue, DecodingStrategy, HistoryItem, ImportMode, PipelineStage, Replacement, Settings,
+    StageTiming, Transcript, TranscriptSegment, TranscriptToken, WhisperTask,
+};
+use crate::ollama;
+use crate::output::{build_sinks, deliver_all, Output};
+use crate::progress::{ProgressCallback, ProgressTracker};
+use crate::queue::QueuedSession;
+use crate::store::{load_data, update};
+use regex::Regex;
 use reqwest::blocking::Client;
+use rodio::Source;
+use serde::Serialize;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::fs::File;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
 use tauri::{AppHandle, Emitter, Manager};
-use tauri_plugin_clipboard_manager::ClipboardExt;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use tokio_util::sync::CancellationToken;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
+
+/// Cancellation tokens of the running pipeline sessions, keyed by session id
+pub struct PipelineState {
+    sessions: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl Default for PipelineState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PipelineState {
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn register(&self, session_id: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(session_id.to_string(), token.clone());
+        token
+    }
+
+    pub(crate) fn remove(&self, session_id: &str) {
+        self.sessions.lock().unwrap().remove(session_id);
+    }
+
+    /// Cancels one session. Returns false if it is not running (anymore).
+    pub fn cancel(&self, session_id: &str) -> bool {
+        match self.sessions.lock().unwrap().get(session_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn cancel_all(&self) {
+        for token in self.sessions.lock().unwrap().values() {
+            token.cancel();
+        }
+    }
+}
+
+/// The loaded Whisper model. Sessions and batch jobs share it, so the model is
+/// held in memory once; it is dropped when neither uses it any more.
+static WHISPER_MODEL: Mutex<Option<(PathBuf, Weak<WhisperContext>)>> = Mutex::new(None);
 
-use once_cell::sync::Lazy;
+/// Whisper runs one inference at a time, so a session does not compete with a
+/// batch job for the CPU.
+static WHISPER_INFERENCE: Mutex<()> = Mutex::new(());
 
-static CANCEL_FLAG: Lazy<Arc<AtomicBool>> = Lazy::new(|| Arc::new(AtomicBool::new(false)));
+/// Audio formats accepted for imported files
+pub const SUPPORTED_AUDIO_EXTENSIONS: &[&str] = &["wav", "flac", "mp3", "ogg"];
+
+/// Payload of the `pipeline-error` event: the error fields plus the session it belongs to
+#[derive(Debug, Serialize)]
+pub struct PipelineFailure<'a> {
+    pub session_id: Option<&'a str>,
+    #[serde(flatten)]
+    pub error: &'a AppError,
+}
 
-pub fn cancel_pipeline() {
-    CANCEL_FLAG.store(true, Ordering::Relaxed);
+/// Payload of the `pipeline-complete` event
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineResult {
+    pub session_id: String,
+    pub status: &'static str,
+    pub history_id: String,
+    pub enriched_content: String,
+    pub instruction: Option<TranscriptConfidence>,
+    pub content: TranscriptConfidence,
+    pub stage_timings: Vec<StageTiming>,
+    /// Names of the outputs the result was delivered to
+    pub outputs: Vec<&'static str>,
 }
 
-const OLLAMA_API_URL: &str = "http://localhost:11434/api/chat";
+// Minimum amount of audio above the silence threshold for a recording to count as speech
+const MIN_VOICED_MS: u64 = 200;
+
+// Phrases Whisper is known to produce on silence or noise. Segments consisting only
+// of these are dropped when Whisper was also unsure about them, so a recording that
+// really says "Thank you." is kept; bracketed markers like [BLANK_AUDIO] are always
+// dropped.
+const HALLUCINATIONS: &[&str] = &[
+    "thank you",
+    "thank you very much",
+    "thanks for watching",
+    "thank you for watching",
+    "please subscribe",
+    "subtitles by the amaraorg community",
+    "you",
+    "bye",
+    "vielen dank",
+    "untertitel im auftrag des zdf",
+    "untertitel der amaraorg-community",
+];
+
 const MODEL_URL: &str =
     "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo-q8_0.bin";
 const MODEL_FILENAME: &str = "ggml-large-v3-turbo-q8_0.bin";
@@ -29,113 +138,468 @@ const MODEL_FILENAME: &str = "ggml-large-v3-turbo-q8_0.bin";
 const EXPECTED_MODEL_SIZE: u64 = 874_188_075; // Actual model size
 const MODEL_SIZE_TOLERANCE: u64 = 10_485_760; // Allow 10MB variance
 
-pub fn run_pipeline(app: AppHandle) {
-    // Reset cancel flag
-    CANCEL_FLAG.store(false, Ordering::Relaxed);
-
-    std::thread::spawn(move || {
-        match internal_run_pipeline(&app) {
-            Ok(_) => {
-                log::info!("Pipeline completed successfully");
-                // Only set status to Idle on successful completion
-                // This ensures we don't interrupt a new session that may have started
-                set_status(&app, AppStatus::Idle);
-            }
-            Err(e) => {
-                log::error!("Pipeline failed: {}", e);
-                // Emit error event to frontend
-                app.emit("pipeline-error", e.clone()).unwrap_or_default();
-                play_sound("Click"); // Error sound
-                                     // DO NOT reset status here - the user may have already started a new session
-                                     // The stop_pipeline command already sets status to Idle immediately when user stops
-            }
+/// Processes one recorded session or imported file from the queue. The outcome is
+/// reported through the session's events (`pipeline-complete`, `pipeline-error` or
+/// `pipeline-cancelled`).
+pub(crate) async fn run_session(app: &AppHandle, session: &QueuedSession) -> AppResult<()> {
+    let cancel = app.state::<PipelineState>().register(&session.id);
+    app.emit("pipeline-started", &session.id)
+        .unwrap_or_default();
+
+    let result = match &session.import {
+        Some(import) => {
+            internal_run_import(
+                app,
+                &session.id,
+                &cancel,
+                &session.content_path,
+                import.mode,
+                import.instruction.clone(),
+            )
+            .await
         }
-    });
+        None => process_session(app, session, &cancel).await,
+    };
+    app.state::<PipelineState>().remove(&session.id);
+    match &result {
+        Ok(_) => log::info!("Pipeline {} completed successfully", session.id),
+        Err(AppError::Cancelled) => {
+            log::info!("Pipeline {} cancelled", session.id);
+            app.emit("pipeline-cancelled", &session.id)
+                .unwrap_or_default();
+        }
+        Err(e) => {
+            log::error!(
+                "Pipeline {} failed: [{}] {} {:?}",
+                session.id,
+                e.code(),
+                e,
+                e.details()
+            );
+            // Emit error event to frontend
+            app.emit(
+                "pipeline-error",
+                PipelineFailure {
+                    session_id: Some(session.id.as_str()),
+                    error: e,
+                },
+            )
+            .unwrap_or_default();
+            app.play_cue(Cue::Error);
+        }
+    }
+    result
 }
 
-fn internal_run_pipeline(app: &AppHandle) -> Result<(), String> {
-    log::info!("Starting pipeline...");
+/// Runs blocking work (model download and load, decoding, inference) on the
+/// blocking thread pool. Returns as soon as `cancel` fires; the work itself has to
+/// observe the token if it should stop early.
+async fn run_blocking<T, F>(cancel: &CancellationToken, f: F) -> AppResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> AppResult<T> + Send + 'static,
+{
+    let task = tokio::task::spawn_blocking(f);
+    tokio::select! {
+        _ = cancel.cancelled() => Err(AppError::Cancelled),
+        result = task => result.map_err(|e| AppError::Internal(e.to_string()))?,
+    }
+}
 
-    // 1. Get Paths and Ensure Model
-    let instruction_path = app.path().app_data_dir().unwrap().join("instruction.wav");
-    let content_path = app.path().app_data_dir().unwrap().join("content.wav");
-    let model_path = app.path().app_data_dir().unwrap().join(MODEL_FILENAME);
+/// Transcribes, enriches and delivers a recorded session and adds it to history.
+pub async fn process_session<C: AppContext + Clone + 'static>(
+    ctx: &C,
+    session: &QueuedSession,
+    cancel: &CancellationToken,
+) -> AppResult<()> {
+    log::info!("Starting pipeline for session {}...", session.id);
+    let session_id = session.id.as_str();
+
+    // A session can be cancelled while it waits in the queue
+    if cancel.is_cancelled() {
+        return Err(AppError::Cancelled);
+    }
 
-    log::info!("Checking for Whisper model...");
-    ensure_whisper_model(app, &model_path)?;
+    // 1. Get Paths
+    let instruction_path = session.instruction_path.clone();
+    let content_path = session.content_path.clone();
 
     // 2. Load Settings & Model
     log::info!("Loading settings...");
-    let data = load_data(app)?;
+    let data = load_data(ctx)?;
     let model = data.settings.model.clone();
 
-    // Load Whisper Context (can take some time, maybe cache this in state later if slow)
-    // For now simple load on demand
-    log::info!("Loading Whisper model into memory...");
-    app.emit("pipeline-status", "Loading AI model...")
-        .unwrap_or_default();
-    let ctx = WhisperContext::new_with_params(
-        &model_path.to_string_lossy(),
-        WhisperContextParameters::default(),
-    )
-    .map_err(|e| format!("Failed to load Whisper model: {}", e))?;
-
-    // Check if cancelled
-    if CANCEL_FLAG.load(Ordering::Relaxed) {
-        return Err("Pipeline cancelled by user".to_string());
+    // Reject empty or silent recordings before any model work
+    {
+        let (instruction_path, content_path) = (instruction_path.clone(), content_path.clone());
+        let settings = data.settings.clone();
+        run_blocking(cancel, move || {
+            check_recording(&instruction_path, "instruction", &settings)?;
+            check_recording(&content_path, "content", &settings)
+        })
+        .await?;
     }
 
+    let mut progress = ProgressTracker::new(ctx, session_id, PipelineStage::ModelCheck);
+    let transcriber = load_transcriber(ctx, &mut progress, cancel).await?;
+
     // 3. Transcribe Instruction
     log::info!("Transcribing instruction...");
-    let instruction_text = transcribe_local(&ctx, &instruction_path)?;
+    progress.stage(PipelineStage::TranscribeInstruction);
+    let instruction = {
+        let (transcriber, settings) = (transcriber.clone(), data.settings.clone());
+        let (on_progress, token) = (progress.percent_callback(), cancel.clone());
+        run_blocking(cancel, move || {
+            transcribe_local(
+                &*transcriber,
+                &instruction_path,
+                &settings,
+                Some(on_progress),
+                Some(token),
+            )
+        })
+        .await?
+    };
+    let instruction_text = instruction.text.clone();
     log::info!("Instruction: {}", instruction_text);
 
-    // Check if cancelled
-    if CANCEL_FLAG.load(Ordering::Relaxed) {
-        return Err("Pipeline cancelled by user".to_string());
-    }
-
     // 4. Transcribe Content
     log::info!("Transcribing content...");
-    let content_text = transcribe_local(&ctx, &content_path)?;
+    progress.stage(PipelineStage::TranscribeContent);
+    let content = {
+        let (transcriber, settings) = (transcriber.clone(), data.settings.clone());
+        let (on_progress, token) = (progress.percent_callback(), cancel.clone());
+        run_blocking(cancel, move || {
+            transcribe_local(
+                &*transcriber,
+                &content_path,
+                &settings,
+                Some(on_progress),
+                Some(token),
+            )
+        })
+        .await?
+    };
+    let content_text = content.text.clone();
     log::info!("Content: {}", content_text);
 
-    // Check if cancelled
-    if CANCEL_FLAG.load(Ordering::Relaxed) {
-        return Err("Pipeline cancelled by user".to_string());
+    if instruction_text.is_empty() {
+        return Err(AppError::NoSpeech("instruction recording".to_string()));
+    }
+    if content_text.is_empty() {
+        return Err(AppError::NoSpeech("content recording".to_string()));
+    }
+
+    let threshold = data.settings.low_confidence_threshold;
+    let instruction_confidence = TranscriptConfidence::new(&instruction, threshold);
+    let content_confidence = TranscriptConfidence::new(&content, threshold);
+    ctx.emit_event(
+        "transcripts-ready",
+        json!({
+            "session_id": session_id,
+            "instruction": &instruction_confidence,
+            "content": &content_confidence,
+        }),
+    );
+
+    // Don't enrich on a misheard instruction; the user should repeat it instead
+    if let (Some(min), Some(confidence)) = (
+        data.settings.min_instruction_confidence,
+        instruction.confidence,
+    ) {
+        if confidence < min {
+            return Err(AppError::LowConfidence(confidence));
+        }
     }
 
     // 5. Enrich
     log::info!("Enriching with model: {}", model);
-    let enriched_text = enrich(&instruction_text, &content_text, &model)?;
+    progress.stage(PipelineStage::Enrich);
+    let enriched_text = enrich(&instruction_text, &content_text, &data.settings, cancel).await?;
     log::info!("Enrichment complete.");
 
-    // 6. Clipboard
-    app.clipboard()
-        .write_text(enriched_text.clone())
-        .map_err(|e| format!("Clipboard error: {}", e))?;
+    // 6. Output
+    progress.stage(PipelineStage::Output);
+    let output = Output {
+        session_id: session_id.to_string(),
+        timestamp: chrono::Local::now(),
+        instruction: instruction_text.clone(),
+        original_content: content_text.clone(),
+        text: enriched_text.clone(),
+    };
+    let outputs = deliver_result(ctx, output, &data.settings, cancel).await?;
 
     // 7. Save History
+    progress.stage(PipelineStage::Save);
+    // Ends the Save stage before writing, so the item records it too
+    let stage_timings = progress.finish();
     let item = HistoryItem {
         id: uuid::Uuid::new_v4().to_string(),
         timestamp: chrono::Local::now().to_rfc3339(),
         instruction: instruction_text,
         original_content: content_text,
-        enriched_content: enriched_text,
+        enriched_content: enriched_text.clone(),
+        source_file: None,
+        language: content.language.clone(),
+        language_probability: content.language_probability,
+        instruction_transcript: Some(instruction),
+        content_transcript: Some(content),
+        stage_timings: stage_timings.clone(),
     };
+    let history_id = item.id.clone();
 
-    let mut current_data = load_data(app)?;
-    current_data.history.push(item);
-    save_data(app, &current_data)?;
+    update(ctx, |data| data.history.push(item))?;
 
     // 8. Success
-    play_sound("Success");
-    app.emit("pipeline-complete", "success").unwrap_or_default();
+    ctx.play_cue(Cue::Success);
+    ctx.emit_event(
+        "pipeline-complete",
+        PipelineResult {
+            session_id: session_id.to_string(),
+            status: "success",
+            history_id,
+            enriched_content: enriched_text,
+            instruction: Some(instruction_confidence),
+            content: content_confidence,
+            stage_timings,
+            outputs,
+        },
+    );
+
+    Ok(())
+}
+
+/// Payload of the `output-failed` event
+#[derive(Debug, Serialize)]
+pub struct OutputFailure<'a> {
+    pub session_id: &'a str,
+    pub sink: &'static str,
+    #[serde(flatten)]
+    pub error: &'a AppError,
+}
+
+/// Sends the result to the outputs of the active template. A failing output does
+/// not fail the session, since the result is still saved to history; each
+/// failure is reported as `output-failed`. Returns the outputs that received it.
+async fn deliver_result<C: AppContext + Clone + 'static>(
+    ctx: &C,
+    output: Output,
+    settings: &Settings,
+    cancel: &CancellationToken,
+) -> AppResult<Vec<&'static str>> {
+    let sinks = build_sinks(ctx, settings);
+    let mut delivered: Vec<_> = sinks.iter().map(|sink| sink.name()).collect();
+    let session_id = output.session_id.clone();
+    let failures = run_blocking(cancel, move || Ok(deliver_all(&sinks, &output))).await?;
+    for (sink, e) in &failures {
+        if let Some(index) = delivered.iter().position(|name| name == sink) {
+            delivered.remove(index);
+        }
+        log::error!(
+            "Output {} failed for session {}: {} {:?}",
+            sink,
+            session_id,
+            e,
+            e.details()
+        );
+        ctx.emit_event(
+            "output-failed",
+            OutputFailure {
+                session_id: &session_id,
+                sink,
+                error: e,
+            },
+        );
+    }
+    Ok(delivered)
+}
+
+async fn internal_run_import<C: AppContext + Clone + 'static>(
+    ctx: &C,
+    session_id: &str,
+    cancel: &CancellationToken,
+    path: &Path,
+    mode: ImportMode,
+    instruction: Option<String>,
+) -> AppResult<()> {
+    log::info!("Importing audio file {:?} ({:?})", path, mode);
+
+    let instruction = match mode {
+        ImportMode::Transcribe => None,
+        ImportMode::Enrich => match instruction.map(|i| i.trim().to_string()) {
+            Some(i) if !i.is_empty() => Some(i),
+            _ => {
+                return Err(AppError::InvalidInput(
+                    "An instruction is required to enrich an imported file".to_string(),
+                ))
+            }
+        },
+    };
+
+    let source_file = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+
+    // Decode before loading the model so unsupported files fail fast
+    ctx.emit_event("pipeline-status", "Decoding audio file...");
+    let samples = {
+        let path = path.to_path_buf();
+        run_blocking(cancel, move || decode_audio_file(&path)).await?
+    };
+
+    let data = load_data(ctx)?;
+
+    // Same silence check as for recordings, before the model is loaded
+    check_speech(&samples, &source_file, &data.settings)?;
+
+    let mut progress = ProgressTracker::new(ctx, session_id, PipelineStage::ModelCheck);
+    let transcriber = load_transcriber(ctx, &mut progress, cancel).await?;
+
+    log::info!("Transcribing imported file...");
+    ctx.emit_event("pipeline-status", "Transcribing audio file...");
+    progress.stage(PipelineStage::TranscribeContent);
+    let content = {
+        let (transcriber, settings) = (transcriber.clone(), data.settings.clone());
+        let (on_progress, token) = (progress.percent_callback(), cancel.clone());
+        run_blocking(cancel, move || {
+            transcribe_samples(
+                &*transcriber,
+                &samples,
+                &settings,
+                Some(on_progress),
+                Some(token),
+            )
+        })
+        .await?
+    };
+    let content_text = content.text.clone();
+    if content_text.is_empty() {
+        return Err(AppError::NoSpeech(source_file));
+    }
+    let content_confidence =
+        TranscriptConfidence::new(&content, data.settings.low_confidence_threshold);
+    ctx.emit_event(
+        "transcripts-ready",
+        json!({ "session_id": session_id, "content": &content_confidence }),
+    );
+
+    // Enrich only when requested, otherwise the transcript is the result
+    let enriched_text = match &instruction {
+        Some(instruction) => {
+            log::info!("Enriching with model: {}", data.settings.model);
+            progress.stage(PipelineStage::Enrich);
+            enrich(instruction, &content_text, &data.settings, cancel).await?
+        }
+        None => content_text.clone(),
+    };
+
+    progress.stage(PipelineStage::Output);
+    let output = Output {
+        session_id: session_id.to_string(),
+        timestamp: chrono::Local::now(),
+        instruction: instruction.clone().unwrap_or_default(),
+        original_content: content_text.clone(),
+        text: enriched_text.clone(),
+    };
+    let outputs = deliver_result(ctx, output, &data.settings, cancel).await?;
+
+    progress.stage(PipelineStage::Save);
+    let stage_timings = progress.finish();
+    let item = HistoryItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Local::now().to_rfc3339(),
+        instruction: instruction.unwrap_or_default(),
+        original_content: content_text,
+        enriched_content: enriched_text.clone(),
+        source_file: Some(source_file),
+        language: content.language.clone(),
+        language_probability: content.language_probability,
+        instruction_transcript: None,
+        content_transcript: Some(content),
+        stage_timings: stage_timings.clone(),
+    };
+    let history_id = item.id.clone();
+
+    update(ctx, |data| data.history.push(item))?;
+
+    ctx.play_cue(Cue::Success);
+    ctx.emit_event(
+        "pipeline-complete",
+        PipelineResult {
+            session_id: session_id.to_string(),
+            status: "success",
+            history_id,
+            enriched_content: enriched_text,
+            instruction: None,
+            content: content_confidence,
+            stage_timings,
+            outputs,
+        },
+    );
 
     Ok(())
 }
 
-fn ensure_whisper_model(app: &AppHandle, path: &PathBuf) -> Result<(), String> {
+pub fn load_whisper_context(ctx: &impl AppContext) -> AppResult<Arc<WhisperContext>> {
+    let model_path = whisper_model_path(ctx);
+
+    log::info!("Checking for Whisper model...");
+    ensure_whisper_model(ctx, &model_path)?;
+    load_whisper_model(ctx, &model_path)
+}
+
+/// Model check and load as separate progress stages, unless the host provides
+/// its own transcriber.
+async fn load_transcriber<C: AppContext + Clone + 'static>(
+    ctx: &C,
+    progress: &mut ProgressTracker<C>,
+    cancel: &CancellationToken,
+) -> AppResult<Arc<dyn Transcriber>> {
+    if let Some(transcriber) = ctx.transcriber() {
+        return Ok(transcriber);
+    }
+
+    let model_path = whisper_model_path(ctx);
+    {
+        let (ctx, model_path) = (ctx.clone(), model_path.clone());
+        run_blocking(cancel, move || ensure_whisper_model(&ctx, &model_path)).await?;
+    }
+    progress.stage(PipelineStage::ModelLoad);
+    let ctx = ctx.clone();
+    let whisper = run_blocking(cancel, move || load_whisper_model(&ctx, &model_path)).await?;
+    Ok(whisper)
+}
+
+pub fn whisper_model_path(ctx: &impl AppContext) -> PathBuf {
+    ctx.data_dir().join(MODEL_FILENAME)
+}
+
+/// Returns the loaded model, or loads it if nothing holds it.
+fn load_whisper_model(ctx: &impl AppContext, model_path: &Path) -> AppResult<Arc<WhisperContext>> {
+    // Held while loading, so a second caller waits for the model instead of loading it again
+    let mut loaded = WHISPER_MODEL.lock().unwrap();
+    if let Some((path, model)) = loaded.as_ref() {
+        if let Some(model) = model.upgrade().filter(|_| path == model_path) {
+            log::info!("Using the loaded Whisper model");
+            return Ok(model);
+        }
+    }
+
+    log::info!("Loading Whisper model into memory...");
+    ctx.emit_event("pipeline-status", "Loading AI model...");
+    let model = WhisperContext::new_with_params(
+        &model_path.to_string_lossy(),
+        WhisperContextParameters::default(),
+    )
+    .map(Arc::new)
+    .map_err(|e| AppError::ModelMissing(e.to_string()))?;
+    *loaded = Some((model_path.to_path_buf(), Arc::downgrade(&model)));
+    Ok(model)
+}
+
+/// Downloads the Whisper model unless a complete copy exists at `path`.
+pub fn ensure_whisper_model(ctx: &impl AppContext, path: &PathBuf) -> AppResult<()> {
     // Check if file exists and validate its size
     if path.exists() {
         match std::fs::metadata(path) {
@@ -180,33 +644,27 @@ fn ensure_whisper_model(app: &AppHandle, path: &PathBuf) -> Result<(), String> {
     log::info!("This may take several minutes (model is ~1.5GB)...");
 
     // Emit status to UI
-    app.emit(
+    ctx.emit_event(
         "pipeline-status",
         "Downloading AI model (this may take several minutes)...",
-    )
-    .unwrap_or_default();
+    );
 
     // Create dir if needed
     if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| {
-            format!(
-                "Failed to create model directory: {}. Please check write permissions.",
-                e
-            )
-        })?;
+        std::fs::create_dir_all(parent)?;
     }
 
     let mut response = Client::new()
         .get(MODEL_URL)
         .timeout(std::time::Duration::from_secs(600)) // 10 minute timeout for large file
         .send()
-        .map_err(|e| format!("Failed to download Whisper model. Please check your internet connection. Error: {}", e))?;
+        .map_err(|e| AppError::DownloadFailed(e.to_string()))?;
 
     if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download Whisper model. Server returned status: {}. Please try again later.",
+        return Err(AppError::DownloadFailed(format!(
+            "Server returned status {}",
             response.status()
-        ));
+        )));
     }
 
     // Get expected content length if available
@@ -219,19 +677,13 @@ fn ensure_whisper_model(app: &AppHandle, path: &PathBuf) -> Result<(), String> {
         );
     }
 
-    let mut file = File::create(path).map_err(|e| {
-        format!(
-            "Failed to create model file. Please check write permissions. Error: {}",
-            e
-        )
-    })?;
+    let mut file = File::create(path)?;
 
-    let bytes_written = response.copy_to(&mut file)
-        .map_err(|e| {
-            // If download fails, delete the incomplete file
-            let _ = std::fs::remove_file(path);
-            format!("Failed to save model file. Please ensure you have enough disk space (~1.5GB). Error: {}", e)
-        })?;
+    let bytes_written = response.copy_to(&mut file).map_err(|e| {
+        // If download fails, delete the incomplete file
+        let _ = std::fs::remove_file(path);
+        AppError::DownloadFailed(e.to_string())
+    })?;
 
     log::info!(
         "Whisper model download complete! Downloaded {} bytes",
@@ -242,27 +694,109 @@ fn ensure_whisper_model(app: &AppHandle, path: &PathBuf) -> Result<(), String> {
     if let Some(expected) = content_length {
         if bytes_written != expected {
             let _ = std::fs::remove_file(path);
-            return Err(format!(
-                "Download incomplete: received {} bytes but expected {} bytes. Please try again.",
+            return Err(AppError::DownloadFailed(format!(
+                "Download incomplete: received {} bytes but expected {} bytes",
                 bytes_written, expected
-            ));
+            )));
         }
     }
 
     Ok(())
 }
 
-fn transcribe_local(ctx: &WhisperContext, path: &PathBuf) -> Result<String, String> {
+/// Checks duration and energy of a recorded WAV before it is transcribed.
+fn check_recording(path: &Path, label: &str, settings: &Settings) -> AppResult<()> {
     if !path.exists() {
-        return Err(format!("File not found: {:?}", path));
+        return Err(AppError::RecordingMissing(label.to_string()));
+    }
+
+    let samples = read_wav_samples(path)?;
+    let stats = analyze_signal(&samples, 16000, settings.silence_threshold_dbfs);
+    log::info!(
+        "{} recording: {} ms, {:.1} dBFS RMS, {} ms voiced",
+        label,
+        stats.duration_ms,
+        stats.rms_dbfs,
+        stats.voiced_ms
+    );
+
+    if stats.duration_ms < settings.min_recording_ms as u64 {
+        return Err(AppError::RecordingTooShort {
+            label: label.to_string(),
+            duration_ms: stats.duration_ms,
+        });
+    }
+    if stats.voiced_ms < MIN_VOICED_MS {
+        return Err(AppError::RecordingSilent(label.to_string()));
     }
+    Ok(())
+}
 
-    // Read WAV and convert to f32 16kHz mono
+/// Rejects decoded audio with too little sound above the silence threshold to be
+/// speech, like `check_recording` does for recordings.
+pub fn check_speech(samples: &[f32], label: &str, settings: &Settings) -> AppResult<()> {
+    let stats = analyze_signal(samples, 16000, settings.silence_threshold_dbfs);
+    if stats.voiced_ms < MIN_VOICED_MS {
+        return Err(AppError::RecordingSilent(label.to_string()));
+    }
+    Ok(())
+}
+
+/// Lowercases and strips punctuation so phrases can be compared with `HALLUCINATIONS`.
+fn normalize_phrase(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace() || *c == '-')
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_non_speech_marker(text: &str) -> bool {
+    let text = text.trim();
+    (text.starts_with('[') && text.ends_with(']')) || (text.starts_with('(') && text.ends_with(')'))
+}
+
+fn is_hallucination_phrase(text: &str) -> bool {
+    let normalized = normalize_phrase(text);
+    normalized.is_empty() || HALLUCINATIONS.contains(&normalized.as_str())
+}
+
+/// A segment is a likely hallucination if it consists of a known phrase and Whisper
+/// either thought there was no speech or was unsure of the words. Empty segments
+/// are always dropped.
+fn is_hallucination(segment: &TranscriptSegment, settings: &Settings) -> bool {
+    if normalize_phrase(&segment.text).is_empty() {
+        return true;
+    }
+    let weak = segment.no_speech_probability >= settings.decoding.no_speech_threshold
+        || segment.confidence < settings.low_confidence_threshold;
+    weak && is_hallucination_phrase(&segment.text)
+}
+
+pub fn transcribe_local(
+    transcriber: &dyn Transcriber,
+    path: &Path,
+    settings: &Settings,
+    on_progress: Option<ProgressCallback>,
+    cancel: Option<CancellationToken>,
+) -> AppResult<Transcript> {
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("{:?}", path)));
+    }
+
+    let samples = decode_audio_file(path)?;
+    transcribe_samples(transcriber, &samples, settings, on_progress, cancel)
+}
+
+/// Reads a WAV file and converts it to f32 16kHz mono, as expected by Whisper.
+fn read_wav_samples(path: &Path) -> AppResult<Vec<f32>> {
     // Note: audio.rs should now try to record 16kHz.
-    // If it recorded at another rate, this simple reader will fail context expectations
-    // because Whisper expects 16k.
+    // Other rates are resampled below because Whisper expects 16k.
 
-    let mut reader = hound::WavReader::open(path).map_err(|e| e.to_string())?;
+    let decode_error = |e: hound::Error| AppError::AudioDecode(format!("{:?}: {}", path, e));
+    let mut reader = hound::WavReader::open(path).map_err(decode_error)?;
     let spec = reader.spec();
     log::info!(
         "Audio file spec: {} channels, {} Hz, {:?} format",
@@ -271,92 +805,434 @@ fn transcribe_local(ctx: &WhisperContext, path: &PathBuf) -> Result<String, Stri
         spec.sample_format
     );
 
-    // Convert samples to f32
+    // Convert samples to f32, scaled by the bit depth (8, 16, 24 or 32 bit)
     let samples: Vec<f32> = match spec.sample_format {
-        hound::SampleFormat::Int => reader
-            .samples::<i16>()
-            .map(|s| s.map(|x| x as f32 / 32768.0).unwrap_or(0.0))
-            .collect(),
-        hound::SampleFormat::Float => reader.samples::<f32>().map(|s| s.unwrap_or(0.0)).collect(),
+        hound::SampleFormat::Int => {
+            let scale = (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|x| x as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(decode_error)?
+        }
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(decode_error)?,
     };
 
+    Ok(to_whisper_input(samples, spec.channels, spec.sample_rate))
+}
+
+/// Decodes an imported audio file (WAV, FLAC, MP3, OGG) to f32 16kHz mono.
+pub fn decode_audio_file(path: &Path) -> AppResult<Vec<f32>> {
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("{:?}", path)));
+    }
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    // hound handles all WAV variants we record ourselves
+    if extension == "wav" {
+        return read_wav_samples(path);
+    }
+
+    let file = File::open(path)?;
+    let decoder = rodio::Decoder::new(BufReader::new(file)).map_err(|e| {
+        AppError::AudioDecode(format!(
+            "Unsupported or corrupted audio file {:?}: {}",
+            path, e
+        ))
+    })?;
+
+    let channels = decoder.channels();
+    let sample_rate = decoder.sample_rate();
+    log::info!(
+        "Decoded audio file spec: {} channels, {} Hz",
+        channels,
+        sample_rate
+    );
+
+    let samples: Vec<f32> = decoder.convert_samples::<f32>().collect();
+    if samples.is_empty() {
+        return Err(AppError::AudioDecode(format!(
+            "Audio file {:?} contains no samples",
+            path
+        )));
+    }
+
+    Ok(to_whisper_input(samples, channels, sample_rate))
+}
+
+/// Downmixes interleaved samples to mono and resamples to 16kHz.
+fn to_whisper_input(samples: Vec<f32>, channels: u16, sample_rate: u32) -> Vec<f32> {
     // If stereo (channels=2), filter to mono (take every 2nd sample or average)
-    let mut mono_samples: Vec<f32> = if spec.channels == 2 {
+    let mut mono_samples: Vec<f32> = if channels == 2 {
         samples
             .chunks(2)
-            .map(|chunk| (chunk[0] + chunk[1]) / 2.0)
+            .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
             .collect()
-    } else if spec.channels == 1 {
+    } else if channels <= 1 {
         samples
     } else {
         // Fallback for > 2 channels: just take first channel
         samples
-            .chunks(spec.channels as usize)
+            .chunks(channels as usize)
             .map(|chunk| chunk[0])
             .collect()
     };
 
     // Resample if needed
-    if spec.sample_rate != 16000 {
-        log::info!("Resampling from {} Hz to 16000 Hz", spec.sample_rate);
-        mono_samples = resample_linear(&mono_samples, spec.sample_rate, 16000);
+    if sample_rate != 16000 {
+        log::info!("Resampling from {} Hz to 16000 Hz", sample_rate);
+        mono_samples = resample_linear(&mono_samples, sample_rate, 16000);
     }
 
-    log::info!("Creating Whisper inference state...");
-    // Create state
-    let mut state = ctx
-        .create_state()
-        .map_err(|e| format!("Failed to create Whisper state: {}", e))?;
+    mono_samples
+}
 
-    log::info!("Configuring Whisper parameters...");
-    // Set params
-    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+/// Speech recognition backend. Whisper in the app; tests substitute a stub so the
+/// pipeline runs without the model.
+pub trait Transcriber: Send + Sync {
+    /// Recognizes 16kHz mono samples. Non-speech markers, hallucinations and
+    /// replacements are handled by the caller.
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        settings: &Settings,
+        on_progress: Option<ProgressCallback>,
+        cancel: Option<CancellationToken>,
+    ) -> AppResult<Transcript>;
+}
 
-    // Use 4 threads for better performance
-    params.set_n_threads(4);
+/// Transcribes 16kHz mono samples and cleans up the result.
+pub fn transcribe_samples(
+    transcriber: &dyn Transcriber,
+    mono_samples: &[f32],
+    settings: &Settings,
+    on_progress: Option<ProgressCallback>,
+    cancel: Option<CancellationToken>,
+) -> AppResult<Transcript> {
+    let mut transcript = transcriber.transcribe(mono_samples, settings, on_progress, cancel)?;
+    let segments = &mut transcript.segments;
+
+    // Drop non-speech markers and likely hallucinations
+    segments.retain(|s| {
+        if is_non_speech_marker(&s.text) {
+            return false;
+        }
+        if is_hallucination(s, settings) {
+            log::warn!(
+                "Discarding likely hallucination: {:?} (no speech p={:.2}, confidence {:.2})",
+                s.text,
+                s.no_speech_probability,
+                s.confidence
+            );
+            return false;
+        }
+        true
+    });
 
-    // Use "auto" for automatic language detection
-    params.set_language(Some("auto"));
-    params.set_print_special(false);
-    params.set_print_progress(false);
-    params.set_print_realtime(false);
-    params.set_print_timestamps(false);
+    // Scored on the recognized tokens, before replacements merge them
+    transcript.confidence = transcript_confidence(segments);
 
-    log::info!(
-        "Running Whisper inference with auto language detection on {} samples...",
-        mono_samples.len()
-    );
-    // Run
-    state
-        .full(params, &mono_samples[..])
-        .map_err(|e| format!("failed to run model: {}", e))?;
-
-    log::info!("Whisper inference complete, extracting text...");
-
-    // Collect text
-    let num_segments = state.full_n_segments();
-    let mut text = String::new();
-    for i in 0..num_segments {
-        if let Some(segment) = state.get_segment(i) {
-            if let Ok(s) = segment.to_str() {
-                text.push_str(s);
-                text.push(' ');
+    // Apply the correction dictionary to every segment and join the corrected segments
+    let replacements = compile_replacements(settings.effective_replacements())?;
+    for segment in segments.iter_mut() {
+        apply_replacements(segment, &replacements);
+    }
+    transcript.text = segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Ok(transcript)
+}
+
+impl Transcriber for WhisperContext {
+    fn transcribe(
+        &self,
+        mono_samples: &[f32],
+        settings: &Settings,
+        on_progress: Option<ProgressCallback>,
+        cancel: Option<CancellationToken>,
+    ) -> AppResult<Transcript> {
+        let _inference = WHISPER_INFERENCE.lock().unwrap();
+        log::info!("Creating Whisper inference state...");
+        // Create state
+        let mut state = self.create_state().map_err(|e| {
+            AppError::Transcription(format!("Failed to create Whisper state: {}", e))
+        })?;
+
+        let decoding = &settings.decoding;
+        let n_threads = decoding.thread_count() as i32;
+
+        // Detect the language up front so we can report its probability and pin the
+        // decoder to it, instead of letting Whisper detect it again internally
+        let configured = settings.transcription_language.trim();
+        let (language, language_probability) = if configured.is_empty() || configured == "auto" {
+            detect_language(&mut state, mono_samples, n_threads)?
+        } else {
+            let language = whisper_rs::get_lang_id(configured)
+                .and_then(whisper_rs::get_lang_str)
+                .ok_or_else(|| {
+                    AppError::InvalidSettings(format!(
+                        "Unsupported transcription language '{}'",
+                        configured
+                    ))
+                })?;
+            (language, None)
+        };
+
+        log::info!("Configuring Whisper parameters...");
+        // Set params
+        let strategy = match decoding.strategy {
+            DecodingStrategy::Greedy => SamplingStrategy::Greedy {
+                best_of: decoding.best_of as i32,
+            },
+            DecodingStrategy::BeamSearch => SamplingStrategy::BeamSearch {
+                beam_size: decoding.beam_size as i32,
+                patience: -1.0,
+            },
+        };
+        let mut params = FullParams::new(strategy);
+
+        // Bias recognition towards user vocabulary (names, product terms, identifiers)
+        let vocabulary = settings.effective_vocabulary();
+        if !vocabulary.is_empty() {
+            params.set_initial_prompt(&vocabulary.join(", "));
+        }
+
+        params.set_n_threads(n_threads);
+        params.set_temperature(decoding.temperature);
+        params.set_temperature_inc(decoding.temperature_increment);
+        params.set_no_speech_thold(decoding.no_speech_threshold);
+        params.set_suppress_blank(decoding.suppress_blank);
+        if decoding.max_segment_length > 0 {
+            // whisper.cpp needs token timestamps to split segments by length
+            params.set_token_timestamps(true);
+            params.set_split_on_word(true);
+            params.set_max_len(decoding.max_segment_length as i32);
+        }
+        params.set_language(Some(language));
+        params.set_translate(settings.whisper_task == WhisperTask::Translate);
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        if let Some(callback) = on_progress {
+            params.set_progress_callback_safe(callback);
+        }
+        // whisper.cpp polls this between decoding steps and stops when it returns true
+        if let Some(token) = cancel.clone() {
+            params.set_abort_callback_safe(move || token.is_cancelled());
+        }
+
+        log::info!(
+            "Running Whisper {:?} ({}, {:?}, {} threads) on {} samples...",
+            settings.whisper_task,
+            language,
+            decoding.strategy,
+            n_threads,
+            mono_samples.len()
+        );
+        // Run
+        let result = state.full(params, mono_samples);
+        if cancel.as_ref().is_some_and(|token| token.is_cancelled()) {
+            return Err(AppError::Cancelled);
+        }
+        result.map_err(|e| AppError::Transcription(format!("failed to run model: {}", e)))?;
+
+        log::info!("Whisper inference complete, extracting text...");
+
+        // Collect segments with timing and token probabilities
+        let eot = self.token_eot();
+        let num_segments = state.full_n_segments();
+        let mut segments = Vec::new();
+        for i in 0..num_segments {
+            if let Some(segment) = state.get_segment(i) {
+                if let Ok(s) = segment.to_str() {
+                    let mut tokens = Vec::new();
+                    for t in 0..segment.n_tokens() {
+                        if let Some(token) = segment.get_token(t) {
+                            // Skip special tokens (timestamps, language tags, end-of-text)
+                            if token.token_id() >= eot {
+                                continue;
+                            }
+                            if let Ok(token_text) = token.to_str_lossy() {
+                                tokens.push(TranscriptToken {
+                                    text: token_text.to_string(),
+                                    probability: token.token_probability(),
+                                });
+                            }
+                        }
+                    }
+
+                    segments.push(TranscriptSegment {
+                        // Whisper timestamps are in centiseconds
+                        start_ms: segment.start_timestamp().max(0) as u64 * 10,
+                        end_ms: segment.end_timestamp().max(0) as u64 * 10,
+                        text: s.trim().to_string(),
+                        confidence: segment_confidence(&tokens),
+                        no_speech_probability: segment.no_speech_probability(),
+                        tokens,
+                    });
+                }
             }
         }
+
+        let text = segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let confidence = transcript_confidence(&segments);
+
+        Ok(Transcript {
+            text,
+            language: Some(language.to_string()),
+            language_probability,
+            segments,
+            confidence,
+            duration_ms: Some(mono_samples.len() as u64 * 1000 / 16000),
+        })
     }
+}
 
-    Ok(text.trim().to_string())
+/// Compiles the user's correction dictionary, in order.
+fn compile_replacements(replacements: &[Replacement]) -> AppResult<Vec<(Regex, &str)>> {
+    replacements
+        .iter()
+        .map(|replacement| {
+            let re = replacement.compile().map_err(AppError::InvalidSettings)?;
+            Ok((re, replacement.replacement.as_str()))
+        })
+        .collect()
 }
 
-fn enrich(instruction: &str, content: &str, model: &str) -> Result<String, String> {
-    let prompt = format!(
-        "Instruction: {}\nOriginal Content: {}\n\nPlease rewrite the content following the instruction. Return ONLY the rewritten text, nothing else.",
-        instruction, content
+/// Applies the compiled correction dictionary to a segment's text and tokens.
+fn apply_replacements(segment: &mut TranscriptSegment, replacements: &[(Regex, &str)]) {
+    for (re, replacement) in replacements {
+        segment.text = re.replace_all(&segment.text, *replacement).into_owned();
+        replace_tokens(&mut segment.tokens, re, replacement);
+    }
+}
+
+/// Replaces matches in the text of `tokens`. The tokens a match spans are merged
+/// into one token with their lowest probability, so a corrected word keeps the
+/// confidence it was recognized with.
+fn replace_tokens(tokens: &mut Vec<TranscriptToken>, re: &Regex, replacement: &str) {
+    if tokens.is_empty() {
+        return;
+    }
+    let joined: String = tokens.iter().map(|t| t.text.as_str()).collect();
+    let mut ranges = Vec::with_capacity(tokens.len());
+    let mut offset = 0;
+    for token in tokens.iter() {
+        ranges.push(offset..offset + token.text.len());
+        offset += token.text.len();
+    }
+
+    // Token ranges touched by matches, with the byte range and replacement text of
+    // each match; a token with several matches is rewritten once
+    type Group = (usize, usize, Vec<(usize, usize, String)>);
+    let last_token = tokens.len() - 1;
+    let mut groups: Vec<Group> = Vec::new();
+    for caps in re.captures_iter(&joined) {
+        let Some(m) = caps.get(0) else { continue };
+        let mut text = String::new();
+        caps.expand(replacement, &mut text);
+        let first = ranges
+            .iter()
+            .position(|r| r.end > m.start())
+            .unwrap_or(last_token);
+        let last = ranges
+            .iter()
+            .position(|r| r.end >= m.end())
+            .unwrap_or(last_token)
+            .max(first);
+        match groups.last_mut() {
+            Some(group) if group.1 >= first => {
+                group.1 = group.1.max(last);
+                group.2.push((m.start(), m.end(), text));
+            }
+            _ => groups.push((first, last, vec![(m.start(), m.end(), text)])),
+        }
+    }
+
+    // Back to front, so the indices of earlier groups stay valid
+    for (first, last, matches) in groups.into_iter().rev() {
+        let mut text = String::new();
+        let mut cursor = ranges[first].start;
+        for (start, end, replaced) in matches {
+            text.push_str(&joined[cursor..start]);
+            text.push_str(&replaced);
+            cursor = end;
+        }
+        text.push_str(&joined[cursor..ranges[last].end]);
+        let probability = tokens[first..=last]
+            .iter()
+            .map(|t| t.probability)
+            .fold(1.0, f32::min);
+        let merged = (!text.is_empty()).then_some(TranscriptToken { text, probability });
+        tokens.splice(first..=last, merged);
+    }
+}
+
+/// Runs Whisper's language detection on the first 30 seconds of audio.
+fn detect_language(
+    state: &mut WhisperState,
+    mono_samples: &[f32],
+    n_threads: i32,
+) -> AppResult<(&'static str, Option<f32>)> {
+    state
+        .pcm_to_mel(mono_samples, n_threads as usize)
+        .map_err(|e| {
+            AppError::Transcription(format!("Failed to compute mel spectrogram: {}", e))
+        })?;
+    let (lang_id, probs) = state
+        .lang_detect(0, n_threads as usize)
+        .map_err(|e| AppError::Transcription(format!("Failed to detect language: {}", e)))?;
+
+    let language = whisper_rs::get_lang_str(lang_id).ok_or_else(|| {
+        AppError::Transcription(format!("Whisper returned unknown language id {}", lang_id))
+    })?;
+    let probability = probs.get(lang_id as usize).copied();
+    log::info!(
+        "Detected language: {} (p={:.2})",
+        language,
+        probability.unwrap_or_default()
     );
 
-    let client = Client::new();
-    let res = client
-        .post(OLLAMA_API_URL)
+    Ok((language, probability))
+}
+
+const DEFAULT_PROMPT: &str = "Instruction: {instruction}\nOriginal Content: {content}\n\nPlease rewrite the content following the instruction. Return ONLY the rewritten text, nothing else.";
+
+/// Sends the prompt to Ollama. The request is dropped as soon as `cancel` fires.
+pub async fn enrich(
+    instruction: &str,
+    content: &str,
+    settings: &Settings,
+    cancel: &CancellationToken,
+) -> AppResult<String> {
+    let model = settings.model.as_str();
+    let template = settings
+        .active_template()
+        .and_then(|t| t.prompt.as_deref())
+        .unwrap_or(DEFAULT_PROMPT);
+    let prompt = template
+        .replace("{instruction}", instruction)
+        .replace("{content}", content);
+
+    let url = ollama::endpoint(&settings.ollama_url, "/api/chat");
+    let client = reqwest::Client::new();
+    let request = client
+        .post(&url)
         .timeout(std::time::Duration::from_secs(120)) // 2 minute timeout
         .json(&json!({
             "model": model,
@@ -368,25 +1244,28 @@ fn enrich(instruction: &str, content: &str, model: &str) -> Result<String, Strin
                 }
             ]
         }))
-        .send()
-        .map_err(|e| {
-            format!(
-                "Failed to connect to Ollama at {}. Please ensure Ollama is running. Error: {}",
-                OLLAMA_API_URL, e
-            )
-        })?;
+        .send();
+    let res = tokio::select! {
+        _ = cancel.cancelled() => return Err(AppError::Cancelled),
+        res = request => res.map_err(|e| AppError::OllamaUnreachable(format!("{}: {}", url, e)))?,
+    };
 
+    // Ollama answers 404 when the requested model has not been pulled
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(AppError::ModelNotInstalled(model.to_string()));
+    }
     if !res.status().is_success() {
-        return Err(format!("Ollama error (status {}). Model '{}' may not be installed. Please run 'ollama pull {}' in your terminal.", res.status(), model, model));
+        return Err(AppError::Ollama(format!("status {}", res.status())));
     }
 
-    let body: Value = res
-        .json()
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+    let body: Value = tokio::select! {
+        _ = cancel.cancelled() => return Err(AppError::Cancelled),
+        body = res.json() => body.map_err(|e| AppError::Ollama(format!("Failed to parse Ollama response: {}", e)))?,
+    };
 
     let result = body["message"]["content"]
         .as_str()
-        .ok_or("Invalid response format from Ollama. Please check Ollama version and model compatibility.")?
+        .ok_or_else(|| AppError::Ollama("Response has no message content".to_string()))?
         .trim()
         .to_string();
 
@@ -439,3 +1318,303 @@ fn resample_linear(input: &[f32], input_rate: u32, target_rate: u32) -> Vec<f32>
     log::info!("Resampling complete: generated {} samples", output.len());
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockContext;
+    use crate::models::OutputSinkConfig;
+
+    fn write_wav(path: &Path, seconds: f32, amplitude: f32) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for i in 0..(seconds * 16000.0) as usize {
+            let t = i as f32 / 16000.0;
+            let sample = amplitude * (2.0 * std::f32::consts::PI * 220.0 * t).sin();
+            writer.write_sample((sample * 32767.0) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn truncated_recordings_report_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("content.wav");
+        write_wav(&path, 0.5, 0.2);
+        assert_eq!(read_wav_samples(&path).unwrap().len(), 8000);
+
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(file.metadata().unwrap().len() - 1).unwrap();
+        assert_eq!(read_wav_samples(&path).unwrap_err().code(), "audio_decode");
+    }
+
+    #[test]
+    fn check_recording_rejects_missing_short_and_silent_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = Settings::default();
+
+        let missing = dir.path().join("missing.wav");
+        assert_eq!(
+            check_recording(&missing, "content", &settings)
+                .unwrap_err()
+                .code(),
+            "recording_missing"
+        );
+
+        let short = dir.path().join("short.wav");
+        write_wav(&short, 0.2, 0.5);
+        assert_eq!(
+            check_recording(&short, "content", &settings)
+                .unwrap_err()
+                .code(),
+            "recording_too_short"
+        );
+
+        let silent = dir.path().join("silent.wav");
+        write_wav(&silent, 2.0, 0.0);
+        assert_eq!(
+            check_recording(&silent, "content", &settings)
+                .unwrap_err()
+                .code(),
+            "recording_silent"
+        );
+
+        let tone = dir.path().join("tone.wav");
+        write_wav(&tone, 2.0, 0.3);
+        assert!(check_recording(&tone, "content", &settings).is_ok());
+    }
+
+    #[test]
+    fn hallucination_phrases_are_recognized() {
+        assert!(is_hallucination_phrase("Thank you."));
+        assert!(is_hallucination_phrase("  "));
+        assert!(!is_hallucination_phrase("Thank you for the report."));
+    }
+
+    #[test]
+    fn only_weak_hallucination_phrases_are_dropped() {
+        let settings = Settings::default();
+        let segment = |text: &str, confidence: f32, no_speech_probability: f32| TranscriptSegment {
+            text: text.to_string(),
+            confidence,
+            no_speech_probability,
+            ..Default::default()
+        };
+
+        // Clearly spoken
+        assert!(!is_hallucination(
+            &segment("Thank you.", 0.9, 0.1),
+            &settings
+        ));
+        assert!(!is_hallucination(&segment("Bye.", 0.8, 0.0), &settings));
+        // Heard in silence, or barely recognized
+        assert!(is_hallucination(
+            &segment("Thank you.", 0.9, 0.8),
+            &settings
+        ));
+        assert!(is_hallucination(&segment("Bye.", 0.2, 0.1), &settings));
+        // Other text is kept however unsure Whisper was
+        assert!(!is_hallucination(&segment("Ship it.", 0.2, 0.9), &settings));
+        assert!(is_hallucination(&segment(" ...", 0.9, 0.0), &settings));
+    }
+
+    #[test]
+    fn transcripts_keep_confident_short_phrases() {
+        let confident = TokenTranscriber(tokens(&[(" Thank", 0.95), (" you", 0.9), (".", 0.9)]));
+        let transcript =
+            transcribe_samples(&confident, &[], &Settings::default(), None, None).unwrap();
+        assert_eq!(transcript.text, "Thank you.");
+
+        let weak = TokenTranscriber(tokens(&[(" Thank", 0.3), (" you", 0.4), (".", 0.5)]));
+        let transcript = transcribe_samples(&weak, &[], &Settings::default(), None, None).unwrap();
+        assert!(transcript.text.is_empty());
+    }
+
+    #[test]
+    fn replacements_are_applied_in_order() {
+        let replacements = vec![
+            Replacement {
+                pattern: "open ai".to_string(),
+                replacement: "OpenAI".to_string(),
+                regex: false,
+            },
+            Replacement {
+                pattern: r"\bteh\b".to_string(),
+                replacement: "the".to_string(),
+                regex: true,
+            },
+        ];
+        let mut segment = TranscriptSegment {
+            text: "Open AI made teh model".to_string(),
+            ..Default::default()
+        };
+        apply_replacements(&mut segment, &compile_replacements(&replacements).unwrap());
+        assert_eq!(segment.text, "OpenAI made the model");
+    }
+
+    fn tokens(tokens: &[(&str, f32)]) -> Vec<TranscriptToken> {
+        tokens
+            .iter()
+            .map(|&(text, probability)| TranscriptToken {
+                text: text.to_string(),
+                probability,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replacements_merge_the_tokens_they_span() {
+        let replacements = vec![
+            Replacement {
+                pattern: "open ai".to_string(),
+                replacement: "OpenAI".to_string(),
+                regex: false,
+            },
+            Replacement {
+                pattern: r"(\d)x".to_string(),
+                replacement: "${1}×".to_string(),
+                regex: true,
+            },
+            Replacement {
+                pattern: " um".to_string(),
+                replacement: String::new(),
+                regex: false,
+            },
+        ];
+        let mut segment = TranscriptSegment {
+            text: "Open AI um made 2x2x gains".to_string(),
+            tokens: tokens(&[
+                (" Open", 0.9),
+                (" A", 0.4),
+                ("I", 0.8),
+                (" um", 0.7),
+                (" made", 0.9),
+                (" 2x2x", 0.6),
+                (" gains", 0.95),
+            ]),
+            ..Default::default()
+        };
+
+        apply_replacements(&mut segment, &compile_replacements(&replacements).unwrap());
+
+        assert_eq!(segment.text, "OpenAI made 2×2× gains");
+        let texts: Vec<_> = segment.tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, [" OpenAI", " made", " 2×2×", " gains"]);
+        assert_eq!(segment.tokens[0].probability, 0.4);
+        assert_eq!(segment.tokens[2].probability, 0.6);
+    }
+
+    /// Returns one segment with fixed tokens
+    struct TokenTranscriber(Vec<TranscriptToken>);
+
+    impl Transcriber for TokenTranscriber {
+        fn transcribe(
+            &self,
+            _samples: &[f32],
+            _settings: &Settings,
+            _on_progress: Option<ProgressCallback>,
+            _cancel: Option<CancellationToken>,
+        ) -> AppResult<Transcript> {
+            let text: String = self.0.iter().map(|t| t.text.as_str()).collect();
+            Ok(Transcript {
+                text: text.trim().to_string(),
+                segments: vec![TranscriptSegment {
+                    text: text.trim().to_string(),
+                    tokens: self.0.clone(),
+                    confidence: segment_confidence(&self.0),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn low_confidence_words_are_flagged_in_the_corrected_text() {
+        let transcriber = TokenTranscriber(tokens(&[
+            (" Ask", 0.9),
+            (" open", 0.3),
+            (" ai", 0.9),
+            (" now", 0.2),
+        ]));
+        let settings = Settings {
+            replacements: vec![Replacement {
+                pattern: "open ai".to_string(),
+                replacement: "OpenAI".to_string(),
+                regex: false,
+            }],
+            ..Settings::default()
+        };
+
+        let transcript = transcribe_samples(&transcriber, &[], &settings, None, None).unwrap();
+        let confidence = TranscriptConfidence::new(&transcript, 0.5);
+
+        assert_eq!(confidence.text, "Ask OpenAI now");
+        let flagged: Vec<_> = confidence
+            .low_confidence_words
+            .iter()
+            .map(|w| (w.word.as_str(), w.probability))
+            .collect();
+        assert_eq!(flagged, [("OpenAI", 0.3), ("now", 0.2)]);
+        // Scored on the recognized tokens
+        assert!((transcript.confidence.unwrap() - 0.575).abs() < 1e-6);
+    }
+
+    #[test]
+    fn invalid_replacements_are_rejected() {
+        let replacements = vec![Replacement {
+            pattern: "(".to_string(),
+            replacement: String::new(),
+            regex: true,
+        }];
+        assert_eq!(
+            compile_replacements(&replacements).unwrap_err().code(),
+            "invalid_settings"
+        );
+    }
+
+    #[test]
+    fn failed_outputs_are_reported_but_do_not_fail_the_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = MockContext::new(dir.path());
+        // A directory cannot be opened for appending
+        let settings = Settings {
+            outputs: vec![
+                OutputSinkConfig::File {
+                    path: dir.path().to_path_buf(),
+                    format: Default::default(),
+                },
+                OutputSinkConfig::Clipboard,
+            ],
+            ..Settings::default()
+        };
+        let output = Output {
+            session_id: "session".to_string(),
+            timestamp: chrono::Local::now(),
+            instruction: String::new(),
+            original_content: "hello".to_string(),
+            text: "Hello.".to_string(),
+        };
+
+        let delivered = tauri::async_runtime::block_on(deliver_result(
+            &ctx,
+            output,
+            &settings,
+            &CancellationToken::new(),
+        ))
+        .unwrap();
+
+        assert_eq!(delivered, ["clipboard"]);
+        assert_eq!(ctx.clipboard().as_deref(), Some("Hello."));
+        let failures = ctx.payloads("output-failed");
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0]["sink"], "file");
+        assert_eq!(failures[0]["code"], "output");
+        assert_eq!(failures[0]["session_id"], "session");
+    }
+}
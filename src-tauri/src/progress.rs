@@ -0,0 +1,140 @@
+use crate::context::AppContext;
+use crate::models::{PipelineStage, StageTiming};
+use serde::Serialize;
+use std::time::Instant;
+
+/// Callback receiving Whisper's progress in percent
+pub type ProgressCallback = Box<dyn FnMut(i32) + Send>;
+
+/// Payload of the `pipeline-progress` event
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineProgress {
+    pub session_id: String,
+    pub stage: PipelineStage,
+    /// Progress within the stage, for stages that report it (transcription)
+    pub percent: Option<u8>,
+    pub stage_elapsed_ms: u64,
+    pub total_elapsed_ms: u64,
+}
+
+/// Records how long each stage of a run took.
+pub struct StageTimer {
+    started: Instant,
+    current: (PipelineStage, Instant),
+    timings: Vec<StageTiming>,
+}
+
+impl StageTimer {
+    pub fn new(first: PipelineStage) -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            current: (first, now),
+            timings: Vec::new(),
+        }
+    }
+
+    /// Ends the current stage and starts `stage`.
+    pub fn stage(&mut self, stage: PipelineStage) {
+        self.end_stage();
+        self.current = (stage, Instant::now());
+    }
+
+    fn end_stage(&mut self) {
+        let (stage, started) = self.current;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        log::info!("Stage {:?} took {} ms", stage, duration_ms);
+        self.timings.push(StageTiming { stage, duration_ms });
+    }
+
+    /// Ends the last stage and logs the total run time.
+    pub fn finish(mut self) -> Vec<StageTiming> {
+        self.end_stage();
+        log::info!(
+            "Pipeline took {} ms",
+            self.started.elapsed().as_millis() as u64
+        );
+        self.timings
+    }
+}
+
+/// Emits `pipeline-progress` events for one pipeline run and records how long
+/// each stage took.
+pub struct ProgressTracker<C: AppContext + Clone + 'static> {
+    ctx: C,
+    session_id: String,
+    timer: StageTimer,
+}
+
+impl<C: AppContext + Clone + 'static> ProgressTracker<C> {
+    pub fn new(ctx: &C, session_id: &str, first: PipelineStage) -> Self {
+        let timer = StageTimer::new(first);
+        emit_progress(ctx, session_id, first, None, timer.started, timer.started);
+        Self {
+            ctx: ctx.clone(),
+            session_id: session_id.to_string(),
+            timer,
+        }
+    }
+
+    /// Ends the current stage and starts `stage`.
+    pub fn stage(&mut self, stage: PipelineStage) {
+        self.timer.stage(stage);
+        let (_, now) = self.timer.current;
+        emit_progress(
+            &self.ctx,
+            &self.session_id,
+            stage,
+            None,
+            now,
+            self.timer.started,
+        );
+    }
+
+    /// Reports Whisper's progress as percentage of the current stage.
+    pub fn percent_callback(&self) -> ProgressCallback {
+        let ctx = self.ctx.clone();
+        let session_id = self.session_id.clone();
+        let (stage, stage_started) = self.timer.current;
+        let started = self.timer.started;
+        let mut last = -1;
+        Box::new(move |percent: i32| {
+            if percent != last {
+                last = percent;
+                emit_progress(
+                    &ctx,
+                    &session_id,
+                    stage,
+                    Some(percent.clamp(0, 100) as u8),
+                    stage_started,
+                    started,
+                );
+            }
+        })
+    }
+
+    /// Ends the last stage and logs the total run time.
+    pub fn finish(self) -> Vec<StageTiming> {
+        self.timer.finish()
+    }
+}
+
+fn emit_progress(
+    ctx: &impl AppContext,
+    session_id: &str,
+    stage: PipelineStage,
+    percent: Option<u8>,
+    stage_started: Instant,
+    started: Instant,
+) {
+    ctx.emit_event(
+        "pipeline-progress",
+        PipelineProgress {
+            session_id: session_id.to_string(),
+            stage,
+            percent,
+            stage_elapsed_ms: stage_started.elapsed().as_millis() as u64,
+            total_elapsed_ms: started.elapsed().as_millis() as u64,
+        },
+    );
+}
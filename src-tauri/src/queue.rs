@@ -0,0 +1,290 @@
+use crate::audio::Interruption;
+use crate::context::AppContext;
+use crate::error::AppResult;
+use crate::logic::{emit_status, handle_event};
+use crate::models::ImportMode;
+use crate::pipeline::{run_session, PipelineState};
+use crate::state_machine::{AppStatus, RecordingEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+const SESSIONS_DIR: &str = "sessions";
+// Written when a session is queued; sessions without it were never completed
+const SESSION_FILENAME: &str = "session.json";
+pub const INSTRUCTION_FILENAME: &str = "instruction.wav";
+pub const CONTENT_FILENAME: &str = "content.wav";
+
+/// A completed recording, or an imported audio file, waiting to be processed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedSession {
+    pub id: String,
+    /// Empty for imported files
+    pub instruction_path: PathBuf,
+    pub content_path: PathBuf,
+    pub queued_at: String,
+    /// Audio lost while recording because the input device failed
+    #[serde(default)]
+    pub gaps: Vec<RecordingGap>,
+    /// Set when the session is an imported file rather than a recording
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import: Option<ImportRequest>,
+}
+
+/// How an imported audio file is processed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRequest {
+    pub mode: ImportMode,
+    #[serde(default)]
+    pub instruction: Option<String>,
+}
+
+/// Part of a recording that is missing because the input device failed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingGap {
+    /// The recording the gap is in
+    pub recording: AppStatus,
+    #[serde(flatten)]
+    pub interruption: Interruption,
+}
+
+/// Completed recordings, processed one at a time in the order they were recorded
+pub struct SessionQueue {
+    pending: Mutex<VecDeque<QueuedSession>>,
+    /// Session the worker is processing
+    active: Mutex<Option<String>>,
+    notify: Notify,
+}
+
+impl Default for SessionQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionQueue {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(VecDeque::new()),
+            active: Mutex::new(None),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Number of sessions waiting or being processed
+    pub fn len(&self) -> usize {
+        self.pending.lock().unwrap().len() + self.active.lock().unwrap().is_some() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn active(&self) -> Option<String> {
+        self.active.lock().unwrap().clone()
+    }
+
+    pub fn pending(&self) -> Vec<QueuedSession> {
+        self.pending.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Directory holding the recordings of one session
+pub fn session_dir(ctx: &impl AppContext, session_id: &str) -> PathBuf {
+    ctx.data_dir().join(SESSIONS_DIR).join(session_id)
+}
+
+/// Queues a session whose recordings are complete.
+pub fn enqueue<C: AppContext>(
+    ctx: &C,
+    queue: &SessionQueue,
+    session_id: &str,
+    gaps: Vec<RecordingGap>,
+) -> AppResult<()> {
+    let dir = session_dir(ctx, session_id);
+    let session = QueuedSession {
+        id: session_id.to_string(),
+        instruction_path: dir.join(INSTRUCTION_FILENAME),
+        content_path: dir.join(CONTENT_FILENAME),
+        queued_at: chrono::Local::now().to_rfc3339(),
+        gaps,
+        import: None,
+    };
+    // Lets the session survive a restart before it was processed
+    fs::write(
+        dir.join(SESSION_FILENAME),
+        serde_json::to_string_pretty(&session)?,
+    )?;
+
+    log::info!("Queued session {}", session.id);
+    push(ctx, queue, session);
+    Ok(())
+}
+
+/// Queues an audio file to be transcribed (and optionally enriched) after the
+/// sessions ahead of it. Imports are not kept across restarts. Returns the
+/// session id.
+pub fn enqueue_import<C: AppContext>(
+    ctx: &C,
+    queue: &SessionQueue,
+    path: PathBuf,
+    mode: ImportMode,
+    instruction: Option<String>,
+) -> String {
+    let session = QueuedSession {
+        id: uuid::Uuid::new_v4().to_string(),
+        instruction_path: PathBuf::new(),
+        content_path: path,
+        queued_at: chrono::Local::now().to_rfc3339(),
+        gaps: Vec::new(),
+        import: Some(ImportRequest { mode, instruction }),
+    };
+    let session_id = session.id.clone();
+
+    log::info!(
+        "Queued import of {:?} as session {}",
+        session.content_path,
+        session_id
+    );
+    push(ctx, queue, session);
+    session_id
+}
+
+/// Adds a session and wakes the worker. Callers emit the new status afterwards.
+fn push<C: AppContext>(ctx: &C, queue: &SessionQueue, session: QueuedSession) {
+    ctx.emit_event("session-queued", &session);
+    queue.pending.lock().unwrap().push_back(session);
+    queue.notify.notify_one();
+}
+
+/// Cancels a waiting or running session (or import).
+pub fn cancel_session(app: &AppHandle, session_id: &str) {
+    let removed = {
+        let queue = app.state::<SessionQueue>();
+        let mut pending = queue.pending.lock().unwrap();
+        pending
+            .iter()
+            .position(|s| s.id == session_id)
+            .and_then(|index| pending.remove(index))
+    };
+
+    match removed {
+        Some(session) => discard(app, &session),
+        None => {
+            if !app.state::<PipelineState>().cancel(session_id) {
+                log::warn!("Pipeline session {} is not running", session_id);
+            }
+        }
+    }
+}
+
+/// Cancels every waiting and running session.
+pub fn cancel_all(app: &AppHandle) {
+    let removed: Vec<_> = app
+        .state::<SessionQueue>()
+        .pending
+        .lock()
+        .unwrap()
+        .drain(..)
+        .collect();
+    for session in &removed {
+        discard(app, session);
+    }
+    app.state::<PipelineState>().cancel_all();
+}
+
+/// Drops a session that never started processing.
+fn discard(app: &AppHandle, session: &QueuedSession) {
+    log::info!("Session {} removed from the queue", session.id);
+    if session.import.is_none() {
+        remove_session_dir(app, &session.id);
+    }
+    let _ = app.emit("pipeline-cancelled", &session.id);
+    emit_status(app);
+}
+
+/// Deletes the recordings of a session.
+pub fn remove_session_dir(ctx: &impl AppContext, session_id: &str) {
+    let dir = session_dir(ctx, session_id);
+    if let Err(e) = fs::remove_dir_all(&dir) {
+        log::warn!("Failed to remove session directory {:?}: {}", dir, e);
+    }
+}
+
+/// Starts the worker that processes queued sessions in order.
+pub fn start_worker(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let session = next_session(&app).await;
+            emit_status(&app);
+
+            let event = match run_session(&app, &session).await {
+                Ok(()) => RecordingEvent::PipelineDone,
+                Err(_) => RecordingEvent::PipelineFailed,
+            };
+            handle_event(&app, event);
+
+            // Recordings are only kept until the session is processed
+            if session.import.is_none() {
+                remove_session_dir(&app, &session.id);
+            }
+            *app.state::<SessionQueue>().active.lock().unwrap() = None;
+            emit_status(&app);
+        }
+    });
+}
+
+/// Waits for the next session and marks it as active.
+async fn next_session(app: &AppHandle) -> QueuedSession {
+    let queue = app.state::<SessionQueue>();
+    loop {
+        {
+            let mut pending = queue.pending.lock().unwrap();
+            if let Some(session) = pending.pop_front() {
+                *queue.active.lock().unwrap() = Some(session.id.clone());
+                return session;
+            }
+        }
+        queue.notify.notified().await;
+    }
+}
+
+/// Called on startup: requeues sessions that were waiting when the app closed
+/// and removes recordings that were never completed.
+pub fn restore(app: &AppHandle) {
+    let root = app.data_dir().join(SESSIONS_DIR);
+    let Ok(entries) = fs::read_dir(&root) else {
+        return;
+    };
+
+    let mut sessions = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let session = fs::read_to_string(path.join(SESSION_FILENAME))
+            .ok()
+            .and_then(|json| serde_json::from_str::<QueuedSession>(&json).ok());
+        match session {
+            Some(session) => sessions.push(session),
+            None => {
+                log::info!("Removing incomplete session {:?}", path);
+                let _ = fs::remove_dir_all(&path);
+            }
+        }
+    }
+
+    sessions.sort_by(|a, b| a.queued_at.cmp(&b.queued_at));
+    let queue = app.state::<SessionQueue>();
+    for session in sessions {
+        log::info!("Requeueing session {} from previous run", session.id);
+        push(app, &queue, session);
+    }
+    emit_status(app);
+}
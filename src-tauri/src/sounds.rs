@@ -0,0 +1,282 @@
+//! Audio cues. A cue plays the sound its theme assigns to it, or the sound the
+//! user picked for it, on the configured output device.
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Cue, CueSound, SoundSettings, SoundTheme, Tone};
+use cpal::traits::{DeviceTrait, HostTrait};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+/// Sounds compiled into the app, by name
+const BUNDLED: &[(&str, &[u8])] = &[
+    ("chime-start", include_bytes!("../sounds/chime-start.wav")),
+    (
+        "chime-advance",
+        include_bytes!("../sounds/chime-advance.wav"),
+    ),
+    (
+        "chime-processing",
+        include_bytes!("../sounds/chime-processing.wav"),
+    ),
+    (
+        "chime-success",
+        include_bytes!("../sounds/chime-success.wav"),
+    ),
+    ("chime-error", include_bytes!("../sounds/chime-error.wav")),
+    (
+        "chime-timeout",
+        include_bytes!("../sounds/chime-timeout.wav"),
+    ),
+];
+
+// Tones play below full scale, as loud as the bundled sounds
+const TONE_AMPLITUDE: f32 = 0.2;
+
+type Sound = Box<dyn Source<Item = f32> + Send>;
+
+pub fn is_bundled(name: &str) -> bool {
+    BUNDLED.iter().any(|(bundled, _)| *bundled == name)
+}
+
+pub fn list_output_devices() -> Vec<String> {
+    let host = cpal::default_host();
+    match host.output_devices() {
+        Ok(devices) => devices.filter_map(|d| d.name().ok()).collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// The sound `cue` plays with `settings`
+pub fn cue_sound(settings: &SoundSettings, cue: Cue) -> CueSound {
+    settings
+        .cues
+        .get(&cue)
+        .cloned()
+        .unwrap_or_else(|| theme_sound(settings.theme, cue))
+}
+
+/// The sound `theme` assigns to `cue`
+pub fn theme_sound(theme: SoundTheme, cue: Cue) -> CueSound {
+    match theme {
+        SoundTheme::Tones => tones(match cue {
+            Cue::Start => &[(880.0, 200)], // A5
+            Cue::Advance => &[(1200.0, 50)],
+            Cue::Processing => &[(400.0, 300)],
+            // C major arpeggio
+            Cue::Success => &[(523.25, 150), (659.25, 150), (783.99, 300)],
+            Cue::Error => &[(300.0, 150), (200.0, 300)],
+            Cue::Timeout => &[(988.0, 120), (740.0, 120), (988.0, 120)],
+        }),
+        SoundTheme::Chimes => CueSound::Bundled {
+            name: format!("chime-{}", cue.as_str()),
+        },
+    }
+}
+
+fn tones(tones: &[(f32, u32)]) -> CueSound {
+    CueSound::Tones {
+        tones: tones
+            .iter()
+            .map(|&(frequency_hz, duration_ms)| Tone {
+                frequency_hz,
+                duration_ms,
+            })
+            .collect(),
+    }
+}
+
+/// Plays `cue` without blocking. A cue that cannot be played is logged and
+/// otherwise ignored.
+pub fn play_cue(settings: SoundSettings, cue: Cue) {
+    if let Err(e) = spawn_playback(settings, cue) {
+        log::warn!(
+            "Failed to play the {} cue: {} {:?}",
+            cue.as_str(),
+            e,
+            e.details()
+        );
+    }
+}
+
+/// Plays `cue` and returns once it is playing, or with the reason it cannot be
+/// played.
+pub fn test_cue(settings: SoundSettings, cue: Cue) -> AppResult<()> {
+    match spawn_playback(settings, cue)? {
+        Some(started) => started.recv().unwrap_or_else(|_| {
+            Err(AppError::Internal(
+                "The playback thread exited unexpectedly".to_string(),
+            ))
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Loads the sound of `cue` and plays it on a new thread. Returns `None` if
+/// nothing is to be played, otherwise whether the output device was opened.
+fn spawn_playback(settings: SoundSettings, cue: Cue) -> AppResult<Option<Receiver<AppResult<()>>>> {
+    if settings.muted {
+        return Ok(None);
+    }
+    let sounds = load_sound(&cue_sound(&settings, cue))?;
+    if sounds.is_empty() {
+        return Ok(None);
+    }
+
+    let (started_tx, started_rx) = std::sync::mpsc::sync_channel(1);
+    // Output streams cannot be moved between threads
+    std::thread::spawn(move || {
+        let playing =
+            open_output(settings.output_device.as_deref()).and_then(|(stream, handle)| {
+                let sink = Sink::try_new(&handle).map_err(|e| {
+                    AppError::AudioDevice(format!("Failed to play on the output device: {}", e))
+                })?;
+                sink.set_volume(settings.volume);
+                for sound in sounds {
+                    sink.append(sound);
+                }
+                Ok((stream, sink))
+            });
+        match playing {
+            Ok((_stream, sink)) => {
+                let _ = started_tx.send(Ok(()));
+                sink.sleep_until_end();
+            }
+            Err(e) => {
+                log::warn!(
+                    "Failed to play the {} cue: {} {:?}",
+                    cue.as_str(),
+                    e,
+                    e.details()
+                );
+                let _ = started_tx.send(Err(e));
+            }
+        }
+    });
+    Ok(Some(started_rx))
+}
+
+/// Sources to play one after another; empty for `Silent`
+fn load_sound(sound: &CueSound) -> AppResult<Vec<Sound>> {
+    Ok(match sound {
+        CueSound::Tones { tones } => tones
+            .iter()
+            .map(|tone| {
+                Box::new(
+                    rodio::source::SineWave::new(tone.frequency_hz)
+                        .take_duration(Duration::from_millis(tone.duration_ms as u64))
+                        .amplify(TONE_AMPLITUDE),
+                ) as Sound
+            })
+            .collect(),
+        CueSound::Bundled { name } => {
+            let (_, data) = BUNDLED
+                .iter()
+                .find(|(bundled, _)| *bundled == name.as_str())
+                .ok_or_else(|| AppError::NotFound(format!("Bundled sound '{}'", name)))?;
+            let decoder = Decoder::new(Cursor::new(*data))
+                .map_err(|e| AppError::AudioDecode(format!("{}: {}", name, e)))?;
+            vec![Box::new(decoder.convert_samples::<f32>()) as Sound]
+        }
+        CueSound::File { path } => {
+            let file = File::open(path).map_err(|e| {
+                AppError::Storage(format!(
+                    "Failed to open sound file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            let decoder = Decoder::new(BufReader::new(file))
+                .map_err(|e| AppError::AudioDecode(format!("{}: {}", path.display(), e)))?;
+            vec![Box::new(decoder.convert_samples::<f32>()) as Sound]
+        }
+        CueSound::Silent => Vec::new(),
+    })
+}
+
+/// Opens the output device named `device_name`, or the default device if it is
+/// not set or no longer connected.
+fn open_output(device_name: Option<&str>) -> AppResult<(OutputStream, OutputStreamHandle)> {
+    let device = device_name.and_then(|name| {
+        let host = cpal::default_host();
+        let device = host
+            .output_devices()
+            .ok()?
+            .find(|d| d.name().is_ok_and(|n| n == name));
+        if device.is_none() {
+            log::warn!("Output device '{}' not found, using the default", name);
+        }
+        device
+    });
+    let opened = match device {
+        Some(device) => OutputStream::try_from_device(&device),
+        None => OutputStream::try_default(),
+    };
+    opened.map_err(|e| AppError::AudioDevice(format!("Failed to open the output device: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn every_theme_has_a_sound_for_every_cue() {
+        for theme in [SoundTheme::Tones, SoundTheme::Chimes] {
+            for cue in Cue::ALL {
+                let sounds = load_sound(&theme_sound(theme, cue)).unwrap();
+                assert!(!sounds.is_empty(), "{:?} {:?}", theme, cue);
+                let samples: usize = sounds.into_iter().map(Iterator::count).sum();
+                assert!(samples > 0, "{:?} {:?}", theme, cue);
+            }
+        }
+    }
+
+    #[test]
+    fn cue_overrides_replace_the_theme() {
+        let settings = SoundSettings {
+            theme: SoundTheme::Chimes,
+            cues: BTreeMap::from([(Cue::Error, CueSound::Silent)]),
+            ..SoundSettings::default()
+        };
+
+        assert_eq!(cue_sound(&settings, Cue::Error), CueSound::Silent);
+        assert_eq!(
+            cue_sound(&settings, Cue::Start),
+            CueSound::Bundled {
+                name: "chime-start".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn unplayable_cues_report_an_error() {
+        let missing = SoundSettings {
+            cues: BTreeMap::from([(
+                Cue::Success,
+                CueSound::File {
+                    path: "/nonexistent/success.wav".into(),
+                },
+            )]),
+            ..SoundSettings::default()
+        };
+        assert_eq!(
+            test_cue(missing.clone(), Cue::Success).unwrap_err().code(),
+            "storage"
+        );
+
+        // Muted and silent cues never open a device
+        let muted = SoundSettings {
+            muted: true,
+            ..missing
+        };
+        assert!(test_cue(muted, Cue::Success).is_ok());
+        let silent = SoundSettings {
+            cues: BTreeMap::from([(Cue::Error, CueSound::Silent)]),
+            ..SoundSettings::default()
+        };
+        assert!(test_cue(silent, Cue::Error).is_ok());
+    }
+}
@@ -0,0 +1,450 @@
+//! Recording state machine. Every change of the recording state is looked up in
+//! `TRANSITIONS`; `logic` performs the effect of the transition that was taken.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use AppStatus::{Content, Idle, Instruction};
+use RecordingEvent::{
+    Cancel, Pause, PipelineDone, PipelineFailed, RecordingFailed, Resume, Timeout, Trigger,
+};
+
+/// Recording state. Processing runs independently in the session queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppStatus {
+    /// "processing" was persisted by older versions and means nothing is recording
+    #[default]
+    #[serde(alias = "processing")]
+    Idle,
+    Instruction,
+    Content,
+}
+
+impl AppStatus {
+    pub const ALL: [AppStatus; 3] = [AppStatus::Idle, AppStatus::Instruction, AppStatus::Content];
+}
+
+/// Inputs of the state machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingEvent {
+    /// Hotkey, tray or UI button
+    Trigger,
+    /// The user abandoned the current recording
+    Cancel,
+    /// Pause hotkey or UI button while recording
+    Pause,
+    Resume,
+    /// The recording reached the configured maximum duration
+    Timeout,
+    /// The input device could not be opened or stopped delivering audio
+    RecordingFailed,
+    /// The queue finished processing a session
+    PipelineDone,
+    /// The queue gave up on a session
+    PipelineFailed,
+}
+
+impl RecordingEvent {
+    pub const ALL: [RecordingEvent; 8] = [
+        RecordingEvent::Trigger,
+        RecordingEvent::Cancel,
+        RecordingEvent::Pause,
+        RecordingEvent::Resume,
+        RecordingEvent::Timeout,
+        RecordingEvent::RecordingFailed,
+        RecordingEvent::PipelineDone,
+        RecordingEvent::PipelineFailed,
+    ];
+}
+
+/// Condition a transition requires besides its source state and event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Guard {
+    /// A session was started for the current recording
+    HasSession,
+    /// The session's recording captured audio before the device failed
+    CapturedAudio,
+    /// Settings keep a recording that reaches the timeout
+    AdvanceOnTimeout,
+}
+
+/// Facts the guards are checked against
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GuardContext {
+    pub has_session: bool,
+    /// The current recording was cut off after recording audio
+    pub captured_audio: bool,
+    /// `advance_on_timeout` is enabled in the settings
+    pub advance_on_timeout: bool,
+}
+
+impl Guard {
+    fn check(self, ctx: &GuardContext) -> bool {
+        match self {
+            Guard::HasSession => ctx.has_session,
+            Guard::CapturedAudio => ctx.has_session && ctx.captured_audio,
+            Guard::AdvanceOnTimeout => ctx.has_session && ctx.advance_on_timeout,
+        }
+    }
+}
+
+/// What has to happen when a transition is taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    None,
+    /// Create a session and record the instruction
+    StartSession,
+    /// Stop the instruction and record the content
+    RecordContent,
+    /// Stop the content and queue the session for processing
+    QueueSession,
+    /// Stop recording and delete the session's recordings
+    DiscardSession,
+    /// Keep the recording open but stop capturing audio
+    PauseRecording,
+    ResumeRecording,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Transition {
+    pub from: AppStatus,
+    pub event: RecordingEvent,
+    pub guard: Option<Guard>,
+    pub to: AppStatus,
+    pub effect: Effect,
+}
+
+const fn on(
+    from: AppStatus,
+    event: RecordingEvent,
+    guard: Option<Guard>,
+    to: AppStatus,
+    effect: Effect,
+) -> Transition {
+    Transition {
+        from,
+        event,
+        guard,
+        to,
+        effect,
+    }
+}
+
+/// Every allowed transition. For a state and event the first entry whose guard
+/// holds is taken; events without an entry are rejected.
+pub const TRANSITIONS: &[Transition] = &[
+    on(Idle, Trigger, None, Instruction, Effect::StartSession),
+    on(Idle, PipelineDone, None, Idle, Effect::None),
+    on(Idle, PipelineFailed, None, Idle, Effect::None),
+    on(
+        Instruction,
+        Trigger,
+        Some(Guard::HasSession),
+        Content,
+        Effect::RecordContent,
+    ),
+    on(Instruction, Trigger, None, Idle, Effect::DiscardSession),
+    on(Instruction, Cancel, None, Idle, Effect::DiscardSession),
+    on(
+        Instruction,
+        Pause,
+        None,
+        Instruction,
+        Effect::PauseRecording,
+    ),
+    on(
+        Instruction,
+        Resume,
+        None,
+        Instruction,
+        Effect::ResumeRecording,
+    ),
+    on(
+        Instruction,
+        Timeout,
+        Some(Guard::AdvanceOnTimeout),
+        Content,
+        Effect::RecordContent,
+    ),
+    on(Instruction, Timeout, None, Idle, Effect::DiscardSession),
+    on(
+        Instruction,
+        RecordingFailed,
+        None,
+        Idle,
+        Effect::DiscardSession,
+    ),
+    on(Instruction, PipelineDone, None, Instruction, Effect::None),
+    on(Instruction, PipelineFailed, None, Instruction, Effect::None),
+    on(
+        Content,
+        Trigger,
+        Some(Guard::HasSession),
+        Idle,
+        Effect::QueueSession,
+    ),
+    on(Content, Trigger, None, Idle, Effect::DiscardSession),
+    on(Content, Cancel, None, Idle, Effect::DiscardSession),
+    on(Content, Pause, None, Content, Effect::PauseRecording),
+    on(Content, Resume, None, Content, Effect::ResumeRecording),
+    on(
+        Content,
+        Timeout,
+        Some(Guard::AdvanceOnTimeout),
+        Idle,
+        Effect::QueueSession,
+    ),
+    on(Content, Timeout, None, Idle, Effect::DiscardSession),
+    // Content cut off by a failing device is still processed
+    on(
+        Content,
+        RecordingFailed,
+        Some(Guard::CapturedAudio),
+        Idle,
+        Effect::QueueSession,
+    ),
+    on(Content, RecordingFailed, None, Idle, Effect::DiscardSession),
+    on(Content, PipelineDone, None, Content, Effect::None),
+    on(Content, PipelineFailed, None, Content, Effect::None),
+];
+
+/// Looks up the transition for `event` in state `from`.
+pub fn transition(
+    from: AppStatus,
+    event: RecordingEvent,
+    guards: &GuardContext,
+) -> Option<&'static Transition> {
+    TRANSITIONS.iter().find(|t| {
+        t.from == from && t.event == event && t.guard.map_or(true, |guard| guard.check(guards))
+    })
+}
+
+// Transitions kept for `get_state_machine`
+const HISTORY_LEN: usize = 50;
+
+/// A transition that was taken
+#[derive(Debug, Clone, Serialize)]
+pub struct TransitionRecord {
+    pub timestamp: String,
+    pub from: AppStatus,
+    pub event: RecordingEvent,
+    pub to: AppStatus,
+}
+
+/// Result of the `get_state_machine` debug command
+#[derive(Debug, Clone, Serialize)]
+pub struct StateMachineSnapshot {
+    pub state: AppStatus,
+    pub transitions: &'static [Transition],
+    /// Most recent transitions, oldest first
+    pub history: Vec<TransitionRecord>,
+}
+
+#[derive(Debug, Default)]
+pub struct StateMachine {
+    state: AppStatus,
+    history: VecDeque<TransitionRecord>,
+}
+
+impl StateMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> AppStatus {
+        self.state
+    }
+
+    /// Moves to the next state, or returns `None` if `event` is not allowed in
+    /// the current state.
+    pub fn apply(&mut self, event: RecordingEvent, guards: &GuardContext) -> Option<Transition> {
+        let transition = *transition(self.state, event, guards)?;
+        self.state = transition.to;
+
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(TransitionRecord {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            from: transition.from,
+            event,
+            to: transition.to,
+        });
+        Some(transition)
+    }
+
+    pub fn snapshot(&self) -> StateMachineSnapshot {
+        StateMachineSnapshot {
+            state: self.state,
+            transitions: TRANSITIONS,
+            history: self.history.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WITH_SESSION: GuardContext = GuardContext {
+        has_session: true,
+        captured_audio: false,
+        advance_on_timeout: false,
+    };
+    const WITHOUT_SESSION: GuardContext = GuardContext {
+        has_session: false,
+        captured_audio: false,
+        advance_on_timeout: false,
+    };
+
+    /// Every combination of the guard facts
+    fn all_guards() -> impl Iterator<Item = GuardContext> {
+        (0..8).map(|bits| GuardContext {
+            has_session: bits & 1 != 0,
+            captured_audio: bits & 2 != 0,
+            advance_on_timeout: bits & 4 != 0,
+        })
+    }
+
+    /// The expected outcome of every state, event and guard combination
+    fn expected(
+        from: AppStatus,
+        event: RecordingEvent,
+        guards: GuardContext,
+    ) -> Option<(AppStatus, Effect)> {
+        let has_session = guards.has_session;
+        match (from, event) {
+            (Idle, Trigger) => Some((Instruction, Effect::StartSession)),
+            (Idle, Cancel | Pause | Resume | Timeout | RecordingFailed) => None,
+            (Instruction | Content, Pause) => Some((from, Effect::PauseRecording)),
+            (Instruction | Content, Resume) => Some((from, Effect::ResumeRecording)),
+            (Instruction, Trigger) if has_session => Some((Content, Effect::RecordContent)),
+            (Content, Trigger) if has_session => Some((Idle, Effect::QueueSession)),
+            (Content, RecordingFailed) if has_session && guards.captured_audio => {
+                Some((Idle, Effect::QueueSession))
+            }
+            (Instruction, Timeout) if has_session && guards.advance_on_timeout => {
+                Some((Content, Effect::RecordContent))
+            }
+            (Content, Timeout) if has_session && guards.advance_on_timeout => {
+                Some((Idle, Effect::QueueSession))
+            }
+            (Instruction | Content, Trigger | Cancel | Timeout | RecordingFailed) => {
+                Some((Idle, Effect::DiscardSession))
+            }
+            (state, PipelineDone | PipelineFailed) => Some((state, Effect::None)),
+        }
+    }
+
+    #[test]
+    fn every_combination_matches_the_expected_outcome() {
+        for from in AppStatus::ALL {
+            for event in RecordingEvent::ALL {
+                for guards in all_guards() {
+                    let actual = transition(from, event, &guards).map(|t| (t.to, t.effect));
+                    assert_eq!(
+                        actual,
+                        expected(from, event, guards),
+                        "{:?} + {:?} ({:?})",
+                        from,
+                        event,
+                        guards
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn table_has_no_unreachable_entries() {
+        for (i, t) in TRANSITIONS.iter().enumerate() {
+            // An unguarded entry shadows every later entry for the same state and event
+            let shadowed = TRANSITIONS[..i].iter().any(|earlier| {
+                earlier.from == t.from && earlier.event == t.event && earlier.guard.is_none()
+            });
+            assert!(!shadowed, "unreachable transition {:?}", t);
+        }
+    }
+
+    #[test]
+    fn guarded_transitions_have_a_fallback() {
+        for t in TRANSITIONS.iter().filter(|t| t.guard.is_some()) {
+            assert!(
+                TRANSITIONS.iter().any(|other| other.from == t.from
+                    && other.event == t.event
+                    && other.guard.is_none()),
+                "{:?} + {:?} has no fallback",
+                t.from,
+                t.event
+            );
+        }
+    }
+
+    #[test]
+    fn only_idle_starts_a_session() {
+        for t in TRANSITIONS {
+            assert_eq!(
+                t.effect == Effect::StartSession,
+                t.from == Idle && t.event == Trigger
+            );
+        }
+    }
+
+    #[test]
+    fn machine_follows_the_table_and_keeps_a_history() {
+        let mut machine = StateMachine::new();
+        assert!(machine.apply(Cancel, &WITH_SESSION).is_none());
+        assert_eq!(machine.state(), Idle);
+
+        machine.apply(Trigger, &WITHOUT_SESSION).unwrap();
+        machine.apply(Trigger, &WITH_SESSION).unwrap();
+        let last = machine.apply(Timeout, &WITH_SESSION).unwrap();
+        assert_eq!(last.effect, Effect::DiscardSession);
+        assert_eq!(machine.state(), Idle);
+
+        let snapshot = machine.snapshot();
+        let events: Vec<_> = snapshot.history.iter().map(|r| r.event).collect();
+        assert_eq!(events, [Trigger, Trigger, Timeout]);
+        assert_eq!(snapshot.history[1].from, Instruction);
+        assert_eq!(snapshot.history[1].to, Content);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut machine = StateMachine::new();
+        for _ in 0..HISTORY_LEN + 10 {
+            machine.apply(PipelineDone, &WITHOUT_SESSION).unwrap();
+        }
+        assert_eq!(machine.snapshot().history.len(), HISTORY_LEN);
+    }
+
+    #[test]
+    fn states_and_events_serialize_as_snake_case() {
+        assert_eq!(serde_json::to_value(Instruction).unwrap(), "instruction");
+        assert_eq!(
+            serde_json::to_value(RecordingFailed).unwrap(),
+            "recording_failed"
+        );
+        assert_eq!(
+            serde_json::from_str::<RecordingEvent>("\"pipeline_done\"").unwrap(),
+            PipelineDone
+        );
+
+        let snapshot = serde_json::to_value(StateMachine::new().snapshot()).unwrap();
+        assert_eq!(snapshot["state"], "idle");
+        assert_eq!(snapshot["transitions"][0]["effect"], "start_session");
+        assert_eq!(snapshot["transitions"][3]["guard"], "has_session");
+    }
+
+    #[test]
+    fn legacy_processing_status_reads_as_idle() {
+        assert_eq!(
+            serde_json::from_str::<AppStatus>("\"processing\"").unwrap(),
+            Idle
+        );
+        assert_eq!(serde_json::to_value(Idle).unwrap(), "idle");
+    }
+}
@@ -1,52 +1,131 @@
-use crate::models::AppStateData;
+use crate::context::AppContext;
+use crate::error::AppResult;
+use crate::models::{AppStateData, BatchQueueData, HistoryItem};
+use crate::state_machine::AppStatus;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager, Runtime}; // Assuming models.rs is visible
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
 const DATA_FILENAME: &str = "app_data.json";
+const BATCH_FILENAME: &str = "batch_queue.json";
 
-fn get_data_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
-    app.path()
-        .app_data_dir()
-        .expect("failed to get app data dir")
-        .join(DATA_FILENAME)
+/// Serializes access to the store files. The session queue, the batch worker and
+/// commands all change them; without the lock, overlapping load-modify-save
+/// cycles lose each other's changes.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock() -> MutexGuard<'static, ()> {
+    // The lock guards no data, so a panic while it was held leaves nothing inconsistent
+    STORE_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
 }
 
-pub fn save_data<R: Runtime>(app: &AppHandle<R>, data: &AppStateData) -> Result<(), String> {
-    let path = get_data_path(app);
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+fn get_data_path(ctx: &impl AppContext) -> PathBuf {
+    ctx.data_dir().join(DATA_FILENAME)
+}
+
+fn get_batch_path(ctx: &impl AppContext) -> PathBuf {
+    ctx.data_dir().join(BATCH_FILENAME)
+}
+
+pub fn save_data(ctx: &impl AppContext, data: &AppStateData) -> AppResult<()> {
+    let _guard = lock();
+    write_json(&get_data_path(ctx), data)
+}
+
+pub fn load_data(ctx: &impl AppContext) -> AppResult<AppStateData> {
+    let _guard = lock();
+    read_data(&get_data_path(ctx))
+}
+
+/// Applies `f` to the stored data and saves the result. Every change to the
+/// store goes through here, so concurrent changes do not overwrite each other.
+/// `f` must not call other store functions.
+pub fn update<C: AppContext, T>(ctx: &C, f: impl FnOnce(&mut AppStateData) -> T) -> AppResult<T> {
+    let _guard = lock();
+    let path = get_data_path(ctx);
+    let mut data = read_data(&path)?;
+    let result = f(&mut data);
+    write_json(&path, &data)?;
+    Ok(result)
+}
+
+pub fn get_status(ctx: &impl AppContext) -> AppResult<AppStatus> {
+    let data = load_data(ctx)?;
+    Ok(data.status)
+}
+
+pub fn set_status(ctx: &impl AppContext, status: AppStatus) -> AppResult<()> {
+    update(ctx, |data| data.status = status)
+}
+
+pub fn append_history_item(ctx: &impl AppContext, item: HistoryItem) -> AppResult<()> {
+    update(ctx, |data| data.history.push(item))
+}
+
+pub fn load_batch_queue(ctx: &impl AppContext) -> AppResult<BatchQueueData> {
+    let _guard = lock();
+    let path = get_batch_path(ctx);
+    if !path.exists() {
+        return Ok(BatchQueueData::default());
     }
-    let json = serde_json::to_string_pretty(data).map_err(|e| e.to_string())?;
-    fs::write(path, json).map_err(|e| e.to_string())?;
-    Ok(())
+    read_or_back_up(&path)
 }
 
-pub fn load_data<R: Runtime>(app: &AppHandle<R>) -> Result<AppStateData, String> {
-    let path = get_data_path(app);
+pub fn save_batch_queue(ctx: &impl AppContext, queue: &BatchQueueData) -> AppResult<()> {
+    let _guard = lock();
+    write_json(&get_batch_path(ctx), queue)
+}
+
+/// Reads the app data, writing the defaults on first start. Needs the store lock.
+fn read_data(path: &Path) -> AppResult<AppStateData> {
     if !path.exists() {
         let default_data = AppStateData::default();
-        // Ensure status is set to idle on first run
-        save_data(app, &default_data)?;
+        write_json(path, &default_data)?;
         return Ok(default_data);
     }
-    let file = fs::File::open(path).map_err(|e| e.to_string())?;
-    let mut data: AppStateData = serde_json::from_reader(file).unwrap_or_default();
-    // Ensure status is always set
-    if data.status.is_empty() {
-        data.status = "idle".to_string();
-    }
-    Ok(data)
+    read_or_back_up(path)
 }
 
-pub fn get_status<R: Runtime>(app: &AppHandle<R>) -> Result<String, String> {
-    let data = load_data(app)?;
-    Ok(data.status)
+/// Parses the file at `path`. A file that cannot be parsed is renamed to
+/// `<name>.corrupt-<timestamp>` and the defaults are returned, so the next save
+/// does not overwrite data that may still be recovered by hand. Needs the store
+/// lock, so a file that is being written is never taken for a corrupt one.
+fn read_or_back_up<T: DeserializeOwned + Default>(path: &Path) -> AppResult<T> {
+    let file = fs::File::open(path)?;
+    match serde_json::from_reader(BufReader::new(file)) {
+        Ok(data) => Ok(data),
+        Err(e) => {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let backup = path.with_file_name(format!(
+                "{}.corrupt-{}",
+                file_name,
+                chrono::Local::now().format("%Y%m%d-%H%M%S")
+            ));
+            log::error!(
+                "Failed to read {}: {}. Moving it to {} and using defaults",
+                path.display(),
+                e,
+                backup.display()
+            );
+            fs::rename(path, &backup)?;
+            Ok(T::default())
+        }
+    }
 }
 
-pub fn set_status<R: Runtime>(app: &AppHandle<R>, status: &str) -> Result<(), String> {
-    let mut data = load_data(app)?;
-    data.status = status.to_string();
-    save_data(app, &data)?;
+/// Writes `value` to a temporary file next to `path` and renames it over `path`,
+/// so readers see either the old or the new file, never a partial one.
+fn write_json<T: Serialize>(path: &Path, value: &T) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(value)?;
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!("{}.tmp", file_name));
+    fs::write(&temp, json)?;
+    fs::rename(&temp, path)?;
     Ok(())
 }
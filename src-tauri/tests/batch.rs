@@ -0,0 +1,229 @@
+mod common;
+
+use app_lib::batch::{self, BatchState};
+use app_lib::error::AppError;
+use app_lib::mock::{MockContext, MockTranscriber};
+use app_lib::models::{BatchJobStatus, ImportMode, PipelineStage};
+use app_lib::store;
+use common::{int_spec, write_tone};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// A context whose data directory holds `count` audio files in `audio/`
+fn setup(count: usize) -> (TempDir, MockContext, BatchState) {
+    let dir = tempfile::tempdir().unwrap();
+    let audio = dir.path().join("audio");
+    std::fs::create_dir(&audio).unwrap();
+    for i in 1..=count {
+        write_tone(
+            &audio.join(format!("{}.wav", i)),
+            int_spec(16_000, 1, 16),
+            1.0,
+            0.5,
+        );
+    }
+    let ctx = MockContext::new(dir.path());
+    (dir, ctx, BatchState::new())
+}
+
+fn enqueue(dir: &TempDir, ctx: &MockContext, state: &BatchState) -> usize {
+    batch::enqueue_directory(
+        ctx,
+        state,
+        &dir.path().join("audio"),
+        ImportMode::Transcribe,
+        None,
+        false,
+    )
+    .unwrap()
+}
+
+fn statuses(ctx: &MockContext) -> Vec<BatchJobStatus> {
+    store::load_batch_queue(ctx)
+        .unwrap()
+        .jobs
+        .iter()
+        .map(|j| j.status)
+        .collect()
+}
+
+#[test]
+fn queued_jobs_are_persisted() {
+    let (dir, ctx, state) = setup(3);
+
+    assert_eq!(enqueue(&dir, &ctx, &state), 3);
+
+    let queue = store::load_batch_queue(&ctx).unwrap();
+    let names: Vec<_> = queue.jobs.iter().map(|j| j.file_name.as_str()).collect();
+    assert_eq!(names, ["1.wav", "2.wav", "3.wav"]);
+    assert_eq!(statuses(&ctx), [BatchJobStatus::Pending; 3]);
+    assert!(!queue.paused);
+    assert!(!ctx.payloads("batch-queue-changed").is_empty());
+}
+
+#[test]
+fn worker_transcribes_pending_jobs_in_order() {
+    let (dir, ctx, state) = setup(3);
+    enqueue(&dir, &ctx, &state);
+    let second = store::load_batch_queue(&ctx).unwrap().jobs[1].id.clone();
+    batch::cancel_job(&ctx, &state, &second).unwrap();
+    ctx.set_transcriber(Arc::new(MockTranscriber::new(["First.", "Third."])));
+
+    batch::run_worker(&ctx, &state);
+
+    assert_eq!(
+        statuses(&ctx),
+        [
+            BatchJobStatus::Completed,
+            BatchJobStatus::Cancelled,
+            BatchJobStatus::Completed
+        ]
+    );
+    let history = store::load_data(&ctx).unwrap().history;
+    let texts: Vec<_> = history
+        .iter()
+        .map(|h| h.original_content.as_str())
+        .collect();
+    assert_eq!(texts, ["First.", "Third."]);
+    assert_eq!(history[1].source_file.as_deref(), Some("3.wav"));
+    let queue = store::load_batch_queue(&ctx).unwrap();
+    assert_eq!(
+        queue.jobs[2].history_id.as_deref(),
+        Some(history[1].id.as_str())
+    );
+}
+
+#[test]
+fn transcription_progress_is_reported_per_job() {
+    let (dir, ctx, state) = setup(1);
+    enqueue(&dir, &ctx, &state);
+    ctx.set_transcriber(Arc::new(MockTranscriber::new(["Done."])));
+
+    batch::run_worker(&ctx, &state);
+
+    let payloads = ctx.payloads("batch-job-progress");
+    let progress: Vec<_> = payloads
+        .iter()
+        .map(|p| {
+            (
+                p["stage"].as_str().unwrap(),
+                p["progress"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        progress,
+        [
+            ("transcribe", 10),
+            ("transcribe", 90),
+            ("save", 90),
+            ("done", 100)
+        ]
+    );
+}
+
+#[test]
+fn silent_files_and_empty_transcripts_fail_without_history() {
+    let (dir, ctx, state) = setup(2);
+    write_tone(
+        &dir.path().join("audio").join("3.wav"),
+        int_spec(16_000, 1, 16),
+        1.0,
+        0.0,
+    );
+    enqueue(&dir, &ctx, &state);
+    let transcriber = Arc::new(MockTranscriber::new(["Spoken.", ""]));
+    ctx.set_transcriber(transcriber.clone());
+
+    batch::run_worker(&ctx, &state);
+
+    assert_eq!(
+        statuses(&ctx),
+        [
+            BatchJobStatus::Completed,
+            BatchJobStatus::Failed,
+            BatchJobStatus::Failed
+        ]
+    );
+    // The silent file never reaches Whisper
+    assert_eq!(transcriber.inputs().len(), 2);
+    let queue = store::load_batch_queue(&ctx).unwrap();
+    assert!(queue.jobs[1].history_id.is_none());
+    assert!(queue.jobs[2].error.is_some());
+
+    let history = store::load_data(&ctx).unwrap().history;
+    assert_eq!(history.len(), 1);
+    let stages: Vec<_> = history[0].stage_timings.iter().map(|t| t.stage).collect();
+    assert_eq!(
+        stages,
+        [PipelineStage::TranscribeContent, PipelineStage::Save]
+    );
+}
+
+#[test]
+fn paused_queue_starts_no_jobs_until_resumed() {
+    let (dir, ctx, state) = setup(2);
+    enqueue(&dir, &ctx, &state);
+
+    batch::pause(&ctx, &state).unwrap();
+    assert!(store::load_batch_queue(&ctx).unwrap().paused);
+    assert!(batch::next_job(&ctx, &state).unwrap().is_none());
+
+    batch::resume(&ctx, &state).unwrap();
+    let (job, _) = batch::next_job(&ctx, &state).unwrap().unwrap();
+    assert_eq!(job.file_name, "1.wav");
+    assert_eq!(
+        statuses(&ctx),
+        [BatchJobStatus::Running, BatchJobStatus::Pending]
+    );
+}
+
+#[test]
+fn pausing_stops_and_requeues_the_running_job() {
+    let (dir, ctx, state) = setup(2);
+    enqueue(&dir, &ctx, &state);
+    let (job, cancel) = batch::next_job(&ctx, &state).unwrap().unwrap();
+
+    batch::pause(&ctx, &state).unwrap();
+    assert!(cancel.is_cancelled());
+    batch::finish_job(&ctx, &state, &job, Err(AppError::Cancelled));
+
+    assert_eq!(statuses(&ctx), [BatchJobStatus::Pending; 2]);
+    batch::resume(&ctx, &state).unwrap();
+    let (resumed, cancel) = batch::next_job(&ctx, &state).unwrap().unwrap();
+    assert_eq!(resumed.id, job.id);
+    assert!(!cancel.is_cancelled());
+}
+
+#[test]
+fn cancelling_the_running_job_stops_it() {
+    let (dir, ctx, state) = setup(2);
+    enqueue(&dir, &ctx, &state);
+    let (job, cancel) = batch::next_job(&ctx, &state).unwrap().unwrap();
+
+    // Other jobs do not affect the running one
+    let other = store::load_batch_queue(&ctx).unwrap().jobs[1].id.clone();
+    batch::cancel_job(&ctx, &state, &other).unwrap();
+    assert!(!cancel.is_cancelled());
+
+    batch::cancel_job(&ctx, &state, &job.id).unwrap();
+    assert!(cancel.is_cancelled());
+    batch::finish_job(&ctx, &state, &job, Err(AppError::Cancelled));
+
+    assert_eq!(statuses(&ctx), [BatchJobStatus::Cancelled; 2]);
+    assert!(batch::next_job(&ctx, &state).unwrap().is_none());
+}
+
+#[test]
+fn cancel_all_stops_everything_and_clear_removes_it() {
+    let (dir, ctx, state) = setup(3);
+    enqueue(&dir, &ctx, &state);
+    let (_, cancel) = batch::next_job(&ctx, &state).unwrap().unwrap();
+
+    batch::cancel_all(&ctx, &state).unwrap();
+    assert!(cancel.is_cancelled());
+    assert_eq!(statuses(&ctx), [BatchJobStatus::Cancelled; 3]);
+
+    batch::clear_finished(&ctx, &state).unwrap();
+    assert!(batch::get_jobs(&ctx, &state).unwrap().jobs.is_empty());
+}
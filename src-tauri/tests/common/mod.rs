@@ -0,0 +1,254 @@
+//! Helpers shared by the integration tests: a harness around the recording state
+//! machine, fixture audio and a stub Ollama server.
+#![allow(dead_code)]
+
+use app_lib::audio::{Interruption, RecordingHandlers};
+use app_lib::logic::{dispatch, recording_interrupted, recording_timed_out, LogicState};
+use app_lib::mock::{MockContext, MockRecorder};
+use app_lib::queue::SessionQueue;
+use app_lib::state_machine::{AppStatus, RecordingEvent, Transition};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/// The recording state machine wired to in-memory stand-ins
+pub struct Harness {
+    pub dir: TempDir,
+    pub ctx: MockContext,
+    pub logic: LogicState,
+    pub recorder: MockRecorder,
+    pub queue: SessionQueue,
+    pub timeouts: Arc<AtomicUsize>,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = MockContext::new(dir.path());
+        Self {
+            dir,
+            ctx,
+            logic: LogicState::new(),
+            recorder: MockRecorder::new(),
+            queue: SessionQueue::new(),
+            timeouts: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn trigger(&self) -> Option<Transition> {
+        self.send(RecordingEvent::Trigger)
+    }
+
+    pub fn send(&self, event: RecordingEvent) -> Option<Transition> {
+        dispatch(
+            &self.ctx,
+            &self.logic,
+            &self.recorder,
+            &self.queue,
+            &self.handlers(),
+            event,
+        )
+    }
+
+    /// Reports a device failure the way the recording thread does.
+    pub fn interrupt(&self, interruption: Interruption) {
+        recording_interrupted(
+            &self.ctx,
+            &self.logic,
+            &self.recorder,
+            &self.queue,
+            &self.handlers(),
+            interruption,
+        );
+    }
+
+    /// Times out recording `recording_id` the way its timeout handler does.
+    pub fn time_out(&self, recording_id: u64) -> Option<Transition> {
+        recording_timed_out(
+            &self.ctx,
+            &self.logic,
+            &self.recorder,
+            &self.queue,
+            &self.handlers(),
+            recording_id,
+        )
+    }
+
+    /// Handlers that count timeouts; interruptions are reported with `interrupt`.
+    fn handlers(&self) -> impl Fn() -> RecordingHandlers {
+        let timeouts = self.timeouts.clone();
+        move || RecordingHandlers {
+            on_timeout: {
+                let timeouts = timeouts.clone();
+                Box::new(move || {
+                    timeouts.fetch_add(1, Ordering::SeqCst);
+                })
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn status(&self) -> AppStatus {
+        self.logic.status()
+    }
+}
+
+/// Writes a 440 Hz tone with the given peak amplitude to every channel.
+pub fn write_tone(path: &Path, spec: hound::WavSpec, seconds: f32, amplitude: f32) {
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    let frames = (seconds * spec.sample_rate as f32) as usize;
+    let max = ((1i64 << (spec.bits_per_sample - 1)) - 1) as f32;
+    for i in 0..frames {
+        let t = i as f32 / spec.sample_rate as f32;
+        let sample = amplitude * (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+        for _ in 0..spec.channels {
+            match spec.sample_format {
+                hound::SampleFormat::Int => writer.write_sample((sample * max) as i32).unwrap(),
+                hound::SampleFormat::Float => writer.write_sample(sample).unwrap(),
+            }
+        }
+    }
+    writer.finalize().unwrap();
+}
+
+pub fn int_spec(sample_rate: u32, channels: u16, bits_per_sample: u16) -> hound::WavSpec {
+    hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample,
+        sample_format: hound::SampleFormat::Int,
+    }
+}
+
+pub fn float_spec(sample_rate: u32, channels: u16) -> hound::WavSpec {
+    hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    }
+}
+
+/// A request received by the stub server
+#[derive(Debug, Clone)]
+pub struct StubRequest {
+    pub method: String,
+    pub path: String,
+    pub body: Value,
+}
+
+/// Answers `/api/tags` with the installed models, `/api/pull` with success and
+/// `/api/chat` with a fixed reply, or 404 for models that are not installed, like
+/// Ollama does.
+pub struct OllamaStub {
+    url: String,
+    requests: Arc<Mutex<Vec<StubRequest>>>,
+}
+
+impl OllamaStub {
+    pub fn start(models: &[&str], reply: &str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let models: Vec<String> = models.iter().map(|m| m.to_string()).collect();
+        let reply = reply.to_string();
+        let received = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                handle(stream, &models, &reply, &received);
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<StubRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn handle(
+    mut stream: TcpStream,
+    models: &[String],
+    reply: &str,
+    received: &Mutex<Vec<StubRequest>>,
+) -> Option<()> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    let (status, response) = match (method.as_str(), path.as_str()) {
+        ("GET", "/api/tags") => (
+            "200 OK",
+            json!({ "models": models.iter().map(|m| json!({ "name": m, "model": m })).collect::<Vec<_>>() }),
+        ),
+        ("POST", "/api/pull") => ("200 OK", json!({ "status": "success" })),
+        ("POST", "/api/chat") => {
+            let model = body["model"].as_str().unwrap_or_default();
+            if models.iter().any(|m| m == model) {
+                (
+                    "200 OK",
+                    json!({
+                        "model": model,
+                        "message": { "role": "assistant", "content": format!("{}\n", reply) },
+                        "done": true
+                    }),
+                )
+            } else {
+                (
+                    "404 Not Found",
+                    json!({ "error": format!("model '{}' not found", model) }),
+                )
+            }
+        }
+        _ => ("404 Not Found", json!({ "error": "not found" })),
+    };
+
+    // Recorded before answering, so the client never sees a response first
+    received
+        .lock()
+        .unwrap()
+        .push(StubRequest { method, path, body });
+
+    let response = response.to_string();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        response.len(),
+        response
+    );
+    Some(())
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use app_lib::pipeline::decode_audio_file;
+use common::{float_spec, int_spec, write_tone};
+
+fn peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0, |max, s| max.max(s.abs()))
+}
+
+fn assert_one_second_tone(samples: &[f32]) {
+    // Resampling may add or drop a sample at the end
+    assert!(
+        (15_998..=16_002).contains(&samples.len()),
+        "{} samples",
+        samples.len()
+    );
+    let peak = peak(samples);
+    assert!((0.45..=0.55).contains(&peak), "peak {}", peak);
+}
+
+#[test]
+fn common_sample_rates_and_channel_counts_decode_to_16khz_mono() {
+    let dir = tempfile::tempdir().unwrap();
+    for sample_rate in [8_000, 16_000, 22_050, 44_100, 48_000] {
+        for channels in [1, 2] {
+            let path = dir
+                .path()
+                .join(format!("tone-{}-{}.wav", sample_rate, channels));
+            write_tone(&path, int_spec(sample_rate, channels, 16), 1.0, 0.5);
+
+            let samples = decode_audio_file(&path).unwrap();
+            assert_one_second_tone(&samples);
+        }
+    }
+}
+
+#[test]
+fn bit_depths_are_scaled_to_the_same_level() {
+    let dir = tempfile::tempdir().unwrap();
+    let specs = [
+        ("8", int_spec(44_100, 2, 8)),
+        ("24", int_spec(44_100, 2, 24)),
+        ("32", int_spec(44_100, 2, 32)),
+        ("float", float_spec(44_100, 2)),
+    ];
+    for (name, spec) in specs {
+        let path = dir.path().join(format!("tone-{}.wav", name));
+        write_tone(&path, spec, 1.0, 0.5);
+
+        let samples = decode_audio_file(&path).unwrap();
+        assert_one_second_tone(&samples);
+    }
+}
+
+#[test]
+fn multichannel_recordings_use_the_first_channel() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("surround.wav");
+    write_tone(&path, int_spec(48_000, 6, 16), 1.0, 0.5);
+
+    let samples = decode_audio_file(&path).unwrap();
+    assert_one_second_tone(&samples);
+}
+
+#[test]
+fn missing_and_invalid_files_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = decode_audio_file(&dir.path().join("missing.wav")).unwrap_err();
+    assert_eq!(missing.code(), "not_found");
+
+    let invalid = dir.path().join("invalid.wav");
+    std::fs::write(&invalid, b"not a wav file").unwrap();
+    assert_eq!(
+        decode_audio_file(&invalid).unwrap_err().code(),
+        "audio_decode"
+    );
+}
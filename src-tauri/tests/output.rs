@@ -0,0 +1,172 @@
+use app_lib::mock::MockContext;
+use app_lib::models::{FileOutputFormat, OutputSinkConfig, PromptTemplate, Settings};
+use app_lib::output::{build_sinks, deliver_all, Output};
+use chrono::{Local, TimeZone};
+use std::fs;
+
+fn output(text: &str) -> Output {
+    Output {
+        session_id: "session".to_string(),
+        timestamp: Local.with_ymd_and_hms(2026, 3, 14, 9, 30, 0).unwrap(),
+        instruction: "Make it formal".to_string(),
+        original_content: "hey".to_string(),
+        text: text.to_string(),
+    }
+}
+
+fn settings(outputs: Vec<OutputSinkConfig>) -> Settings {
+    Settings {
+        outputs,
+        ..Settings::default()
+    }
+}
+
+#[test]
+fn clipboard_is_the_default_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+
+    let sinks = build_sinks(&ctx, &Settings::default());
+    let failures = deliver_all(&sinks, &output("Hello"));
+
+    assert!(failures.is_empty());
+    assert_eq!(ctx.clipboard().as_deref(), Some("Hello"));
+}
+
+#[test]
+fn markdown_file_collects_results() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+    let path = dir.path().join("notes").join("results.md");
+    let sinks = build_sinks(
+        &ctx,
+        &settings(vec![OutputSinkConfig::File {
+            path: path.clone(),
+            format: FileOutputFormat::Markdown,
+        }]),
+    );
+
+    assert!(deliver_all(&sinks, &output("First")).is_empty());
+    assert!(deliver_all(&sinks, &output("Second")).is_empty());
+
+    let text = fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        text,
+        "## 2026-03-14 09:30\n\n> Make it formal\n\nFirst\n\n\n\
+         ## 2026-03-14 09:30\n\n> Make it formal\n\nSecond\n\n"
+    );
+    // Nothing was copied
+    assert_eq!(ctx.clipboard(), None);
+}
+
+#[test]
+fn daily_notes_use_one_file_per_day() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+    let sinks = build_sinks(
+        &ctx,
+        &settings(vec![OutputSinkConfig::File {
+            path: dir.path().to_path_buf(),
+            format: FileOutputFormat::DailyNotes,
+        }]),
+    );
+
+    assert!(deliver_all(&sinks, &output("Hello")).is_empty());
+
+    let text = fs::read_to_string(dir.path().join("2026-03-14.md")).unwrap();
+    assert!(text.starts_with("## 09:30\n"));
+}
+
+#[cfg(unix)]
+#[test]
+fn command_receives_the_text_on_stdin() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+    let target = dir.path().join("piped.txt");
+    let sinks = build_sinks(
+        &ctx,
+        &settings(vec![OutputSinkConfig::Command {
+            command: format!("cat > '{}'", target.display()),
+        }]),
+    );
+
+    assert!(deliver_all(&sinks, &output("Hello")).is_empty());
+    assert_eq!(fs::read_to_string(target).unwrap(), "Hello");
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_output_does_not_stop_the_others() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+    let sinks = build_sinks(
+        &ctx,
+        &settings(vec![
+            OutputSinkConfig::Command {
+                command: "echo broken >&2; exit 3".to_string(),
+            },
+            OutputSinkConfig::Clipboard,
+        ]),
+    );
+
+    let failures = deliver_all(&sinks, &output("Hello"));
+
+    assert_eq!(failures.len(), 1);
+    let (sink, error) = &failures[0];
+    assert_eq!(*sink, "command");
+    assert_eq!(error.code(), "output");
+    assert!(error.details().unwrap().contains("broken"));
+    assert_eq!(ctx.clipboard().as_deref(), Some("Hello"));
+}
+
+#[test]
+fn active_template_overrides_the_outputs() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+    let path = dir.path().join("template.md");
+    let settings = Settings {
+        templates: vec![PromptTemplate {
+            id: "notes".to_string(),
+            name: "Notes".to_string(),
+            prompt: None,
+            vocabulary: None,
+            replacements: None,
+            outputs: Some(vec![OutputSinkConfig::File {
+                path: path.clone(),
+                format: FileOutputFormat::Markdown,
+            }]),
+        }],
+        active_template: Some("notes".to_string()),
+        ..Settings::default()
+    };
+
+    let sinks = build_sinks(&ctx, &settings);
+    assert_eq!(sinks.len(), 1);
+    assert!(deliver_all(&sinks, &output("Hello")).is_empty());
+    assert!(path.exists());
+    assert_eq!(ctx.clipboard(), None);
+}
+
+#[test]
+fn output_settings_are_validated() {
+    let relative = settings(vec![OutputSinkConfig::File {
+        path: "notes.md".into(),
+        format: FileOutputFormat::Markdown,
+    }]);
+    assert!(relative.validate().is_err());
+
+    let ftp = settings(vec![OutputSinkConfig::Webhook {
+        url: "ftp://localhost/hook".to_string(),
+    }]);
+    assert!(ftp.validate().is_err());
+
+    let empty = settings(vec![OutputSinkConfig::Command {
+        command: "  ".to_string(),
+    }]);
+    assert!(empty.validate().is_err());
+
+    let valid = settings(vec![OutputSinkConfig::Webhook {
+        url: "http://localhost:5678/webhook".to_string(),
+    }]);
+    assert!(valid.validate().is_ok());
+}
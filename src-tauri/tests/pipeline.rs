@@ -0,0 +1,214 @@
+mod common;
+
+use app_lib::mock::MockTranscriber;
+use app_lib::models::{Cue, PipelineStage, Replacement, Settings};
+use app_lib::ollama::{installed_models, pull_missing_model};
+use app_lib::pipeline::{decode_audio_file, enrich, process_session, Transcriber};
+use app_lib::state_machine::AppStatus;
+use app_lib::store;
+use common::{int_spec, write_tone, Harness, OllamaStub};
+use std::sync::Arc;
+use tauri::async_runtime::block_on;
+use tokio_util::sync::CancellationToken;
+use whisper_rs::{WhisperContext, WhisperContextParameters};
+
+const REPLY: &str = "Dear team, could we move the meeting to Thursday?";
+
+fn use_ollama(h: &Harness, stub: &OllamaStub) {
+    let mut data = store::load_data(&h.ctx).unwrap();
+    data.settings.ollama_url = stub.url().to_string();
+    store::save_data(&h.ctx, &data).unwrap();
+}
+
+/// Runs the state machine through one recording, writing `instruction` and
+/// `content` as the recorded audio.
+fn record(h: &Harness, instruction: hound::WavSpec, content: hound::WavSpec, amplitude: f32) {
+    h.trigger();
+    write_tone(&h.recorder.started()[0], instruction, 1.0, amplitude);
+    h.trigger();
+    write_tone(&h.recorder.started()[1], content, 2.0, amplitude);
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Idle);
+}
+
+#[test]
+fn recorded_session_ends_up_in_history() {
+    let h = Harness::new();
+    let stub = OllamaStub::start(&["gemma3:4b"], REPLY);
+    use_ollama(&h, &stub);
+    let transcriber = Arc::new(MockTranscriber::new([
+        "Make it formal.",
+        "hey can we move the meeting to thursday",
+    ]));
+    h.ctx.set_transcriber(transcriber.clone());
+
+    record(&h, int_spec(44_100, 2, 16), int_spec(48_000, 1, 16), 0.5);
+    let session = h.queue.pending().remove(0);
+    block_on(process_session(&h.ctx, &session, &CancellationToken::new())).unwrap();
+
+    // Both recordings were resampled to 16kHz mono before transcription
+    let inputs = transcriber.inputs();
+    assert_eq!(inputs.len(), 2);
+    assert!((15_998..=16_002).contains(&inputs[0]), "{:?}", inputs);
+    assert!((31_998..=32_002).contains(&inputs[1]), "{:?}", inputs);
+
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/api/chat");
+    assert_eq!(requests[0].body["model"], "gemma3:4b");
+    let prompt = requests[0].body["messages"][0]["content"].as_str().unwrap();
+    assert!(prompt.contains("Make it formal."));
+    assert!(prompt.contains("hey can we move the meeting to thursday"));
+
+    let history = store::load_data(&h.ctx).unwrap().history;
+    assert_eq!(history.len(), 1);
+    let item = &history[0];
+    assert_eq!(item.instruction, "Make it formal.");
+    assert_eq!(
+        item.original_content,
+        "hey can we move the meeting to thursday"
+    );
+    assert_eq!(item.enriched_content, REPLY);
+    assert_eq!(item.language.as_deref(), Some("en"));
+
+    assert_eq!(h.ctx.clipboard().as_deref(), Some(REPLY));
+    assert_eq!(h.ctx.cues().last(), Some(&Cue::Success));
+    let complete = h.ctx.payloads("pipeline-complete");
+    assert_eq!(complete.len(), 1);
+    assert_eq!(complete[0]["session_id"], session.id.as_str());
+    assert_eq!(complete[0]["history_id"], item.id.as_str());
+    assert_eq!(complete[0]["outputs"], serde_json::json!(["clipboard"]));
+
+    // Every stage is timed, including saving the item itself
+    let stages: Vec<_> = item.stage_timings.iter().map(|t| t.stage).collect();
+    assert_eq!(
+        stages,
+        [
+            PipelineStage::ModelCheck,
+            PipelineStage::TranscribeInstruction,
+            PipelineStage::TranscribeContent,
+            PipelineStage::Enrich,
+            PipelineStage::Output,
+            PipelineStage::Save,
+        ]
+    );
+    assert_eq!(
+        complete[0]["stage_timings"],
+        serde_json::to_value(&item.stage_timings).unwrap()
+    );
+}
+
+#[test]
+fn silent_recording_fails_before_transcription() {
+    let h = Harness::new();
+    let stub = OllamaStub::start(&["gemma3:4b"], REPLY);
+    use_ollama(&h, &stub);
+    let transcriber = Arc::new(MockTranscriber::new(["unused", "unused"]));
+    h.ctx.set_transcriber(transcriber.clone());
+
+    record(&h, int_spec(16_000, 1, 16), int_spec(16_000, 1, 16), 0.0);
+    let session = h.queue.pending().remove(0);
+    let error = block_on(process_session(&h.ctx, &session, &CancellationToken::new())).unwrap_err();
+
+    assert_eq!(error.code(), "recording_silent");
+    assert!(transcriber.inputs().is_empty());
+    assert!(stub.requests().is_empty());
+    assert!(store::load_data(&h.ctx).unwrap().history.is_empty());
+}
+
+#[test]
+fn replacements_apply_to_transcriber_output() {
+    let h = Harness::new();
+    let stub = OllamaStub::start(&["gemma3:4b"], REPLY);
+    use_ollama(&h, &stub);
+    let mut data = store::load_data(&h.ctx).unwrap();
+    data.settings.replacements = vec![Replacement {
+        pattern: "thursday".to_string(),
+        replacement: "Thursday".to_string(),
+        regex: false,
+    }];
+    store::save_data(&h.ctx, &data).unwrap();
+    h.ctx.set_transcriber(Arc::new(MockTranscriber::new([
+        "Make it formal.",
+        "move it to thursday",
+    ])));
+
+    record(&h, int_spec(16_000, 1, 16), int_spec(16_000, 1, 16), 0.5);
+    let session = h.queue.pending().remove(0);
+    block_on(process_session(&h.ctx, &session, &CancellationToken::new())).unwrap();
+
+    let history = store::load_data(&h.ctx).unwrap().history;
+    assert_eq!(history[0].original_content, "move it to Thursday");
+}
+
+#[test]
+fn missing_ollama_model_is_reported() {
+    let stub = OllamaStub::start(&["gemma3:4b"], REPLY);
+    let settings = Settings {
+        model: "llama3:8b".to_string(),
+        ollama_url: stub.url().to_string(),
+        ..Settings::default()
+    };
+
+    let error = block_on(enrich("a", "b", &settings, &CancellationToken::new())).unwrap_err();
+    assert_eq!(error.code(), "model_not_installed");
+}
+
+#[test]
+fn unreachable_ollama_is_reported() {
+    // Nothing listens on the discard port
+    let settings = Settings {
+        ollama_url: "http://127.0.0.1:9".to_string(),
+        ..Settings::default()
+    };
+
+    let error = block_on(enrich("a", "b", &settings, &CancellationToken::new())).unwrap_err();
+    assert_eq!(error.code(), "ollama_unreachable");
+}
+
+#[test]
+fn installed_models_are_not_pulled_again() {
+    let stub = OllamaStub::start(&["gemma3:4b", "llama3:8b"], REPLY);
+    // A trailing slash in the configured URL is accepted
+    let url = format!("{}/", stub.url());
+
+    assert_eq!(installed_models(&url).unwrap(), ["gemma3:4b", "llama3:8b"]);
+    assert!(!pull_missing_model(&url, "gemma3:4b").unwrap());
+    assert!(pull_missing_model(&url, "qwen3:8b").unwrap());
+
+    let requests = stub.requests();
+    let paths: Vec<_> = requests.iter().map(|r| r.path.as_str()).collect();
+    assert_eq!(paths, ["/api/tags", "/api/tags", "/api/tags", "/api/pull"]);
+    assert_eq!(requests[3].body["model"], "qwen3:8b");
+}
+
+/// Runs the language detection of the linked whisper-rs against a real model,
+/// which is too large to download in CI:
+/// `WHISPER_MODEL=/path/to/ggml-model.bin cargo test -- --ignored`
+#[test]
+#[ignore]
+fn whisper_detects_or_pins_the_language() {
+    let model = std::env::var("WHISPER_MODEL").expect("WHISPER_MODEL must point to a model");
+    let whisper =
+        WhisperContext::new_with_params(&model, WhisperContextParameters::default()).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tone.wav");
+    write_tone(&path, int_spec(16_000, 1, 16), 2.0, 0.5);
+    let samples = decode_audio_file(&path).unwrap();
+
+    let detected = whisper
+        .transcribe(&samples, &Settings::default(), None, None)
+        .unwrap();
+    let language = detected.language.as_deref().unwrap();
+    assert!(whisper_rs::get_lang_id(language).is_some(), "{}", language);
+    let probability = detected.language_probability.unwrap();
+    assert!((0.0..=1.0).contains(&probability), "{}", probability);
+
+    let settings = Settings {
+        transcription_language: "de".to_string(),
+        ..Settings::default()
+    };
+    let pinned = whisper.transcribe(&samples, &settings, None, None).unwrap();
+    assert_eq!(pinned.language.as_deref(), Some("de"));
+    assert_eq!(pinned.language_probability, None);
+}
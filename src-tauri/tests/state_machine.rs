@@ -0,0 +1,506 @@
+mod common;
+
+use app_lib::audio::Interruption;
+use app_lib::error::AppError;
+use app_lib::logic;
+use app_lib::models::{Cue, ImportMode};
+use app_lib::queue::{self, CONTENT_FILENAME, INSTRUCTION_FILENAME};
+use app_lib::state_machine::{AppStatus, Effect, RecordingEvent};
+use app_lib::store;
+use common::Harness;
+use serde_json::json;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+#[test]
+fn full_cycle_queues_the_session() {
+    let h = Harness::new();
+
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Instruction);
+    assert_eq!(store::get_status(&h.ctx).unwrap(), AppStatus::Instruction);
+    let started = h.recorder.started();
+    assert_eq!(started.len(), 1);
+    assert!(started[0].ends_with(INSTRUCTION_FILENAME));
+    assert!(started[0].starts_with(h.dir.path().join("sessions")));
+    assert!(started[0].parent().unwrap().is_dir());
+
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Content);
+    assert_eq!(h.recorder.stop_count(), 1);
+    let started = h.recorder.started();
+    assert_eq!(started.len(), 2);
+    assert_eq!(
+        started[1],
+        started[0].parent().unwrap().join(CONTENT_FILENAME)
+    );
+
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert_eq!(h.recorder.stop_count(), 2);
+    assert_eq!(h.queue.len(), 1);
+    let pending = h.queue.pending();
+    assert_eq!(pending[0].instruction_path, started[0]);
+    assert_eq!(pending[0].content_path, started[1]);
+    assert!(started[0].parent().unwrap().join("session.json").exists());
+
+    assert_eq!(h.ctx.cues(), [Cue::Start, Cue::Advance, Cue::Processing]);
+    assert_eq!(h.ctx.payloads("session-queued").len(), 1);
+    let statuses = h.ctx.payloads("status-changed");
+    assert_eq!(statuses.len(), 3);
+    assert_eq!(statuses[0]["recording"], json!("instruction"));
+    assert_eq!(statuses[1]["recording"], json!("content"));
+    assert_eq!(statuses[2]["recording"], json!("idle"));
+    assert_eq!(statuses[2]["queued_jobs"], json!(1));
+}
+
+#[test]
+fn imports_wait_behind_recordings() {
+    let h = Harness::new();
+    for _ in 0..3 {
+        h.trigger();
+    }
+    let file = h.dir.path().join("meeting.mp3");
+    let import_id = queue::enqueue_import(
+        &h.ctx,
+        &h.queue,
+        file.clone(),
+        ImportMode::Enrich,
+        Some("Summarize".to_string()),
+    );
+
+    let pending = h.queue.pending();
+    assert_eq!(pending.len(), 2);
+    assert!(pending[0].import.is_none());
+    assert_eq!(pending[1].id, import_id);
+    assert_eq!(pending[1].content_path, file);
+    let import = pending[1].import.as_ref().unwrap();
+    assert_eq!(import.mode, ImportMode::Enrich);
+    assert_eq!(import.instruction.as_deref(), Some("Summarize"));
+
+    assert_eq!(h.ctx.payloads("session-queued").len(), 2);
+    assert_eq!(logic::snapshot(&h.logic, &h.queue).queued_jobs, 2);
+    // Imports are not written to the sessions directory
+    assert!(!h.dir.path().join("sessions").join(&import_id).exists());
+}
+
+#[test]
+fn every_recording_gets_its_own_session() {
+    let h = Harness::new();
+    for _ in 0..6 {
+        h.trigger();
+    }
+
+    let pending = h.queue.pending();
+    assert_eq!(pending.len(), 2);
+    assert_ne!(pending[0].id, pending[1].id);
+}
+
+#[test]
+fn instruction_without_session_returns_to_idle() {
+    let h = Harness::new();
+    h.trigger();
+    // The session was lost while recording
+    *h.logic.session_id.lock().unwrap() = None;
+
+    let transition = h.trigger().unwrap();
+    assert_eq!(transition.effect, Effect::DiscardSession);
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert_eq!(h.recorder.started().len(), 1);
+    assert_eq!(h.recorder.stop_count(), 1);
+    assert!(h.queue.is_empty());
+}
+
+#[test]
+fn cancel_discards_the_session() {
+    let h = Harness::new();
+    h.trigger();
+    h.trigger();
+    let session_dir = h.recorder.started()[0].parent().unwrap().to_path_buf();
+    assert!(session_dir.is_dir());
+
+    assert!(h.send(RecordingEvent::Cancel).is_some());
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert_eq!(h.recorder.stop_count(), 2);
+    assert!(!session_dir.exists());
+    assert!(h.queue.is_empty());
+    assert_eq!(h.ctx.cues().last(), Some(&Cue::Advance));
+    assert_eq!(store::get_status(&h.ctx).unwrap(), AppStatus::Idle);
+
+    // The next trigger starts a new session
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Instruction);
+    assert_ne!(h.recorder.started()[2].parent().unwrap(), session_dir);
+}
+
+#[test]
+fn timeout_discards_the_session() {
+    let h = Harness::new();
+    h.trigger();
+    let session_dir = h.recorder.started()[0].parent().unwrap().to_path_buf();
+
+    h.send(RecordingEvent::Timeout).unwrap();
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert!(!session_dir.exists());
+}
+
+#[test]
+fn timeout_advances_when_enabled() {
+    let h = Harness::new();
+    let mut data = store::load_data(&h.ctx).unwrap();
+    data.settings.advance_on_timeout = true;
+    store::save_data(&h.ctx, &data).unwrap();
+
+    h.trigger();
+    let transition = h.send(RecordingEvent::Timeout).unwrap();
+    assert_eq!(transition.effect, Effect::RecordContent);
+    assert_eq!(h.status(), AppStatus::Content);
+    assert_eq!(h.recorder.started().len(), 2);
+
+    let transition = h.send(RecordingEvent::Timeout).unwrap();
+    assert_eq!(transition.effect, Effect::QueueSession);
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert_eq!(h.queue.len(), 1);
+    assert_eq!(h.ctx.cues(), [Cue::Start, Cue::Timeout, Cue::Timeout]);
+}
+
+#[test]
+fn recordings_are_warned_before_the_timeout() {
+    let h = Harness::new();
+    h.trigger();
+    assert_eq!(
+        h.recorder.options()[0].timeout_warning,
+        Duration::from_secs(30)
+    );
+
+    logic::timeout_warning(&h.ctx, &h.logic, Duration::from_millis(29_800));
+    let warnings = h.ctx.payloads("recording-timeout-warning");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0]["recording"], json!("instruction"));
+    assert_eq!(warnings[0]["seconds_left"], json!(30));
+    assert_eq!(warnings[0]["advance_on_timeout"], json!(false));
+    assert!(warnings[0]["session_id"].is_string());
+}
+
+#[test]
+fn failed_recording_start_returns_to_idle() {
+    let h = Harness::new();
+    h.recorder
+        .fail_next_start(AppError::InputDeviceNotFound("USB Headset".to_string()));
+
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert!(h.queue.is_empty());
+    assert!(!h.recorder.started()[0].parent().unwrap().exists());
+
+    // The frontend never sees the recording state, only the error
+    let statuses = h.ctx.payloads("status-changed");
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(statuses[0]["recording"], json!("idle"));
+    let errors = h.ctx.payloads("recording-error");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["code"], json!("input_device_not_found"));
+    assert_eq!(h.ctx.cues(), [Cue::Error]);
+    assert_eq!(store::get_status(&h.ctx).unwrap(), AppStatus::Idle);
+}
+
+#[test]
+fn failed_content_recording_discards_the_session() {
+    let h = Harness::new();
+    h.trigger();
+    h.recorder
+        .fail_next_start(AppError::AudioDevice("The device is busy".to_string()));
+
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert!(h.queue.is_empty());
+    assert!(!h.recorder.started()[0].parent().unwrap().exists());
+    let errors = h.ctx.payloads("recording-error");
+    assert_eq!(errors[0]["details"], json!("The device is busy"));
+}
+
+#[test]
+fn unfinalized_content_is_not_queued() {
+    let h = Harness::new();
+    h.trigger();
+    h.trigger();
+    let session_dir = h.recorder.started()[0].parent().unwrap().to_path_buf();
+    h.recorder.fail_next_stop(AppError::Storage(
+        "Failed to finalize the recording: No space left on device".to_string(),
+    ));
+
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert!(h.queue.is_empty());
+    assert!(!session_dir.exists());
+    let errors = h.ctx.payloads("recording-error");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["code"], json!("storage"));
+}
+
+#[test]
+fn unfinalized_instruction_discards_the_session() {
+    let h = Harness::new();
+    h.trigger();
+    h.recorder.fail_next_stop(AppError::Storage(
+        "Failed to finalize the recording".to_string(),
+    ));
+
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Idle);
+    // The content recording is never started
+    assert_eq!(h.recorder.started().len(), 1);
+    assert!(!h.recorder.started()[0].parent().unwrap().exists());
+    assert_eq!(h.ctx.payloads("recording-error").len(), 1);
+}
+
+#[test]
+fn events_that_do_not_apply_are_ignored() {
+    let h = Harness::new();
+    for event in [
+        RecordingEvent::Cancel,
+        RecordingEvent::Timeout,
+        RecordingEvent::RecordingFailed,
+    ] {
+        assert!(h.send(event).is_none());
+    }
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert!(h.ctx.events().is_empty());
+    assert!(h.ctx.cues().is_empty());
+}
+
+#[test]
+fn pipeline_events_keep_the_recording_going() {
+    let h = Harness::new();
+    h.trigger();
+
+    h.send(RecordingEvent::PipelineDone).unwrap();
+    h.send(RecordingEvent::PipelineFailed).unwrap();
+    assert_eq!(h.status(), AppStatus::Instruction);
+    assert_eq!(h.recorder.stop_count(), 0);
+    assert_eq!(h.ctx.payloads("status-changed").len(), 1);
+
+    let history = h.logic.machine.lock().unwrap().snapshot().history;
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[2].event, RecordingEvent::PipelineFailed);
+}
+
+#[test]
+fn each_recording_gets_a_timeout_handler() {
+    let h = Harness::new();
+
+    h.trigger();
+    assert!(h.recorder.fire_timeout());
+    assert_eq!(h.timeouts.load(Ordering::SeqCst), 1);
+
+    // Stopping a recording drops its handler
+    h.trigger();
+    h.trigger();
+    assert!(!h.recorder.fire_timeout());
+    assert_eq!(h.timeouts.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn timeouts_of_earlier_recordings_are_ignored() {
+    let h = Harness::new();
+
+    h.trigger();
+    let instruction = h.logic.recording_id();
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Content);
+    assert_ne!(h.logic.recording_id(), instruction);
+
+    assert!(h.time_out(instruction).is_none());
+    assert_eq!(h.status(), AppStatus::Content);
+    assert_eq!(h.ctx.cues(), [Cue::Start, Cue::Advance]);
+
+    let transition = h.time_out(h.logic.recording_id()).unwrap();
+    assert_eq!(transition.effect, Effect::DiscardSession);
+    assert_eq!(h.status(), AppStatus::Idle);
+}
+
+#[test]
+fn stale_timeout_during_a_trigger_is_ignored() {
+    let h = Harness::new();
+    h.trigger();
+    let instruction = h.logic.recording_id();
+
+    std::thread::scope(|scope| {
+        // The trigger has advanced the state machine and is stopping the instruction
+        let gate = h.recorder.hold_next_stop();
+        let trigger = scope.spawn(|| h.trigger());
+        gate.wait_until_reached();
+
+        // The instruction's timer fires just then
+        assert!(h.time_out(instruction).is_none());
+        gate.open();
+        assert_eq!(
+            trigger.join().unwrap().unwrap().effect,
+            Effect::RecordContent
+        );
+    });
+
+    assert_eq!(h.status(), AppStatus::Content);
+    assert_eq!(h.recorder.started().len(), 2);
+    assert!(h.queue.is_empty());
+    assert_eq!(h.ctx.cues(), [Cue::Start, Cue::Advance]);
+}
+
+fn disconnect(at_ms: u64, resumed_on: Option<&str>) -> Interruption {
+    Interruption {
+        device: "USB Headset".to_string(),
+        error: "The requested device is no longer available".to_string(),
+        at_ms,
+        resumed_on: resumed_on.map(str::to_string),
+        gap_ms: if resumed_on.is_some() { 800 } else { 0 },
+    }
+}
+
+#[test]
+fn content_cut_off_by_the_device_is_processed() {
+    let h = Harness::new();
+    h.trigger();
+    h.trigger();
+
+    h.interrupt(disconnect(4200, None));
+    assert_eq!(h.status(), AppStatus::Idle);
+    // The failure is signalled even though the content is processed
+    assert_eq!(h.ctx.cues().last(), Some(&Cue::Error));
+    let pending = h.queue.pending();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].gaps.len(), 1);
+    assert_eq!(pending[0].gaps[0].recording, AppStatus::Content);
+    assert_eq!(pending[0].gaps[0].interruption.at_ms, 4200);
+
+    let interrupted = h.ctx.payloads("recording-interrupted");
+    assert_eq!(interrupted.len(), 1);
+    assert_eq!(interrupted[0]["session_id"], json!(pending[0].id));
+    assert_eq!(interrupted[0]["recording"], json!("content"));
+    assert_eq!(interrupted[0]["device"], json!("USB Headset"));
+    assert_eq!(interrupted[0]["resumed_on"], json!(null));
+}
+
+#[test]
+fn interrupted_instruction_discards_the_session() {
+    let h = Harness::new();
+    h.trigger();
+    let session_dir = h.recorder.started()[0].parent().unwrap().to_path_buf();
+
+    h.interrupt(disconnect(1500, None));
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert!(h.queue.is_empty());
+    assert!(!session_dir.exists());
+}
+
+#[test]
+fn content_without_audio_is_discarded() {
+    let h = Harness::new();
+    h.trigger();
+    h.trigger();
+
+    h.interrupt(disconnect(0, None));
+    assert_eq!(h.status(), AppStatus::Idle);
+    assert!(h.queue.is_empty());
+}
+
+#[test]
+fn recording_continued_on_another_device_keeps_the_gap() {
+    let h = Harness::new();
+    let mut data = store::load_data(&h.ctx).unwrap();
+    data.settings.continue_on_default_device = true;
+    store::save_data(&h.ctx, &data).unwrap();
+
+    h.trigger();
+    assert!(h.recorder.options()[0].continue_on_default_device);
+    h.interrupt(disconnect(2000, Some("Built-in Microphone")));
+    assert_eq!(h.status(), AppStatus::Instruction);
+    assert_eq!(h.recorder.stop_count(), 0);
+
+    h.trigger();
+    h.trigger();
+    let pending = h.queue.pending();
+    assert_eq!(pending.len(), 1);
+    let gap = &pending[0].gaps[0];
+    assert_eq!(gap.recording, AppStatus::Instruction);
+    assert_eq!(
+        gap.interruption.resumed_on.as_deref(),
+        Some("Built-in Microphone")
+    );
+
+    // The gap is kept with the session across restarts
+    let dir = h.recorder.started()[0].parent().unwrap().to_path_buf();
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("session.json")).unwrap()).unwrap();
+    assert_eq!(saved["gaps"][0]["recording"], json!("instruction"));
+    assert_eq!(saved["gaps"][0]["at_ms"], json!(2000));
+    assert_eq!(saved["gaps"][0]["gap_ms"], json!(800));
+}
+
+#[test]
+fn gaps_do_not_carry_over_to_the_next_session() {
+    let h = Harness::new();
+    h.trigger();
+    h.interrupt(disconnect(2000, Some("Built-in Microphone")));
+    h.send(RecordingEvent::Cancel).unwrap();
+
+    for _ in 0..3 {
+        h.trigger();
+    }
+    assert!(h.queue.pending()[0].gaps.is_empty());
+}
+
+#[test]
+fn pause_and_resume_keep_the_recording() {
+    let h = Harness::new();
+    h.trigger();
+    h.trigger();
+
+    h.send(RecordingEvent::Pause).unwrap();
+    assert_eq!(h.status(), AppStatus::Content);
+    assert!(h.logic.is_paused());
+    // Pausing twice does nothing
+    h.send(RecordingEvent::Pause).unwrap();
+    h.send(RecordingEvent::Resume).unwrap();
+    assert!(!h.logic.is_paused());
+    assert_eq!(h.recorder.pause_calls(), [true, false]);
+    assert_eq!(h.recorder.started().len(), 2);
+    assert_eq!(h.recorder.stop_count(), 1);
+
+    let statuses = h.ctx.payloads("status-changed");
+    assert_eq!(statuses.len(), 4);
+    assert_eq!(statuses[2]["recording"], json!("content"));
+    assert_eq!(statuses[2]["paused"], json!(true));
+    assert_eq!(statuses[3]["paused"], json!(false));
+
+    // One recording, so one transcript
+    h.trigger();
+    assert_eq!(h.queue.len(), 1);
+}
+
+#[test]
+fn advancing_ends_the_pause() {
+    let h = Harness::new();
+    h.trigger();
+    h.send(RecordingEvent::Pause).unwrap();
+
+    h.trigger();
+    assert_eq!(h.status(), AppStatus::Content);
+    assert!(!h.logic.is_paused());
+    assert_eq!(
+        h.ctx.payloads("status-changed").last().unwrap()["paused"],
+        json!(false)
+    );
+
+    h.send(RecordingEvent::Pause).unwrap();
+    h.send(RecordingEvent::Cancel).unwrap();
+    assert!(!h.logic.is_paused());
+}
+
+#[test]
+fn pause_is_ignored_while_idle() {
+    let h = Harness::new();
+    assert!(h.send(RecordingEvent::Pause).is_none());
+    assert!(h.send(RecordingEvent::Resume).is_none());
+    assert!(h.recorder.pause_calls().is_empty());
+    assert!(!h.logic.is_paused());
+}
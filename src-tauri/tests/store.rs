@@ -0,0 +1,260 @@
+use app_lib::mock::MockContext;
+use app_lib::models::{
+    AppStateData, BatchQueueData, Cue, CueSound, HistoryItem, Settings, SoundTheme,
+};
+use app_lib::state_machine::AppStatus;
+use app_lib::store;
+use std::fs;
+
+fn history_item(id: &str) -> HistoryItem {
+    HistoryItem {
+        id: id.to_string(),
+        timestamp: "2026-01-01T12:00:00+01:00".to_string(),
+        instruction: "Make it formal".to_string(),
+        original_content: "hey".to_string(),
+        enriched_content: "Hello".to_string(),
+        source_file: None,
+        language: Some("en".to_string()),
+        language_probability: None,
+        instruction_transcript: None,
+        content_transcript: None,
+        stage_timings: Vec::new(),
+    }
+}
+
+#[test]
+fn first_load_writes_defaults() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+
+    let data = store::load_data(&ctx).unwrap();
+    assert!(data.history.is_empty());
+    assert_eq!(data.settings.hotkey, "Ctrl+I");
+    assert!(dir.path().join("app_data.json").exists());
+}
+
+#[test]
+fn saved_data_is_loaded_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+
+    let mut data = AppStateData::default();
+    data.settings.model = "llama3:8b".to_string();
+    data.history.push(history_item("a"));
+    store::save_data(&ctx, &data).unwrap();
+
+    let loaded = store::load_data(&ctx).unwrap();
+    assert_eq!(loaded.settings.model, "llama3:8b");
+    assert_eq!(loaded.history.len(), 1);
+    assert_eq!(loaded.history[0].id, "a");
+}
+
+#[test]
+fn status_round_trips() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+
+    assert_eq!(store::get_status(&ctx).unwrap(), AppStatus::Idle);
+    store::set_status(&ctx, AppStatus::Instruction).unwrap();
+    assert_eq!(store::get_status(&ctx).unwrap(), AppStatus::Instruction);
+}
+
+#[test]
+fn history_items_are_appended() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+
+    store::append_history_item(&ctx, history_item("a")).unwrap();
+    store::append_history_item(&ctx, history_item("b")).unwrap();
+
+    let ids: Vec<_> = store::load_data(&ctx)
+        .unwrap()
+        .history
+        .into_iter()
+        .map(|item| item.id)
+        .collect();
+    assert_eq!(ids, ["a", "b"]);
+}
+
+#[test]
+fn corrupt_file_falls_back_to_defaults() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+    fs::write(dir.path().join("app_data.json"), "{ not json").unwrap();
+    fs::write(dir.path().join("batch_queue.json"), r#"{"jobs": 3"#).unwrap();
+
+    let data = store::load_data(&ctx).unwrap();
+    assert!(data.history.is_empty());
+    assert_eq!(data.status, AppStatus::Idle);
+    assert!(store::load_batch_queue(&ctx).unwrap().jobs.is_empty());
+
+    // The unreadable files are kept next to the defaults
+    let backups = |name: &str| -> Vec<String> {
+        let prefix = format!("{}.corrupt-", name);
+        fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with(&prefix))
+            })
+            .map(|path| fs::read_to_string(path).unwrap())
+            .collect()
+    };
+    assert_eq!(backups("app_data.json"), ["{ not json"]);
+    assert_eq!(backups("batch_queue.json"), [r#"{"jobs": 3"#]);
+    assert!(!dir.path().join("batch_queue.json").exists());
+}
+
+#[test]
+fn concurrent_updates_are_all_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+
+    std::thread::scope(|scope| {
+        for i in 0..8 {
+            let ctx = &ctx;
+            scope.spawn(move || {
+                store::append_history_item(ctx, history_item(&i.to_string())).unwrap();
+                store::update(ctx, |data| data.settings.min_recording_ms += 1).unwrap();
+            });
+        }
+    });
+
+    let data = store::load_data(&ctx).unwrap();
+    assert_eq!(data.history.len(), 8);
+    assert_eq!(
+        data.settings.min_recording_ms,
+        Settings::default().min_recording_ms + 8
+    );
+    // Saves go through a temporary file that is renamed into place
+    let names: Vec<_> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, ["app_data.json"]);
+}
+
+#[test]
+fn missing_fields_get_defaults() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+    // Written by a version without most of the current settings
+    fs::write(
+        dir.path().join("app_data.json"),
+        r#"{
+            "settings": {
+                "model": "gemma3:4b",
+                "microphone": "default",
+                "hotkey": "Ctrl+K",
+                "recording_timeout_minutes": 5
+            },
+            "history": []
+        }"#,
+    )
+    .unwrap();
+
+    let data = store::load_data(&ctx).unwrap();
+    assert_eq!(data.settings.hotkey, "Ctrl+K");
+    assert_eq!(data.settings.transcription_language, "auto");
+    assert_eq!(data.settings.timeout_warning_seconds, 30);
+    assert_eq!(data.settings.sounds.theme, SoundTheme::Tones);
+    assert_eq!(data.settings.sounds.volume, 1.0);
+    assert_eq!(data.status, AppStatus::Idle);
+}
+
+#[test]
+fn empty_language_reads_as_auto() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+    // Older versions saved an empty language for automatic detection
+    fs::write(
+        dir.path().join("app_data.json"),
+        r#"{
+            "settings": {
+                "model": "gemma3:4b",
+                "microphone": "default",
+                "hotkey": "Ctrl+K",
+                "recording_timeout_minutes": 5,
+                "transcription_language": ""
+            },
+            "history": []
+        }"#,
+    )
+    .unwrap();
+
+    let settings = store::load_data(&ctx).unwrap().settings;
+    assert_eq!(settings.transcription_language, "auto");
+    assert_eq!(settings.validate(), Ok(()));
+}
+
+#[test]
+fn sound_settings_are_read_and_validated() {
+    let mut settings: Settings = serde_json::from_value(serde_json::json!({
+        "model": "gemma3:4b",
+        "microphone": "default",
+        "hotkey": "Ctrl+I",
+        "recording_timeout_minutes": 10,
+        "sounds": {
+            "theme": "chimes",
+            "volume": 0.5,
+            "cues": {
+                "error": { "kind": "silent" },
+                "start": { "kind": "tones", "tones": [{ "frequency_hz": 440.0, "duration_ms": 100 }] }
+            }
+        }
+    }))
+    .unwrap();
+    assert_eq!(settings.sounds.theme, SoundTheme::Chimes);
+    assert_eq!(settings.sounds.cues[&Cue::Error], CueSound::Silent);
+    assert!(!settings.sounds.muted);
+    assert!(settings.validate().is_ok());
+
+    settings.sounds.cues.insert(
+        Cue::Success,
+        CueSound::Bundled {
+            name: "fanfare".to_string(),
+        },
+    );
+    assert!(settings.validate().is_err());
+    settings.sounds.cues.insert(
+        Cue::Success,
+        CueSound::File {
+            path: "/nonexistent/success.wav".into(),
+        },
+    );
+    assert!(settings.validate().is_err());
+    settings.sounds.cues.remove(&Cue::Success);
+    settings.sounds.volume = 1.5;
+    assert!(settings.validate().is_err());
+}
+
+#[test]
+fn legacy_processing_status_reads_as_idle() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+    let mut data = AppStateData::default();
+    data.history.push(history_item("a"));
+    let mut json = serde_json::to_value(&data).unwrap();
+    json["status"] = "processing".into();
+    fs::write(dir.path().join("app_data.json"), json.to_string()).unwrap();
+
+    let data = store::load_data(&ctx).unwrap();
+    assert_eq!(data.status, AppStatus::Idle);
+    assert_eq!(data.history.len(), 1);
+}
+
+#[test]
+fn batch_queue_round_trips() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = MockContext::new(dir.path());
+
+    assert!(store::load_batch_queue(&ctx).unwrap().jobs.is_empty());
+
+    let queue = BatchQueueData {
+        jobs: Vec::new(),
+        paused: true,
+    };
+    store::save_batch_queue(&ctx, &queue).unwrap();
+    assert!(store::load_batch_queue(&ctx).unwrap().paused);
+}
@@ -5,10 +5,12 @@ import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { State } from "@/components/State";
 import { Settings } from "@/components/Settings";
 import { History } from "@/components/History";
+import { Batch } from "@/components/Batch";
 import {
   Activity,
   Settings as SettingsIcon,
   History as HistoryIcon,
+  ListChecks,
 } from "lucide-react";
 import { useAppStore } from "@/lib/store";
 
@@ -33,13 +35,16 @@ export default function Home() {
         className="h-full flex flex-col"
       >
         <div className="border-b px-4 py-2 bg-muted/20">
-          <TabsList className="grid w-full grid-cols-3">
+          <TabsList className="grid w-full grid-cols-4">
             <TabsTrigger value="state">
               <Activity className="w-4 h-4 mr-2" /> State
             </TabsTrigger>
             <TabsTrigger value="history">
               <HistoryIcon className="w-4 h-4 mr-2" /> History
             </TabsTrigger>
+            <TabsTrigger value="batch">
+              <ListChecks className="w-4 h-4 mr-2" /> Batch
+            </TabsTrigger>
             <TabsTrigger value="settings">
               <SettingsIcon className="w-4 h-4 mr-2" /> Settings
             </TabsTrigger>
@@ -61,6 +66,13 @@ export default function Home() {
             <History />
           </TabsContent>
 
+          <TabsContent
+            value="batch"
+            className="h-full m-0 data-[state=active]:flex flex-col"
+          >
+            <Batch />
+          </TabsContent>
+
           <TabsContent value="settings" className="h-full m-0 overflow-y-auto">
             <Settings />
           </TabsContent>
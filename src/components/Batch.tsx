@@ -0,0 +1,240 @@
+"use client";
+
+import React, { useEffect, useState } from "react";
+import {
+  useAppStore,
+  type AppError,
+  type BatchJobProgress,
+  type BatchJobStatus,
+  type BatchQueueData,
+} from "@/lib/store";
+import { Card, CardContent } from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { ScrollArea } from "@/components/ui/scroll-area";
+import { Badge } from "@/components/ui/badge";
+import { FolderOpen, Pause, Play, Trash2, X } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+
+const STATUS_LABELS: Record<BatchJobStatus, string> = {
+  pending: "Waiting",
+  running: "Running",
+  completed: "Done",
+  failed: "Failed",
+  cancelled: "Cancelled",
+};
+
+export function Batch() {
+  const { fetchHistory } = useAppStore();
+  const [queue, setQueue] = useState<BatchQueueData>({
+    jobs: [],
+    paused: false,
+  });
+  const [instruction, setInstruction] = useState("");
+  const [recursive, setRecursive] = useState(false);
+
+  const fetchJobs = () => {
+    invoke<BatchQueueData>("get_batch_jobs")
+      .then(setQueue)
+      .catch(console.error);
+  };
+
+  useEffect(() => {
+    fetchJobs();
+
+    let unlistenQueue: (() => void) | undefined;
+    let unlistenProgress: (() => void) | undefined;
+
+    async function setupListeners() {
+      unlistenQueue = await listen<BatchQueueData>(
+        "batch-queue-changed",
+        (event) => setQueue(event.payload)
+      );
+
+      unlistenProgress = await listen<BatchJobProgress>(
+        "batch-job-progress",
+        (event) => {
+          const { job_id, status, progress } = event.payload;
+          setQueue((queue) => ({
+            ...queue,
+            jobs: queue.jobs.map((job) =>
+              job.id === job_id ? { ...job, status, progress } : job
+            ),
+          }));
+          if (status === "completed") {
+            fetchHistory();
+          }
+        }
+      );
+    }
+    setupListeners();
+
+    return () => {
+      if (unlistenQueue) unlistenQueue();
+      if (unlistenProgress) unlistenProgress();
+    };
+  }, []);
+
+  const run = async (command: string, args?: Record<string, unknown>) => {
+    try {
+      return await invoke<number | null>(command, args);
+    } catch (error: any) {
+      const e = error as AppError;
+      toast.error("Batch Error", {
+        description: e?.message ?? String(error),
+        duration: 5000,
+      });
+      return null;
+    }
+  };
+
+  const handleAddFolder = async () => {
+    const text = instruction.trim();
+    // Shows a folder picker
+    const count = await run("start_batch", {
+      mode: text ? "enrich" : "transcribe",
+      instruction: text || null,
+      recursive,
+    });
+    if (count) {
+      toast.info("Files Queued", {
+        description: `${count} ${count === 1 ? "file" : "files"} added to the batch`,
+        duration: 3000,
+      });
+    }
+  };
+
+  const active = queue.jobs.some(
+    (job) => job.status === "pending" || job.status === "running"
+  );
+  const finished = queue.jobs.some(
+    (job) => job.status !== "pending" && job.status !== "running"
+  );
+
+  return (
+    <div className="flex flex-col h-screen">
+      <div className="flex justify-between items-center px-4 pt-4 pb-3 shrink-0">
+        <h2 className="text-xl font-bold">Batch</h2>
+        <div className="flex gap-2">
+          <Button
+            variant="outline"
+            size="sm"
+            onClick={() => run(queue.paused ? "resume_batch" : "pause_batch")}
+            title={queue.paused ? "Resume" : "Pause"}
+          >
+            {queue.paused ? (
+              <Play className="h-4 w-4" />
+            ) : (
+              <Pause className="h-4 w-4" />
+            )}
+          </Button>
+          <Button
+            variant="outline"
+            size="sm"
+            onClick={() => run("clear_finished_batch_jobs")}
+            disabled={!finished}
+            title="Clear Finished"
+          >
+            <Trash2 className="h-4 w-4" />
+          </Button>
+          <Button
+            variant="destructive"
+            size="sm"
+            onClick={() => run("cancel_batch")}
+            disabled={!active}
+          >
+            <X className="mr-2 h-4 w-4" /> Cancel All
+          </Button>
+        </div>
+      </div>
+
+      <div className="px-4 pb-3 shrink-0">
+        <Card>
+          <CardContent className="space-y-3">
+            <div className="flex gap-2">
+              <Input
+                placeholder="Instruction (empty to only transcribe)"
+                value={instruction}
+                onChange={(e) => setInstruction(e.target.value)}
+              />
+              <Button onClick={handleAddFolder} variant="outline">
+                <FolderOpen className="mr-2 h-4 w-4" /> Add Folder
+              </Button>
+            </div>
+            <div className="flex items-center justify-between">
+              <Label htmlFor="batch-recursive">Include Subfolders</Label>
+              <Switch
+                id="batch-recursive"
+                checked={recursive}
+                onCheckedChange={setRecursive}
+              />
+            </div>
+          </CardContent>
+        </Card>
+      </div>
+
+      <div className="flex-1 overflow-hidden">
+        <ScrollArea className="h-[80%]">
+          <div className="px-4 pb-12 space-y-2">
+            {queue.paused && active && (
+              <p className="text-sm text-muted-foreground">
+                Paused. Resume to process the remaining files.
+              </p>
+            )}
+            {queue.jobs.length === 0 ? (
+              <div className="flex flex-col items-center text-sm justify-center h-40 text-muted-foreground">
+                <p>No files queued</p>
+              </div>
+            ) : (
+              queue.jobs.map((job) => (
+                <Card key={job.id}>
+                  <CardContent className="flex items-start justify-between gap-2">
+                    <div className="min-w-0 space-y-1">
+                      <p className="text-sm font-medium break-words">
+                        {job.file_name}
+                      </p>
+                      <div className="flex items-center gap-2">
+                        <Badge
+                          variant={
+                            job.status === "failed" ? "destructive" : "secondary"
+                          }
+                        >
+                          {STATUS_LABELS[job.status]}
+                        </Badge>
+                        {job.status === "running" && (
+                          <span className="text-xs text-muted-foreground">
+                            {job.progress}%
+                          </span>
+                        )}
+                      </div>
+                      {job.error && (
+                        <p className="text-xs text-destructive break-words">
+                          {job.error}
+                        </p>
+                      )}
+                    </div>
+                    {(job.status === "pending" || job.status === "running") && (
+                      <Button
+                        variant="ghost"
+                        size="icon-xs"
+                        onClick={() => run("cancel_batch_job", { id: job.id })}
+                        className="shrink-0"
+                        title="Cancel"
+                      >
+                        <X className="h-3.5 w-3.5" />
+                      </Button>
+                    )}
+                  </CardContent>
+                </Card>
+              ))
+            )}
+          </div>
+        </ScrollArea>
+      </div>
+    </div>
+  );
+}
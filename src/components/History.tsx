@@ -1,11 +1,11 @@
 "use client";
 
 import React, { useEffect, useState } from "react";
-import { useAppStore } from "@/lib/store";
+import { useAppStore, type SubtitleFormat } from "@/lib/store";
 import { Card, CardContent } from "@/components/ui/card";
 import { Button } from "@/components/ui/button";
 import { ScrollArea } from "@/components/ui/scroll-area";
-import { Trash2, RefreshCw, X, Copy } from "lucide-react";
+import { Trash2, RefreshCw, X, Copy, Download } from "lucide-react";
 import { Badge } from "@/components/ui/badge";
 import {
   AlertDialog,
@@ -17,6 +17,14 @@ import {
   AlertDialogHeader,
   AlertDialogTitle,
 } from "@/components/ui/alert-dialog";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+
+const EXPORT_FORMATS: { format: SubtitleFormat; label: string }[] = [
+  { format: "srt", label: "SRT" },
+  { format: "vtt", label: "VTT" },
+  { format: "json", label: "JSON" },
+];
 
 export function History() {
   const { history, fetchHistory, clearHistory, deleteHistoryItem } =
@@ -27,6 +35,23 @@ export function History() {
     fetchHistory();
   }, []);
 
+  const exportTranscript = async (id: string, format: SubtitleFormat) => {
+    try {
+      // Shows a save dialog
+      const path = await invoke<string | null>("export_transcript", {
+        id,
+        format,
+      });
+      if (path) {
+        toast.success("Transcript Exported", { description: path });
+      }
+    } catch (error: any) {
+      toast.error("Export Failed", {
+        description: error?.message ?? String(error),
+      });
+    }
+  };
+
   const handleClearAll = () => {
     clearHistory();
     setShowClearDialog(false);
@@ -91,9 +116,29 @@ export function History() {
                         <div className="grid gap-2.5 text-sm">
                           <ScrollArea className="max-h-20 w-full">
                             <div className="bg-muted p-2.5 rounded-md pr-3">
-                              <p className="text-xs font-semibold mb-1.5 text-muted-foreground">
-                                Original:
-                              </p>
+                              <div className="flex items-center justify-between mb-1.5">
+                                <p className="text-xs font-semibold text-muted-foreground">
+                                  Original:
+                                </p>
+                                {item.content_transcript && (
+                                  <div className="flex items-center gap-1">
+                                    <Download className="h-3 w-3 text-muted-foreground" />
+                                    {EXPORT_FORMATS.map(({ format, label }) => (
+                                      <Button
+                                        key={format}
+                                        variant="ghost"
+                                        size="xs"
+                                        onClick={() =>
+                                          exportTranscript(item.id, format)
+                                        }
+                                        title={`Export as ${label}`}
+                                      >
+                                        {label}
+                                      </Button>
+                                    ))}
+                                  </div>
+                                )}
+                              </div>
                               <p className="whitespace-pre-wrap break-words text-sm leading-relaxed">
                                 {item.original_content}
                               </p>
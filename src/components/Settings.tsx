@@ -1,9 +1,20 @@
 "use client";
 
 import React, { useEffect, useState } from "react";
-import { useAppStore } from "@/lib/store";
+import {
+  useAppStore,
+  type AudioProcessingSettings,
+  type AutoPasteSettings,
+  type Cue,
+  type DecodingSettings,
+  type OutputSinkConfig,
+  type CueSound,
+  type Replacement,
+  type SoundSettings,
+} from "@/lib/store";
 import { Label } from "@/components/ui/label";
 import { Input } from "@/components/ui/input";
+import { Switch } from "@/components/ui/switch";
 import {
   Select,
   SelectContent,
@@ -13,10 +24,96 @@ import {
 } from "@/components/ui/select";
 import { Button } from "@/components/ui/button";
 import { Card, CardContent } from "@/components/ui/card";
+import { Badge } from "@/components/ui/badge";
 import { ScrollArea } from "@/components/ui/scroll-area";
-import { RefreshCw, Save, FileText } from "lucide-react";
+import {
+  RefreshCw,
+  Save,
+  FileText,
+  Play,
+  Plus,
+  X,
+  Download,
+  Upload,
+} from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-shell";
+import { toast } from "sonner";
+
+const DEFAULT_SOUNDS: SoundSettings = {
+  theme: "tones",
+  cues: {},
+  volume: 1,
+  muted: false,
+  output_device: null,
+};
+
+const DEFAULT_AUDIO_PROCESSING: AudioProcessingSettings = {
+  enabled: false,
+  dc_removal: true,
+  high_pass_enabled: true,
+  high_pass_cutoff_hz: 80,
+  normalization_enabled: true,
+  normalization_target_dbfs: -20,
+  normalization_max_gain_db: 24,
+  noise_gate_enabled: true,
+  noise_gate_threshold_dbfs: -50,
+};
+
+const DEFAULT_DECODING: DecodingSettings = {
+  strategy: "greedy",
+  best_of: 1,
+  beam_size: 5,
+  temperature: 0,
+  temperature_increment: 0.2,
+  threads: null,
+  no_speech_threshold: 0.6,
+  suppress_blank: true,
+  max_segment_length: 0,
+};
+
+// Languages offered in the list; any other Whisper code set in the settings file is kept
+const LANGUAGES: { code: string; label: string }[] = [
+  { code: "auto", label: "Detect Automatically" },
+  { code: "en", label: "English" },
+  { code: "de", label: "German" },
+  { code: "fr", label: "French" },
+  { code: "es", label: "Spanish" },
+  { code: "it", label: "Italian" },
+  { code: "pt", label: "Portuguese" },
+  { code: "nl", label: "Dutch" },
+  { code: "pl", label: "Polish" },
+  { code: "sv", label: "Swedish" },
+  { code: "ru", label: "Russian" },
+  { code: "uk", label: "Ukrainian" },
+  { code: "tr", label: "Turkish" },
+  { code: "ja", label: "Japanese" },
+  { code: "zh", label: "Chinese" },
+];
+
+const DEFAULT_AUTO_PASTE: AutoPasteSettings = {
+  enabled: false,
+  method: "paste",
+  linux_backend: "auto",
+  restore_clipboard: true,
+  delay_ms: 150,
+};
+
+const NEW_OUTPUTS: Record<OutputSinkConfig["type"], OutputSinkConfig> = {
+  clipboard: { type: "clipboard" },
+  file: { type: "file", path: "", format: "markdown" },
+  webhook: { type: "webhook", url: "" },
+  command: { type: "command", command: "" },
+};
+
+const CUES: { cue: Cue; label: string }[] = [
+  { cue: "start", label: "Start" },
+  { cue: "advance", label: "Advance" },
+  { cue: "processing", label: "Processing" },
+  { cue: "success", label: "Success" },
+  { cue: "error", label: "Error" },
+  { cue: "timeout", label: "Timeout" },
+];
 
 export function Settings() {
   const {
@@ -30,11 +127,20 @@ export function Settings() {
   } = useAppStore();
   const [localSettings, setLocalSettings] = useState(settings);
   const [appInfo, setAppInfo] = useState<any>(null);
+  const [outputDevices, setOutputDevices] = useState<string[]>([]);
+  const [newTerm, setNewTerm] = useState("");
+
+  const fetchOutputDevices = () => {
+    invoke<string[]>("get_output_devices")
+      .then(setOutputDevices)
+      .catch(console.error);
+  };
 
   useEffect(() => {
     fetchSettings();
     fetchModels();
     fetchMicrophones();
+    fetchOutputDevices();
 
     // Fetch app info for diagnostics
     invoke("get_app_info")
@@ -47,15 +153,135 @@ export function Settings() {
   }, [settings]);
 
   const handleSave = async () => {
-    await saveSettings(localSettings);
+    // Rows added but never filled in would fail validation
+    await saveSettings({
+      ...localSettings,
+      replacements: localSettings.replacements?.filter((r) => r.pattern),
+    });
+  };
+
+  const processing =
+    localSettings.audio_processing ?? DEFAULT_AUDIO_PROCESSING;
+  const updateProcessing = (patch: Partial<AudioProcessingSettings>) =>
+    setLocalSettings({
+      ...localSettings,
+      audio_processing: { ...processing, ...patch },
+    });
+
+  const language = localSettings.transcription_language ?? "auto";
+  const decoding = localSettings.decoding ?? DEFAULT_DECODING;
+  const updateDecoding = (patch: Partial<DecodingSettings>) =>
+    setLocalSettings({
+      ...localSettings,
+      decoding: { ...decoding, ...patch },
+    });
+
+  const vocabulary = localSettings.vocabulary ?? [];
+  const replacements = localSettings.replacements ?? [];
+  const addTerm = () => {
+    const term = newTerm.trim();
+    if (term && !vocabulary.includes(term)) {
+      setLocalSettings({ ...localSettings, vocabulary: [...vocabulary, term] });
+    }
+    setNewTerm("");
+  };
+  const updateReplacement = (index: number, patch: Partial<Replacement>) =>
+    setLocalSettings({
+      ...localSettings,
+      replacements: replacements.map((r, i) =>
+        i === index ? { ...r, ...patch } : r
+      ),
+    });
+
+  const showError = (title: string, error: any) =>
+    toast.error(title, {
+      description: error?.details
+        ? `${error.message} (${error.details})`
+        : error?.message ?? String(error),
+    });
+
+  const exportVocabulary = async () => {
+    try {
+      // Shows a save dialog; exports the saved settings
+      const path = await invoke<string | null>("export_vocabulary");
+      if (path) {
+        toast.success("Vocabulary Exported", { description: path });
+      }
+    } catch (error: any) {
+      showError("Export Failed", error);
+    }
+  };
+
+  const importVocabulary = async () => {
+    try {
+      // Shows a file picker; imported entries are merged and saved
+      await invoke("import_vocabulary");
+      await fetchSettings();
+    } catch (error: any) {
+      showError("Import Failed", error);
+    }
+  };
+
+  const outputs = localSettings.outputs ?? [NEW_OUTPUTS.clipboard];
+  const setOutputs = (outputs: OutputSinkConfig[]) =>
+    setLocalSettings({ ...localSettings, outputs });
+  const updateOutput = (index: number, output: OutputSinkConfig) =>
+    setOutputs(outputs.map((o, i) => (i === index ? output : o)));
+
+  const autoPaste = localSettings.auto_paste ?? DEFAULT_AUTO_PASTE;
+  const updateAutoPaste = (patch: Partial<AutoPasteSettings>) =>
+    setLocalSettings({
+      ...localSettings,
+      auto_paste: { ...autoPaste, ...patch },
+    });
+
+  const sounds = localSettings.sounds ?? DEFAULT_SOUNDS;
+  const updateSounds = (patch: Partial<SoundSettings>) =>
+    setLocalSettings({ ...localSettings, sounds: { ...sounds, ...patch } });
+  const setCueSound = (cue: Cue, sound: CueSound | null) => {
+    const cues = { ...sounds.cues };
+    if (sound) {
+      cues[cue] = sound;
+    } else {
+      delete cues[cue];
+    }
+    updateSounds({ cues });
+  };
+
+  const playTestSound = async (cue: Cue) => {
+    try {
+      // Plays the unsaved settings
+      await invoke("play_test_sound", { cue, sounds });
+    } catch (error: any) {
+      showError("Cannot Play Sound", error);
+    }
   };
 
   const hasChanges =
     localSettings.model !== settings.model ||
     localSettings.microphone !== settings.microphone ||
     localSettings.hotkey !== settings.hotkey ||
+    (localSettings.pause_hotkey ?? null) !== (settings.pause_hotkey ?? null) ||
     localSettings.recording_timeout_minutes !==
-      settings.recording_timeout_minutes;
+      settings.recording_timeout_minutes ||
+    localSettings.timeout_warning_seconds !==
+      settings.timeout_warning_seconds ||
+    localSettings.advance_on_timeout !== settings.advance_on_timeout ||
+    JSON.stringify(localSettings.audio_processing) !==
+      JSON.stringify(settings.audio_processing) ||
+    localSettings.transcription_language !== settings.transcription_language ||
+    localSettings.whisper_task !== settings.whisper_task ||
+    JSON.stringify(localSettings.decoding) !==
+      JSON.stringify(settings.decoding) ||
+    JSON.stringify(localSettings.vocabulary) !==
+      JSON.stringify(settings.vocabulary) ||
+    JSON.stringify(localSettings.replacements) !==
+      JSON.stringify(settings.replacements) ||
+    JSON.stringify(localSettings.auto_paste) !==
+      JSON.stringify(settings.auto_paste) ||
+    JSON.stringify(localSettings.outputs) !==
+      JSON.stringify(settings.outputs) ||
+    JSON.stringify(localSettings.sounds) !== JSON.stringify(settings.sounds);
 
   return (
     <div className="flex flex-col h-full">
@@ -168,6 +394,25 @@ export function Settings() {
                   </p>
                 </div>
 
+                <div className="space-y-2">
+                  <Label htmlFor="pause-hotkey">Pause Hotkey</Label>
+                  <Input
+                    id="pause-hotkey"
+                    placeholder="Not set"
+                    value={localSettings.pause_hotkey ?? ""}
+                    onChange={(e) =>
+                      setLocalSettings({
+                        ...localSettings,
+                        pause_hotkey: e.target.value || null,
+                      })
+                    }
+                  />
+                  <p className="text-xs text-muted-foreground">
+                    Pauses and resumes the current recording; paused time does
+                    not count toward the timeout
+                  </p>
+                </div>
+
                 <div className="space-y-2">
                   <Label htmlFor="timeout">Recording Timeout (minutes)</Label>
                   <Input
@@ -188,6 +433,910 @@ export function Settings() {
                     Maximum duration before auto-timeout (1-60 minutes)
                   </p>
                 </div>
+
+                <div className="space-y-2">
+                  <Label htmlFor="timeout-warning">
+                    Timeout Warning (seconds)
+                  </Label>
+                  <Input
+                    id="timeout-warning"
+                    type="number"
+                    min="0"
+                    max="300"
+                    value={localSettings.timeout_warning_seconds ?? 30}
+                    onChange={(e) =>
+                      setLocalSettings({
+                        ...localSettings,
+                        timeout_warning_seconds: parseInt(e.target.value) || 0,
+                      })
+                    }
+                  />
+                  <p className="text-xs text-muted-foreground">
+                    Warns this long before the timeout (0 disables the warning)
+                  </p>
+                </div>
+
+                <div className="space-y-2">
+                  <div className="flex items-center justify-between">
+                    <Label htmlFor="advance-on-timeout">
+                      Continue on Timeout
+                    </Label>
+                    <Switch
+                      id="advance-on-timeout"
+                      checked={localSettings.advance_on_timeout ?? false}
+                      onCheckedChange={(checked) =>
+                        setLocalSettings({
+                          ...localSettings,
+                          advance_on_timeout: checked,
+                        })
+                      }
+                    />
+                  </div>
+                  <p className="text-xs text-muted-foreground">
+                    Keeps a recording that reaches the timeout: the instruction
+                    moves on to the content, the content is processed
+                  </p>
+                </div>
+              </CardContent>
+            </Card>
+
+            <Card>
+              <CardContent className="space-y-4">
+                <div className="space-y-2">
+                  <div className="flex items-center justify-between">
+                    <Label htmlFor="processing-enabled">Audio Processing</Label>
+                    <Switch
+                      id="processing-enabled"
+                      checked={processing.enabled}
+                      onCheckedChange={(enabled) =>
+                        updateProcessing({ enabled })
+                      }
+                    />
+                  </div>
+                  <p className="text-xs text-muted-foreground">
+                    Cleans up recordings before they are saved and transcribed
+                  </p>
+                </div>
+
+                {processing.enabled && (
+                  <>
+                    <div className="flex items-center justify-between">
+                      <Label htmlFor="dc-removal">Remove DC Offset</Label>
+                      <Switch
+                        id="dc-removal"
+                        checked={processing.dc_removal}
+                        onCheckedChange={(dc_removal) =>
+                          updateProcessing({ dc_removal })
+                        }
+                      />
+                    </div>
+
+                    <div className="space-y-2">
+                      <div className="flex items-center justify-between">
+                        <Label htmlFor="high-pass">High-Pass Filter (Hz)</Label>
+                        <Switch
+                          id="high-pass"
+                          checked={processing.high_pass_enabled}
+                          onCheckedChange={(high_pass_enabled) =>
+                            updateProcessing({ high_pass_enabled })
+                          }
+                        />
+                      </div>
+                      <Input
+                        type="number"
+                        min="20"
+                        max="500"
+                        disabled={!processing.high_pass_enabled}
+                        value={processing.high_pass_cutoff_hz}
+                        onChange={(e) =>
+                          updateProcessing({
+                            high_pass_cutoff_hz:
+                              parseFloat(e.target.value) || 80,
+                          })
+                        }
+                      />
+                      <p className="text-xs text-muted-foreground">
+                        Removes rumble and hum below the cutoff
+                      </p>
+                    </div>
+
+                    <div className="space-y-2">
+                      <div className="flex items-center justify-between">
+                        <Label htmlFor="noise-gate">Noise Gate (dBFS)</Label>
+                        <Switch
+                          id="noise-gate"
+                          checked={processing.noise_gate_enabled}
+                          onCheckedChange={(noise_gate_enabled) =>
+                            updateProcessing({ noise_gate_enabled })
+                          }
+                        />
+                      </div>
+                      <Input
+                        type="number"
+                        min="-90"
+                        max="0"
+                        disabled={!processing.noise_gate_enabled}
+                        value={processing.noise_gate_threshold_dbfs}
+                        onChange={(e) =>
+                          updateProcessing({
+                            noise_gate_threshold_dbfs:
+                              parseFloat(e.target.value) || -50,
+                          })
+                        }
+                      />
+                      <p className="text-xs text-muted-foreground">
+                        Quiets background noise below the threshold
+                      </p>
+                    </div>
+
+                    <div className="space-y-2">
+                      <div className="flex items-center justify-between">
+                        <Label htmlFor="normalization">
+                          Normalization (dBFS / max dB)
+                        </Label>
+                        <Switch
+                          id="normalization"
+                          checked={processing.normalization_enabled}
+                          onCheckedChange={(normalization_enabled) =>
+                            updateProcessing({ normalization_enabled })
+                          }
+                        />
+                      </div>
+                      <div className="flex gap-2">
+                        <Input
+                          type="number"
+                          min="-60"
+                          max="0"
+                          title="Target Level (dBFS)"
+                          disabled={!processing.normalization_enabled}
+                          value={processing.normalization_target_dbfs}
+                          onChange={(e) =>
+                            updateProcessing({
+                              normalization_target_dbfs:
+                                parseFloat(e.target.value) || -20,
+                            })
+                          }
+                        />
+                        <Input
+                          type="number"
+                          min="0"
+                          max="40"
+                          title="Maximum Gain (dB)"
+                          disabled={!processing.normalization_enabled}
+                          value={processing.normalization_max_gain_db}
+                          onChange={(e) =>
+                            updateProcessing({
+                              normalization_max_gain_db:
+                                parseFloat(e.target.value) || 0,
+                            })
+                          }
+                        />
+                      </div>
+                      <p className="text-xs text-muted-foreground">
+                        Brings speech to the target level, amplifying by at most
+                        the maximum gain
+                      </p>
+                    </div>
+                  </>
+                )}
+              </CardContent>
+            </Card>
+
+            <Card>
+              <CardContent className="space-y-4">
+                <div className="space-y-2">
+                  <Label htmlFor="language">Language</Label>
+                  <Select
+                    value={language}
+                    onValueChange={(val) =>
+                      setLocalSettings({
+                        ...localSettings,
+                        transcription_language: val,
+                      })
+                    }
+                  >
+                    <SelectTrigger id="language" className="w-69">
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent className="w-69">
+                      {LANGUAGES.map(({ code, label }) => (
+                        <SelectItem key={code} value={code}>
+                          {label}
+                        </SelectItem>
+                      ))}
+                      {!LANGUAGES.some(({ code }) => code === language) && (
+                        <SelectItem value={language}>{language}</SelectItem>
+                      )}
+                    </SelectContent>
+                  </Select>
+                  <p className="text-xs text-muted-foreground">
+                    Spoken language; a fixed language skips detection
+                  </p>
+                </div>
+
+                <div className="space-y-2">
+                  <Label htmlFor="whisper-task">Task</Label>
+                  <Select
+                    value={localSettings.whisper_task ?? "transcribe"}
+                    onValueChange={(val) =>
+                      setLocalSettings({
+                        ...localSettings,
+                        whisper_task: val as "transcribe" | "translate",
+                      })
+                    }
+                  >
+                    <SelectTrigger id="whisper-task" className="w-69">
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent className="w-69">
+                      <SelectItem value="transcribe">Transcribe</SelectItem>
+                      <SelectItem value="translate">
+                        Translate to English
+                      </SelectItem>
+                    </SelectContent>
+                  </Select>
+                </div>
+
+                <div className="space-y-2">
+                  <Label htmlFor="decoding-strategy">Decoding</Label>
+                  <div className="flex gap-2">
+                    <Select
+                      value={decoding.strategy}
+                      onValueChange={(val) =>
+                        updateDecoding({
+                          strategy: val as DecodingSettings["strategy"],
+                        })
+                      }
+                    >
+                      <SelectTrigger id="decoding-strategy" className="flex-1">
+                        <SelectValue />
+                      </SelectTrigger>
+                      <SelectContent>
+                        <SelectItem value="greedy">Greedy</SelectItem>
+                        <SelectItem value="beam_search">Beam Search</SelectItem>
+                      </SelectContent>
+                    </Select>
+                    {decoding.strategy === "greedy" ? (
+                      <Input
+                        type="number"
+                        min="1"
+                        max="10"
+                        className="w-24"
+                        title="Best Of (1-10)"
+                        value={decoding.best_of}
+                        onChange={(e) =>
+                          updateDecoding({
+                            best_of: parseInt(e.target.value) || 1,
+                          })
+                        }
+                      />
+                    ) : (
+                      <Input
+                        type="number"
+                        min="1"
+                        max="16"
+                        className="w-24"
+                        title="Beam Size (1-16)"
+                        value={decoding.beam_size}
+                        onChange={(e) =>
+                          updateDecoding({
+                            beam_size: parseInt(e.target.value) || 5,
+                          })
+                        }
+                      />
+                    )}
+                  </div>
+                  <p className="text-xs text-muted-foreground">
+                    {decoding.strategy === "greedy"
+                      ? "Candidates per step (1-10)"
+                      : "Beam width (1-16); slower but more accurate"}
+                  </p>
+                </div>
+
+                <div className="grid grid-cols-2 gap-2">
+                  <div className="space-y-2">
+                    <Label htmlFor="temperature">Temperature</Label>
+                    <Input
+                      id="temperature"
+                      type="number"
+                      min="0"
+                      max="1"
+                      step="0.1"
+                      value={decoding.temperature}
+                      onChange={(e) =>
+                        updateDecoding({
+                          temperature: parseFloat(e.target.value) || 0,
+                        })
+                      }
+                    />
+                  </div>
+                  <div className="space-y-2">
+                    <Label htmlFor="temperature-increment">Fallback Step</Label>
+                    <Input
+                      id="temperature-increment"
+                      type="number"
+                      min="0"
+                      max="1"
+                      step="0.1"
+                      value={decoding.temperature_increment}
+                      onChange={(e) =>
+                        updateDecoding({
+                          temperature_increment:
+                            parseFloat(e.target.value) || 0,
+                        })
+                      }
+                    />
+                  </div>
+                  <div className="space-y-2">
+                    <Label htmlFor="no-speech-threshold">No-Speech Threshold</Label>
+                    <Input
+                      id="no-speech-threshold"
+                      type="number"
+                      min="0"
+                      max="1"
+                      step="0.05"
+                      value={decoding.no_speech_threshold}
+                      onChange={(e) =>
+                        updateDecoding({
+                          no_speech_threshold:
+                            parseFloat(e.target.value) || 0,
+                        })
+                      }
+                    />
+                  </div>
+                  <div className="space-y-2">
+                    <Label htmlFor="threads">Threads</Label>
+                    <Input
+                      id="threads"
+                      type="number"
+                      min="1"
+                      placeholder="All cores"
+                      value={decoding.threads ?? ""}
+                      onChange={(e) =>
+                        updateDecoding({
+                          threads: parseInt(e.target.value) || null,
+                        })
+                      }
+                    />
+                  </div>
+                  <div className="space-y-2">
+                    <Label htmlFor="max-segment-length">
+                      Max Segment Length
+                    </Label>
+                    <Input
+                      id="max-segment-length"
+                      type="number"
+                      min="0"
+                      value={decoding.max_segment_length}
+                      onChange={(e) =>
+                        updateDecoding({
+                          max_segment_length: parseInt(e.target.value) || 0,
+                        })
+                      }
+                    />
+                  </div>
+                  <div className="flex items-center justify-between pt-6">
+                    <Label htmlFor="suppress-blank">Suppress Blank</Label>
+                    <Switch
+                      id="suppress-blank"
+                      checked={decoding.suppress_blank}
+                      onCheckedChange={(suppress_blank) =>
+                        updateDecoding({ suppress_blank })
+                      }
+                    />
+                  </div>
+                </div>
+                <p className="text-xs text-muted-foreground">
+                  Temperatures and the no-speech threshold range from 0 to 1; a
+                  max segment length of 0 means no limit
+                </p>
+              </CardContent>
+            </Card>
+
+            <Card>
+              <CardContent className="space-y-4">
+                <div className="space-y-2">
+                  <Label htmlFor="vocabulary-term">Vocabulary</Label>
+                  <div className="flex gap-2">
+                    <Input
+                      id="vocabulary-term"
+                      placeholder="Name or term"
+                      value={newTerm}
+                      onChange={(e) => setNewTerm(e.target.value)}
+                      onKeyDown={(e) => {
+                        if (e.key === "Enter") addTerm();
+                      }}
+                    />
+                    <Button
+                      variant="outline"
+                      size="icon"
+                      onClick={addTerm}
+                      title="Add Term"
+                    >
+                      <Plus className="h-4 w-4" />
+                    </Button>
+                  </div>
+                  {vocabulary.length > 0 && (
+                    <div className="flex flex-wrap gap-1">
+                      {vocabulary.map((term) => (
+                        <Badge key={term} variant="secondary" className="gap-1">
+                          {term}
+                          <button
+                            onClick={() =>
+                              setLocalSettings({
+                                ...localSettings,
+                                vocabulary: vocabulary.filter(
+                                  (t) => t !== term
+                                ),
+                              })
+                            }
+                            title={`Remove ${term}`}
+                          >
+                            <X className="h-3 w-3" />
+                          </button>
+                        </Badge>
+                      ))}
+                    </div>
+                  )}
+                  <p className="text-xs text-muted-foreground">
+                    Names and jargon Whisper should expect
+                  </p>
+                </div>
+
+                <div className="space-y-2">
+                  <div className="flex items-center justify-between">
+                    <Label>Replacements</Label>
+                    <Button
+                      variant="outline"
+                      size="sm"
+                      onClick={() =>
+                        setLocalSettings({
+                          ...localSettings,
+                          replacements: [
+                            ...replacements,
+                            { pattern: "", replacement: "", regex: false },
+                          ],
+                        })
+                      }
+                    >
+                      <Plus className="mr-2 h-3 w-3" /> Add
+                    </Button>
+                  </div>
+                  {replacements.map((replacement, index) => (
+                    <div key={index} className="flex items-center gap-2">
+                      <Input
+                        placeholder="Heard"
+                        value={replacement.pattern}
+                        onChange={(e) =>
+                          updateReplacement(index, { pattern: e.target.value })
+                        }
+                      />
+                      <Input
+                        placeholder="Written"
+                        value={replacement.replacement}
+                        onChange={(e) =>
+                          updateReplacement(index, {
+                            replacement: e.target.value,
+                          })
+                        }
+                      />
+                      <Switch
+                        checked={replacement.regex}
+                        onCheckedChange={(regex) =>
+                          updateReplacement(index, { regex })
+                        }
+                        title="Regular Expression"
+                      />
+                      <Button
+                        variant="ghost"
+                        size="icon"
+                        onClick={() =>
+                          setLocalSettings({
+                            ...localSettings,
+                            replacements: replacements.filter(
+                              (_, i) => i !== index
+                            ),
+                          })
+                        }
+                        title="Remove Replacement"
+                      >
+                        <X className="h-4 w-4" />
+                      </Button>
+                    </div>
+                  ))}
+                  <p className="text-xs text-muted-foreground">
+                    Applied to every transcript in order; the switch treats the
+                    pattern as a regular expression
+                  </p>
+                </div>
+
+                <div className="flex gap-2">
+                  <Button
+                    variant="outline"
+                    size="sm"
+                    onClick={importVocabulary}
+                  >
+                    <Upload className="mr-2 h-3 w-3" /> Import
+                  </Button>
+                  <Button
+                    variant="outline"
+                    size="sm"
+                    onClick={exportVocabulary}
+                    disabled={hasChanges}
+                    title={hasChanges ? "Save changes before exporting" : ""}
+                  >
+                    <Download className="mr-2 h-3 w-3" /> Export
+                  </Button>
+                </div>
+              </CardContent>
+            </Card>
+
+            <Card>
+              <CardContent className="space-y-4">
+                <div className="flex items-center justify-between">
+                  <Label>Outputs</Label>
+                  <Select
+                    value=""
+                    onValueChange={(type) =>
+                      setOutputs([
+                        ...outputs,
+                        NEW_OUTPUTS[type as OutputSinkConfig["type"]],
+                      ])
+                    }
+                  >
+                    <SelectTrigger className="w-32" size="sm">
+                      <Plus className="h-3 w-3" />
+                      <SelectValue placeholder="Add" />
+                    </SelectTrigger>
+                    <SelectContent>
+                      <SelectItem value="clipboard">Clipboard</SelectItem>
+                      <SelectItem value="file">File</SelectItem>
+                      <SelectItem value="webhook">Webhook</SelectItem>
+                      <SelectItem value="command">Command</SelectItem>
+                    </SelectContent>
+                  </Select>
+                </div>
+                {outputs.length === 0 && (
+                  <p className="text-xs text-muted-foreground">
+                    Results are only saved to history
+                  </p>
+                )}
+                {outputs.map((output, index) => (
+                  <div key={index} className="space-y-1">
+                    <div className="flex items-center gap-2">
+                      <span className="text-sm w-20 shrink-0">
+                        {output.type === "clipboard"
+                          ? "Clipboard"
+                          : output.type === "file"
+                            ? "File"
+                            : output.type === "webhook"
+                              ? "Webhook"
+                              : "Command"}
+                      </span>
+                      {output.type === "file" && (
+                        <Select
+                          value={output.format}
+                          onValueChange={(format) =>
+                            updateOutput(index, {
+                              ...output,
+                              format: format as typeof output.format,
+                            })
+                          }
+                        >
+                          <SelectTrigger className="flex-1">
+                            <SelectValue />
+                          </SelectTrigger>
+                          <SelectContent>
+                            <SelectItem value="markdown">
+                              Append to File
+                            </SelectItem>
+                            <SelectItem value="daily_notes">
+                              Daily Notes Folder
+                            </SelectItem>
+                          </SelectContent>
+                        </Select>
+                      )}
+                      {output.type === "webhook" && (
+                        <Input
+                          placeholder="https://example.com/hook"
+                          value={output.url}
+                          onChange={(e) =>
+                            updateOutput(index, {
+                              ...output,
+                              url: e.target.value,
+                            })
+                          }
+                        />
+                      )}
+                      {output.type === "command" && (
+                        <Input
+                          placeholder="Command reading the text on stdin"
+                          value={output.command}
+                          onChange={(e) =>
+                            updateOutput(index, {
+                              ...output,
+                              command: e.target.value,
+                            })
+                          }
+                        />
+                      )}
+                      <Button
+                        variant="ghost"
+                        size="icon"
+                        className="ml-auto shrink-0"
+                        onClick={() =>
+                          setOutputs(outputs.filter((_, i) => i !== index))
+                        }
+                        title="Remove Output"
+                      >
+                        <X className="h-4 w-4" />
+                      </Button>
+                    </div>
+                    {output.type === "file" && (
+                      <Input
+                        placeholder={
+                          output.format === "daily_notes"
+                            ? "/path/to/notes"
+                            : "/path/to/results.md"
+                        }
+                        value={output.path}
+                        onChange={(e) =>
+                          updateOutput(index, {
+                            ...output,
+                            path: e.target.value,
+                          })
+                        }
+                      />
+                    )}
+                  </div>
+                ))}
+                <p className="text-xs text-muted-foreground">
+                  Templates with their own outputs override this list
+                </p>
+              </CardContent>
+            </Card>
+
+            <Card>
+              <CardContent className="space-y-4">
+                <div className="flex items-center justify-between">
+                  <Label htmlFor="auto-paste">Paste into Active Window</Label>
+                  <Switch
+                    id="auto-paste"
+                    checked={autoPaste.enabled}
+                    onCheckedChange={(enabled) => updateAutoPaste({ enabled })}
+                  />
+                </div>
+                {autoPaste.enabled && (
+                  <>
+                    <div className="space-y-2">
+                      <Label htmlFor="auto-paste-method">Method</Label>
+                      <Select
+                        value={autoPaste.method}
+                        onValueChange={(val) =>
+                          updateAutoPaste({
+                            method: val as AutoPasteSettings["method"],
+                          })
+                        }
+                      >
+                        <SelectTrigger id="auto-paste-method" className="w-69">
+                          <SelectValue />
+                        </SelectTrigger>
+                        <SelectContent className="w-69">
+                          <SelectItem value="paste">Paste Shortcut</SelectItem>
+                          <SelectItem value="type">Type Keystrokes</SelectItem>
+                        </SelectContent>
+                      </Select>
+                    </div>
+
+                    <div className="space-y-2">
+                      <Label htmlFor="auto-paste-backend">Linux Input Tool</Label>
+                      <Select
+                        value={autoPaste.linux_backend}
+                        onValueChange={(val) =>
+                          updateAutoPaste({
+                            linux_backend:
+                              val as AutoPasteSettings["linux_backend"],
+                          })
+                        }
+                      >
+                        <SelectTrigger id="auto-paste-backend" className="w-69">
+                          <SelectValue />
+                        </SelectTrigger>
+                        <SelectContent className="w-69">
+                          <SelectItem value="auto">Automatic</SelectItem>
+                          <SelectItem value="xdotool">xdotool (X11)</SelectItem>
+                          <SelectItem value="wtype">wtype (Wayland)</SelectItem>
+                          <SelectItem value="ydotool">ydotool</SelectItem>
+                        </SelectContent>
+                      </Select>
+                      <p className="text-xs text-muted-foreground">
+                        Ignored on macOS and Windows
+                      </p>
+                    </div>
+
+                    <div className="space-y-2">
+                      <Label htmlFor="auto-paste-delay">Delay (ms)</Label>
+                      <Input
+                        id="auto-paste-delay"
+                        type="number"
+                        min="0"
+                        value={autoPaste.delay_ms}
+                        onChange={(e) =>
+                          updateAutoPaste({
+                            delay_ms: Math.max(0, parseInt(e.target.value) || 0),
+                          })
+                        }
+                      />
+                      <p className="text-xs text-muted-foreground">
+                        Wait for the hotkey to be released before pasting
+                      </p>
+                    </div>
+
+                    <div className="flex items-center justify-between">
+                      <Label htmlFor="auto-paste-restore">
+                        Restore Clipboard
+                      </Label>
+                      <Switch
+                        id="auto-paste-restore"
+                        checked={autoPaste.restore_clipboard}
+                        onCheckedChange={(restore_clipboard) =>
+                          updateAutoPaste({ restore_clipboard })
+                        }
+                      />
+                    </div>
+                  </>
+                )}
+              </CardContent>
+            </Card>
+
+            <Card>
+              <CardContent className="space-y-4">
+                <div className="flex items-center justify-between">
+                  <Label htmlFor="sounds-muted">Mute Sounds</Label>
+                  <Switch
+                    id="sounds-muted"
+                    checked={sounds.muted}
+                    onCheckedChange={(muted) => updateSounds({ muted })}
+                  />
+                </div>
+
+                <div className="space-y-2">
+                  <Label htmlFor="sound-theme">Sound Theme</Label>
+                  <Select
+                    value={sounds.theme}
+                    onValueChange={(theme) =>
+                      updateSounds({ theme: theme as SoundSettings["theme"] })
+                    }
+                  >
+                    <SelectTrigger id="sound-theme" className="w-69">
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent className="w-69">
+                      <SelectItem value="tones">Tones</SelectItem>
+                      <SelectItem value="chimes">Chimes</SelectItem>
+                    </SelectContent>
+                  </Select>
+                </div>
+
+                <div className="space-y-2">
+                  <Label htmlFor="sound-volume">
+                    Volume ({Math.round(sounds.volume * 100)}%)
+                  </Label>
+                  <Input
+                    id="sound-volume"
+                    type="range"
+                    min="0"
+                    max="100"
+                    value={Math.round(sounds.volume * 100)}
+                    onChange={(e) =>
+                      updateSounds({ volume: parseInt(e.target.value) / 100 })
+                    }
+                  />
+                </div>
+
+                <div className="space-y-2">
+                  <Label htmlFor="output-device">Output Device</Label>
+                  <div className="flex gap-2">
+                    <Select
+                      value={sounds.output_device ?? "default"}
+                      onValueChange={(val) =>
+                        updateSounds({
+                          output_device: val === "default" ? null : val,
+                        })
+                      }
+                    >
+                      <SelectTrigger id="output-device" className="w-69">
+                        <SelectValue placeholder="Select output device" />
+                      </SelectTrigger>
+                      <SelectContent className="w-69">
+                        <SelectItem value="default">
+                          Default System Device
+                        </SelectItem>
+                        {outputDevices.map((device) => (
+                          <SelectItem key={device} value={device}>
+                            {device}
+                          </SelectItem>
+                        ))}
+                      </SelectContent>
+                    </Select>
+                    <Button
+                      variant="outline"
+                      size="icon"
+                      onClick={fetchOutputDevices}
+                      title="Refresh Output Devices"
+                    >
+                      <RefreshCw className="h-4 w-4" />
+                    </Button>
+                  </div>
+                </div>
+
+                <div className="space-y-2">
+                  <Label>Cues</Label>
+                  {CUES.map(({ cue, label }) => {
+                    const sound = sounds.cues[cue];
+                    return (
+                      <div key={cue} className="space-y-1">
+                        <div className="flex items-center gap-2">
+                          <span className="text-sm w-24">{label}</span>
+                          <Select
+                            value={sound?.kind ?? "theme"}
+                            onValueChange={(kind) => {
+                              // Custom sounds from the settings file stay as they are
+                              if (kind === sound?.kind) return;
+                              setCueSound(
+                                cue,
+                                kind === "silent"
+                                  ? { kind: "silent" }
+                                  : kind === "file"
+                                    ? { kind: "file", path: "" }
+                                    : null
+                              );
+                            }}
+                          >
+                            <SelectTrigger className="flex-1">
+                              <SelectValue />
+                            </SelectTrigger>
+                            <SelectContent>
+                              <SelectItem value="theme">Theme</SelectItem>
+                              <SelectItem value="silent">Silent</SelectItem>
+                              <SelectItem value="file">Audio File</SelectItem>
+                              {sound &&
+                                (sound.kind === "tones" ||
+                                  sound.kind === "bundled") && (
+                                  <SelectItem value={sound.kind}>
+                                    Custom
+                                  </SelectItem>
+                                )}
+                            </SelectContent>
+                          </Select>
+                          <Button
+                            variant="outline"
+                            size="icon"
+                            onClick={() => playTestSound(cue)}
+                            title={`Play ${label} Sound`}
+                          >
+                            <Play className="h-4 w-4" />
+                          </Button>
+                        </div>
+                        {sound?.kind === "file" && (
+                          <Input
+                            placeholder="/path/to/sound.wav"
+                            value={sound.path}
+                            onChange={(e) =>
+                              setCueSound(cue, {
+                                kind: "file",
+                                path: e.target.value,
+                              })
+                            }
+                          />
+                        )}
+                      </div>
+                    );
+                  })}
+                  <p className="text-xs text-muted-foreground">
+                    Audio files can be WAV, FLAC, MP3 or Ogg Vorbis
+                  </p>
+                </div>
               </CardContent>
             </Card>
           </div>
@@ -3,8 +3,20 @@
 import React, { useEffect, useState } from "react";
 import { Card, CardContent } from "@/components/ui/card";
 import { Button } from "@/components/ui/button";
-import { useAppStore } from "@/lib/store";
-import { Mic, Brain, CheckCircle2 } from "lucide-react";
+import { Input } from "@/components/ui/input";
+import {
+  useAppStore,
+  type AppError,
+  type OutputFailure,
+  type OutputSinkName,
+  type PipelineProgress,
+  type PipelineResult,
+  type PipelineStage,
+  type RecordingInterrupted,
+  type TimeoutWarning,
+} from "@/lib/store";
+import { Mic, Brain, CheckCircle2, FileAudio } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { toast } from "sonner";
 import {
@@ -18,11 +30,76 @@ import {
   AlertDialogTitle,
 } from "@/components/ui/alert-dialog";
 
+const STAGE_LABELS: Record<PipelineStage, string> = {
+  model_check: "Checking AI model",
+  model_load: "Loading AI model",
+  transcribe_instruction: "Transcribing instruction",
+  transcribe_content: "Transcribing content",
+  enrich: "Enriching",
+  output: "Delivering result",
+  save: "Saving",
+};
+
+/** "copied to clipboard and saved to file" */
+function describeOutputs(outputs: OutputSinkName[]): string {
+  const autoPaste = useAppStore.getState().settings.auto_paste?.enabled;
+  const phrases = outputs.map((output) => {
+    switch (output) {
+      case "clipboard":
+        return autoPaste
+          ? "pasted into the active window"
+          : "copied to clipboard";
+      case "file":
+        return "saved to file";
+      case "webhook":
+        return "sent to webhook";
+      case "command":
+        return "passed to command";
+    }
+  });
+  const unique = phrases.filter((p, i) => phrases.indexOf(p) === i);
+  return unique.length > 1
+    ? `${unique.slice(0, -1).join(", ")} and ${unique[unique.length - 1]}`
+    : unique[0];
+}
+
 export function State() {
-  const { status, fetchHistory, fetchStatus, triggerAction, stopPipeline } =
-    useAppStore();
+  const {
+    status,
+    fetchHistory,
+    fetchStatus,
+    triggerAction,
+    togglePause,
+    paused,
+    stopPipeline,
+    queuedJobs,
+  } = useAppStore();
   const [statusMessage, setStatusMessage] = React.useState<string>("");
   const [showStopDialog, setShowStopDialog] = useState(false);
+  const [importInstruction, setImportInstruction] = useState("");
+
+  const handleImport = async () => {
+    const instruction = importInstruction.trim();
+    try {
+      // Shows a file picker; the file is processed after the queued sessions
+      const path = await invoke<string | null>("import_audio_file", {
+        mode: instruction ? "enrich" : "transcribe",
+        instruction: instruction || null,
+      });
+      if (path) {
+        toast.info("Import Queued", {
+          description: path.split(/[\\/]/).pop(),
+          duration: 3000,
+        });
+        setImportInstruction("");
+      }
+    } catch (error: any) {
+      toast.error("Import Failed", {
+        description: error?.message ?? String(error),
+        duration: 5000,
+      });
+    }
+  };
 
   const handleStopProcessing = async () => {
     await stopPipeline();
@@ -39,8 +116,13 @@ export function State() {
     let unlistenStatus: (() => void) | undefined;
     let unlistenError: (() => void) | undefined;
     let unlistenPipelineStatus: (() => void) | undefined;
+    let unlistenPipelineProgress: (() => void) | undefined;
     let unlistenPipelineComplete: (() => void) | undefined;
+    let unlistenOutputFailed: (() => void) | undefined;
     let unlistenRecordingTimeout: (() => void) | undefined;
+    let unlistenTimeoutWarning: (() => void) | undefined;
+    let unlistenRecordingError: (() => void) | undefined;
+    let unlistenRecordingInterrupted: (() => void) | undefined;
 
     async function setupListeners() {
       unlistenStatus = await listen("status-changed", (event) => {
@@ -49,24 +131,62 @@ export function State() {
         setStatusMessage("");
       });
 
-      unlistenError = await listen("pipeline-error", (event) => {
+      unlistenError = await listen<AppError>("pipeline-error", (event) => {
         console.error("Pipeline error:", event.payload);
         toast.error("Pipeline Error", {
-          description: event.payload as string,
+          description: event.payload.message,
           duration: 5000,
         });
         setStatusMessage("");
       });
 
-      unlistenPipelineComplete = await listen("pipeline-complete", (event) => {
-        console.log("Pipeline complete:", event.payload);
-        toast.success("Success!", {
-          description: "Content has been enriched and copied to clipboard",
-          duration: 3000,
-        });
-        fetchHistory();
-        setStatusMessage("");
-      });
+      unlistenPipelineProgress = await listen<PipelineProgress>(
+        "pipeline-progress",
+        (event) => {
+          const { stage, percent, total_elapsed_ms } = event.payload;
+          const label = STAGE_LABELS[stage];
+          const seconds = Math.floor(total_elapsed_ms / 1000);
+          setStatusMessage(
+            percent !== null
+              ? `${label}... ${percent}% (${seconds}s)`
+              : `${label}... (${seconds}s)`
+          );
+        }
+      );
+
+      unlistenPipelineComplete = await listen<PipelineResult>(
+        "pipeline-complete",
+        (event) => {
+          console.log("Pipeline complete:", event.payload);
+          const { outputs } = event.payload;
+          if (outputs.length > 0) {
+            toast.success("Success!", {
+              description: `Result ${describeOutputs(outputs)}`,
+              duration: 3000,
+            });
+          } else {
+            toast.warning("Result Saved to History", {
+              description: "No output received the result",
+              duration: 5000,
+            });
+          }
+          fetchHistory();
+          setStatusMessage("");
+        }
+      );
+
+      unlistenOutputFailed = await listen<OutputFailure>(
+        "output-failed",
+        (event) => {
+          console.error("Output failed:", event.payload);
+          toast.error(`Output Failed (${event.payload.sink})`, {
+            description: event.payload.details
+              ? `${event.payload.message} (${event.payload.details})`
+              : event.payload.message,
+            duration: 5000,
+          });
+        }
+      );
 
       unlistenRecordingTimeout = await listen("recording-timeout", (event) => {
         console.warn("Recording timeout:", event.payload);
@@ -77,6 +197,56 @@ export function State() {
         // Fetch status from JSON instead of setting directly
         fetchStatus();
       });
+
+      unlistenTimeoutWarning = await listen<TimeoutWarning>(
+        "recording-timeout-warning",
+        (event) => {
+          const { recording, seconds_left, advance_on_timeout } =
+            event.payload;
+          toast.warning("Recording Almost Over", {
+            description: advance_on_timeout
+              ? recording === "instruction"
+                ? `Recording content in ${seconds_left} seconds`
+                : `Processing in ${seconds_left} seconds`
+              : `Recording will be discarded in ${seconds_left} seconds`,
+            duration: 5000,
+          });
+        }
+      );
+
+      unlistenRecordingError = await listen<AppError>(
+        "recording-error",
+        (event) => {
+          console.error("Recording error:", event.payload);
+          toast.error("Recording Failed", {
+            description: event.payload.details
+              ? `${event.payload.message} (${event.payload.details})`
+              : event.payload.message,
+            duration: 5000,
+          });
+          fetchStatus();
+        }
+      );
+
+      unlistenRecordingInterrupted = await listen<RecordingInterrupted>(
+        "recording-interrupted",
+        (event) => {
+          const { device, error, resumed_on } = event.payload;
+          console.warn("Recording interrupted:", event.payload);
+          if (resumed_on) {
+            toast.warning("Microphone Changed", {
+              description: `${device} stopped working (${error}), continuing on ${resumed_on}`,
+              duration: 5000,
+            });
+          } else {
+            toast.error("Recording Interrupted", {
+              description: `${device} stopped working (${error})`,
+              duration: 5000,
+            });
+          }
+          fetchStatus();
+        }
+      );
     }
     setupListeners();
 
@@ -89,8 +259,13 @@ export function State() {
     return () => {
       if (unlistenStatus) unlistenStatus();
       if (unlistenError) unlistenError();
+      if (unlistenPipelineProgress) unlistenPipelineProgress();
       if (unlistenPipelineComplete) unlistenPipelineComplete();
+      if (unlistenOutputFailed) unlistenOutputFailed();
       if (unlistenRecordingTimeout) unlistenRecordingTimeout();
+      if (unlistenTimeoutWarning) unlistenTimeoutWarning();
+      if (unlistenRecordingError) unlistenRecordingError();
+      if (unlistenRecordingInterrupted) unlistenRecordingInterrupted();
       window.removeEventListener("focus", handleFocus);
     };
   }, []);
@@ -155,7 +330,7 @@ export function State() {
             {getStatusIcon(status)}
           </div>
           <h2 className="text-xl font-semibold tracking-tight">
-            {getStatusText(status)}
+            {paused ? "Paused" : getStatusText(status)}
           </h2>
           {statusMessage && (
             <p className="text-sm font-medium text-foreground">
@@ -183,15 +358,45 @@ export function State() {
                   : "Continue Processing"}
             </Button>
           )}
-          {status === "processing" && (
-            <Button
-              onClick={() => setShowStopDialog(true)}
-              size="lg"
-              variant="destructive"
-            >
-              Stop Session
+          {(status === "instruction" || status === "content") && (
+            <Button onClick={togglePause} size="sm" variant="outline">
+              {paused ? "Resume" : "Pause"}
             </Button>
           )}
+          {queuedJobs > 0 && (
+            <div className="flex flex-col items-center space-y-2">
+              <p className="text-sm text-muted-foreground">
+                Processing {queuedJobs} {queuedJobs === 1 ? "session" : "sessions"}.
+                The result will be copied to your clipboard.
+              </p>
+              <Button
+                onClick={() => setShowStopDialog(true)}
+                size="sm"
+                variant="destructive"
+              >
+                Stop Session
+              </Button>
+            </div>
+          )}
+        </CardContent>
+      </Card>
+
+      <Card>
+        <CardContent className="space-y-2">
+          <div className="flex gap-2">
+            <Input
+              placeholder="Instruction (empty to only transcribe)"
+              value={importInstruction}
+              onChange={(e) => setImportInstruction(e.target.value)}
+            />
+            <Button onClick={handleImport} variant="outline">
+              <FileAudio className="mr-2 h-4 w-4" /> Import
+            </Button>
+          </div>
+          <p className="text-xs text-muted-foreground">
+            Transcribes a WAV, FLAC, MP3 or Ogg file, enriched with the
+            instruction if one is given
+          </p>
         </CardContent>
       </Card>
 
@@ -5,7 +5,132 @@ export interface Settings {
     model: string;
     microphone: string;
     hotkey: string;
+    /** Pauses and resumes the current recording; null when unset */
+    pause_hotkey?: string | null;
     recording_timeout_minutes: number;
+    /** Seconds before the timeout at which a warning is shown; 0 disables it */
+    timeout_warning_seconds?: number;
+    /** Keep a recording that reaches the timeout and move on instead of discarding it */
+    advance_on_timeout?: boolean;
+    audio_processing?: AudioProcessingSettings;
+    /** Whisper language code (e.g. "en", "de") or "auto" for detection */
+    transcription_language?: string;
+    whisper_task?: 'transcribe' | 'translate';
+    decoding?: DecodingSettings;
+    /** Terms that bias recognition towards names and jargon */
+    vocabulary?: string[];
+    /** Corrections applied to every transcript, in order */
+    replacements?: Replacement[];
+    auto_paste?: AutoPasteSettings;
+    /** Where results are delivered */
+    outputs?: OutputSinkConfig[];
+    sounds?: SoundSettings;
+}
+
+/** Pasting or typing the result into the focused application */
+export interface AutoPasteSettings {
+    enabled: boolean;
+    method: 'paste' | 'type';
+    /** Input tool used on Linux */
+    linux_backend: 'auto' | 'xdotool' | 'wtype' | 'ydotool';
+    restore_clipboard: boolean;
+    /** Wait before sending keystrokes, so the hotkey's modifiers are released */
+    delay_ms: number;
+}
+
+export interface Replacement {
+    pattern: string;
+    replacement: string;
+    /** Treat the pattern as a regular expression instead of literal, case-insensitive text */
+    regex: boolean;
+}
+
+/** Whisper decoder parameters */
+export interface DecodingSettings {
+    strategy: 'greedy' | 'beam_search';
+    /** 1-10, used by the greedy strategy */
+    best_of: number;
+    /** 1-16, used by beam search */
+    beam_size: number;
+    /** 0 to 1 */
+    temperature: number;
+    /** 0 to 1; 0 disables the temperature fallback */
+    temperature_increment: number;
+    /** null uses all available cores */
+    threads: number | null;
+    /** 0 to 1 */
+    no_speech_threshold: number;
+    suppress_blank: boolean;
+    /** Characters per segment; 0 means no limit */
+    max_segment_length: number;
+}
+
+/** Filters applied to recordings before they are saved and transcribed */
+export interface AudioProcessingSettings {
+    enabled: boolean;
+    dc_removal: boolean;
+    high_pass_enabled: boolean;
+    high_pass_cutoff_hz: number;
+    normalization_enabled: boolean;
+    normalization_target_dbfs: number;
+    normalization_max_gain_db: number;
+    noise_gate_enabled: boolean;
+    noise_gate_threshold_dbfs: number;
+}
+
+/** Moment the app signals with a sound */
+export type Cue = 'start' | 'advance' | 'processing' | 'success' | 'error' | 'timeout';
+
+/** What a cue plays */
+export type CueSound =
+    | { kind: 'tones'; tones: { frequency_hz: number; duration_ms: number }[] }
+    | { kind: 'bundled'; name: string }
+    | { kind: 'file'; path: string }
+    | { kind: 'silent' };
+
+export interface SoundSettings {
+    theme: 'tones' | 'chimes';
+    /** Sounds that replace the theme's for single cues */
+    cues: Partial<Record<Cue, CueSound>>;
+    /** 0 to 1 */
+    volume: number;
+    muted: boolean;
+    /** Output device; null plays on the default device */
+    output_device: string | null;
+}
+
+/** Result of `get_current_status` and payload of `status-changed` */
+export interface StatusSnapshot {
+    recording: 'idle' | 'instruction' | 'content';
+    paused: boolean;
+    queued_jobs: number;
+    active_session: string | null;
+}
+
+/** Error returned by commands and sent with `pipeline-error` */
+export interface AppError {
+    code: string;
+    message: string;
+    details: string | null;
+}
+
+/** Payload of `recording-interrupted` */
+export interface RecordingInterrupted {
+    session_id: string | null;
+    recording: "idle" | "instruction" | "content";
+    device: string;
+    error: string;
+    at_ms: number;
+    resumed_on: string | null;
+    gap_ms: number;
+}
+
+/** Payload of `recording-timeout-warning` */
+export interface TimeoutWarning {
+    session_id: string | null;
+    recording: "idle" | "instruction" | "content";
+    seconds_left: number;
+    advance_on_timeout: boolean;
 }
 
 export interface HistoryItem {
@@ -14,6 +139,105 @@ export interface HistoryItem {
     instruction: string;
     original_content: string;
     enriched_content: string;
+    source_file?: string;
+    /** Timed transcripts; absent for items stored by older versions */
+    instruction_transcript?: Transcript;
+    content_transcript?: Transcript;
+    stage_timings?: StageTiming[];
+}
+
+export interface Transcript {
+    text: string;
+    language: string | null;
+    segments: { start_ms: number; end_ms: number; text: string }[];
+    confidence: number | null;
+}
+
+export type SubtitleFormat = 'srt' | 'vtt' | 'json';
+
+export type BatchJobStatus =
+    | 'pending'
+    | 'running'
+    | 'completed'
+    | 'failed'
+    | 'cancelled';
+
+/** An audio file queued by `start_batch` */
+export interface BatchJob {
+    id: string;
+    path: string;
+    file_name: string;
+    mode: 'transcribe' | 'enrich';
+    instruction: string | null;
+    status: BatchJobStatus;
+    /** 0-100 */
+    progress: number;
+    error: string | null;
+    history_id: string | null;
+    created_at: string;
+}
+
+/** Result of `get_batch_jobs` and payload of `batch-queue-changed` */
+export interface BatchQueueData {
+    jobs: BatchJob[];
+    paused: boolean;
+}
+
+/** Payload of `batch-job-progress` */
+export interface BatchJobProgress {
+    job_id: string;
+    status: BatchJobStatus;
+    stage: string;
+    progress: number;
+    error: string | null;
+}
+
+export type PipelineStage =
+    | 'model_check'
+    | 'model_load'
+    | 'transcribe_instruction'
+    | 'transcribe_content'
+    | 'enrich'
+    | 'output'
+    | 'save';
+
+export interface StageTiming {
+    stage: PipelineStage;
+    duration_ms: number;
+}
+
+/** Payload of the `pipeline-progress` event */
+/** Destination a result is delivered to */
+export type OutputSinkConfig =
+    | { type: 'clipboard' }
+    /** `markdown` appends to the file at `path`; `daily_notes` writes one note per day into the folder */
+    | { type: 'file'; path: string; format: 'markdown' | 'daily_notes' }
+    | { type: 'webhook'; url: string }
+    | { type: 'command'; command: string };
+
+export type OutputSinkName = OutputSinkConfig['type'];
+
+/** Payload of `pipeline-complete` */
+export interface PipelineResult {
+    session_id: string;
+    history_id: string;
+    enriched_content: string;
+    stage_timings: StageTiming[];
+    /** Outputs the result was delivered to */
+    outputs: OutputSinkName[];
+}
+
+/** Payload of `output-failed` */
+export interface OutputFailure extends AppError {
+    session_id: string;
+    sink: OutputSinkName;
+}
+
+export interface PipelineProgress {
+    stage: PipelineStage;
+    percent: number | null;
+    stage_elapsed_ms: number;
+    total_elapsed_ms: number;
 }
 
 interface AppState {
@@ -22,7 +246,13 @@ interface AppState {
     models: string[];
     microphones: string[];
     status: 'idle' | 'instruction' | 'content' | 'processing' | 'success';
+    /** The current recording is paused */
+    paused: boolean;
     error: string | null;
+    /** Recorded sessions waiting for or undergoing processing */
+    queuedJobs: number;
+    /** Session being processed, targeted by stopPipeline */
+    activeSession: string | null;
     
     // Actions
     fetchSettings: () => Promise<void>;
@@ -36,6 +266,7 @@ interface AppState {
     setStatus: (status: AppState['status']) => void;
     setError: (error: string | null) => void;
     triggerAction: () => Promise<void>;
+    togglePause: () => Promise<void>;
     stopPipeline: () => Promise<void>;
 }
 
@@ -50,7 +281,10 @@ export const useAppStore = create<AppState>((set, get) => ({
     models: [],
     microphones: [],
     status: 'idle',
+    paused: false,
     error: null,
+    queuedJobs: 0,
+    activeSession: null,
 
     fetchSettings: async () => {
         try {
@@ -118,8 +352,13 @@ export const useAppStore = create<AppState>((set, get) => ({
 
     fetchStatus: async () => {
         try {
-            const status = await invoke<string>('get_current_status');
-            set({ status: status as AppState['status'] });
+            const snapshot = await invoke<StatusSnapshot>('get_current_status');
+            set({
+                status: snapshot.recording,
+                paused: snapshot.paused,
+                queuedJobs: snapshot.queued_jobs,
+                activeSession: snapshot.active_session,
+            });
         } catch (error) {
             console.error('Failed to fetch status:', error);
         }
@@ -128,7 +367,6 @@ export const useAppStore = create<AppState>((set, get) => ({
     setStatus: (status) => set({ status }),
 
     setError: (error) => set({ error }),
-
     triggerAction: async () => {
         try {
             await invoke('manual_trigger');
@@ -137,9 +375,17 @@ export const useAppStore = create<AppState>((set, get) => ({
         }
     },
 
+    togglePause: async () => {
+        try {
+            await invoke('toggle_recording_pause');
+        } catch (error) {
+            console.error('Failed to pause recording:', error);
+        }
+    },
+
     stopPipeline: async () => {
         try {
-            await invoke('stop_pipeline');
+            await invoke('stop_pipeline', { sessionId: get().activeSession });
         } catch (error) {
             console.error('Failed to stop pipeline:', error);
         }